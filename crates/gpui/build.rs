@@ -299,6 +299,7 @@ mod windows {
             "monochrome_sprite",
             "subpixel_sprite",
             "polychrome_sprite",
+            "gpu_texture",
         ];
 
         let rust_binding_path = format!("{}/shaders_bytes.rs", out_dir);
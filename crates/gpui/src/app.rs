@@ -2283,6 +2283,18 @@ impl App {
         }
     }
 
+    /// Removes every rasterized glyph from the sprite atlas on all windows.
+    ///
+    /// If the current window is being updated, it will be removed from `App.windows`, you can use `current_window` to specify the current window.
+    pub fn drop_glyphs(&mut self, current_window: Option<&mut Window>) {
+        for window in self.windows.values_mut().flatten() {
+            window.drop_glyphs();
+        }
+        if let Some(window) = current_window {
+            window.drop_glyphs();
+        }
+    }
+
     /// Sets the renderer for the inspector.
     #[cfg(any(feature = "inspector", debug_assertions))]
     pub fn set_inspector_renderer(&mut self, f: crate::InspectorRenderer) {
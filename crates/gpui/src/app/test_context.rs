@@ -14,6 +14,23 @@ use std::{
     cell::RefCell, future::Future, ops::Deref, path::PathBuf, rc::Rc, sync::Arc, time::Duration,
 };
 
+/// A GPU texture painted into a test window's scene, as returned by
+/// [`VisualTestContext::painted_gpu_textures`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaintedGpuTexture {
+    /// The bounds the texture was painted into, in window coordinates.
+    pub bounds: Bounds<Pixels>,
+    /// The native handle of the painted texture, when the platform surface carries one.
+    pub native_handle: Option<isize>,
+    /// How the texture was fitted within its bounds.
+    pub object_fit: crate::ObjectFit,
+    /// The opacity the texture was painted with.
+    pub opacity: f32,
+    /// The native handle of the texture being crossfaded from, and how far the crossfade has
+    /// progressed, while painting a transition.
+    pub transition_from: Option<(Option<isize>, f32)>,
+}
+
 /// A TestAppContext is provided to tests created with `#[gpui::test]`, it provides
 /// an implementation of `Context` with additional methods that are useful in tests.
 #[derive(Clone)]
@@ -800,11 +817,56 @@ impl VisualTestContext {
         self.simulate_window_resize(self.window, size)
     }
 
+    /// Simulates the window moving to a display with a different scale factor.
+    pub fn simulate_scale_factor_change(&self, scale_factor: f32) {
+        self.test_window(self.window)
+            .simulate_scale_factor_change(scale_factor);
+        self.background_executor.run_until_parked();
+    }
+
+    /// Simulates the window being minimized, restored, or covered by other windows.
+    pub fn simulate_visibility_change(&self, visibility: crate::WindowVisibility) {
+        self.test_window(self.window)
+            .simulate_visibility_change(visibility);
+        self.background_executor.run_until_parked();
+    }
+
     /// debug_bounds returns the bounds of the element with the given selector.
     pub fn debug_bounds(&mut self, selector: &'static str) -> Option<Bounds<Pixels>> {
         self.update(|window, _| window.rendered_frame.debug_bounds.get(selector).copied())
     }
 
+    /// The GPU textures painted by the last frame, in paint order, e.g. by
+    /// [`gpu_canvas`](crate::gpu_canvas) elements.
+    pub fn painted_gpu_textures(&mut self) -> Vec<PaintedGpuTexture> {
+        self.update(|window, _| {
+            let scale_factor = window.scale_factor();
+            let mut textures = Vec::new();
+            for batch in window.rendered_frame.scene.batches(&window.segment_pool) {
+                if let crate::PrimitiveBatch::Surfaces(surfaces) = batch {
+                    textures.extend(surfaces.iter().map(|surface| PaintedGpuTexture {
+                        bounds: surface.bounds.map(|value| Pixels(value.0 / scale_factor)),
+                        native_handle: surface.source.native_handle(),
+                        object_fit: surface.object_fit,
+                        opacity: surface.opacity,
+                        transition_from: surface.transition.as_ref().map(|transition| {
+                            (transition.source.native_handle(), transition.progress)
+                        }),
+                    }));
+                }
+            }
+            textures
+        })
+    }
+
+    /// The pixels written to `texture`, one of the buffers allocated by
+    /// [`Window::create_gpu_canvas_source`], with rows tightly packed. The test platform keeps
+    /// these buffers on the CPU.
+    pub fn gpu_texture_pixels(&self, texture: &crate::GpuTextureHandle) -> Option<Vec<u8>> {
+        self.test_window(self.window)
+            .shared_texture_pixels(texture.native_handle)
+    }
+
     /// Draw an element to the window using the fiber-backed rendering pipeline.
     ///
     /// This is the preferred method for drawing elements in tests. It uses the retained
@@ -1,9 +1,27 @@
 use crate::{
-    App, Bounds, Element, ElementId, GlobalElementId, InspectorElementId, IntoElement, LayoutId,
-    ObjectFit, Pixels, Style, StyleRefinement, Styled, Window,
+    Animation, AnyElement, App, Bounds, ContentMask, CursorStyle, DevicePixels, DispatchPhase,
+    Element, ElementId, FocusHandle, GlobalElementId, Hitbox, HitboxBehavior, Hsla,
+    InspectorElementId, IntoElement, Keystroke, LayoutId, Length, MouseButton, MouseDownEvent,
+    MouseMoveEvent, MouseUpEvent, ObjectFit, Pixels, PlatformInput, Point, PresentInfo,
+    ScrollWheelEvent, Size, Style, StyleRefinement, Styled, Subscription, Task, Window, point,
+    size,
 };
+use parking_lot::Mutex;
 use refineable::Refineable;
-use std::sync::Arc;
+use std::{
+    borrow::Cow,
+    collections::VecDeque,
+    mem,
+    rc::Rc,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+// Under `--cfg loom` the source's atomics are loom's, so its tests can model check them.
+#[cfg(loom)]
+use loom::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+#[cfg(not(loom))]
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 
 /// Universal GPU texture handle for zero-copy rendering.
 ///
@@ -15,6 +33,11 @@ use std::sync::Arc;
 ///
 /// The key insight: these are all different OS-level ways to reference the
 /// SAME underlying GPU memory with the SAME RGBA8 byte format.
+///
+/// The handle is a plain number and does not own what it refers to: cloning it copies the
+/// number, and GPUI never closes it. Whoever created the handle keeps it open for as long as
+/// any source refers to it and closes it exactly once. That is also what makes the handle safe
+/// to send between threads.
 #[derive(Clone, Debug)]
 pub struct GpuTextureHandle {
     /// Platform-native handle to the shared GPU texture memory
@@ -31,10 +54,38 @@ pub struct GpuTextureHandle {
 
     /// Texture format (typically RGBA8, universal across all platforms)
     pub format: GpuTextureFormat,
+
+    /// Which corner the texture's first row starts at
+    pub origin: TextureOrigin,
+
+    /// How the samples of a YUV format are converted to RGB, ignored for RGB formats
+    pub yuv_conversion: YuvConversion,
+
+    /// How the texture's RGB values are encoded, ignored for YUV formats
+    pub color_space: ColorSpace,
+
+    /// Distinguishes textures that reuse a handle value, 0 by default. Renderers cache what they
+    /// import per handle, size, format and generation, so a producer that recreates a texture
+    /// behind the same handle value can bump this to make sure the new one is imported.
+    pub generation: u64,
+}
+
+/// Where row 0 of a GPU texture sits.
+///
+/// GPUI's convention is y-down: row 0 is the top of the displayed image, as in Direct3D and
+/// Metal. OpenGL and many Vulkan producers write row 0 at the bottom instead, and declaring
+/// [`TextureOrigin::BottomLeft`] makes GPUI flip the image when it is drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TextureOrigin {
+    /// Row 0 is the top of the image (default)
+    #[default]
+    TopLeft,
+    /// Row 0 is the bottom of the image
+    BottomLeft,
 }
 
 /// GPU texture format - universal across all platforms
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum GpuTextureFormat {
     /// 8-bit RGBA (4 bytes per pixel) - most common
     RGBA8,
@@ -42,6 +93,266 @@ pub enum GpuTextureFormat {
     BGRA8,
     /// 16-bit float RGBA (8 bytes per pixel) - HDR
     RGBA16F,
+    /// 8-bit 4:2:0 YUV (1.5 bytes per pixel) - a full-resolution luma plane followed by an
+    /// interleaved half-resolution chroma plane, as output by video decoders. Converted to RGB
+    /// when drawn, according to the handle's [`YuvConversion`]. Width and height must be even.
+    Nv12,
+    /// 8-bit single channel (1 byte per pixel) - drawn as the coverage of a solid color rather
+    /// than as color, see [`GpuTextureOptions::mask_color`]
+    R8,
+}
+
+impl GpuTextureFormat {
+    /// Every format a [`GpuTextureHandle`] can declare.
+    pub const ALL: [GpuTextureFormat; 5] = [
+        GpuTextureFormat::RGBA8,
+        GpuTextureFormat::BGRA8,
+        GpuTextureFormat::RGBA16F,
+        GpuTextureFormat::Nv12,
+        GpuTextureFormat::R8,
+    ];
+
+    /// Whether textures of this format store YUV rather than RGB samples.
+    pub fn is_yuv(&self) -> bool {
+        matches!(self, GpuTextureFormat::Nv12)
+    }
+}
+
+/// The matrix relating a YUV texture's samples to RGB.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum YuvMatrix {
+    /// ITU-R BT.601, used by standard-definition video
+    Bt601,
+    /// ITU-R BT.709, used by high-definition video (default)
+    #[default]
+    Bt709,
+}
+
+/// The range of values a YUV texture's samples are encoded in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum YuvRange {
+    /// Luma spans 16-235 and chroma 16-240, as most video does (default)
+    #[default]
+    Limited,
+    /// Every sample spans 0-255
+    Full,
+}
+
+/// How the samples of a YUV texture, such as one in [`GpuTextureFormat::Nv12`], are converted to
+/// RGB when it is drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct YuvConversion {
+    /// The matrix relating YUV to RGB
+    pub matrix: YuvMatrix,
+    /// The range the samples are encoded in
+    pub range: YuvRange,
+}
+
+/// What the window's GPU device can do with a [`GpuTextureFormat`], as reported by
+/// [`Window::gpu_texture_format_support`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GpuTextureFormatSupport {
+    /// The format being described
+    pub format: GpuTextureFormat,
+    /// Whether textures of this format can be sampled, which painting a [`GpuCanvas`] requires
+    pub sample: bool,
+    /// Whether a producer can render into textures of this format on the same device
+    pub render_target: bool,
+    /// Whether textures of this format can be mapped for CPU reads and writes
+    pub cpu_access: bool,
+}
+
+/// GPU time spent on a texture, averaged over the frames measured since
+/// [`Window::set_gpu_timing_enabled`] turned timing on. Reported by
+/// [`Window::gpu_texture_timings`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GpuTextureTimings {
+    /// Microseconds spent drawing the texture into the window, `None` if never measured
+    pub draw_micros: Option<f64>,
+    /// Microseconds spent per write into the texture, including the copy of the displayed frame
+    /// that starts a new one, `None` if never measured
+    pub copy_micros: Option<f64>,
+}
+
+/// How the RGB values of a texture are encoded. GPUI converts them to the window's
+/// [`OutputColorSpace`] when the texture is drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    /// sRGB primaries with the sRGB transfer function applied, as most 8-bit content is
+    /// (default)
+    #[default]
+    SrgbNonLinear,
+    /// sRGB primaries with linear values in 0-1, e.g. the output of a renderer's lighting pass
+    LinearSrgb,
+    /// BT.709 video in limited range, where 16-235 spans black to white
+    Bt709Limited,
+    /// Linear values on sRGB primaries that may exceed 1.0 or go negative, as rendered for HDR
+    /// output in half-float textures
+    ScRgbLinear,
+}
+
+impl ColorSpace {
+    /// Whether values are proportional to light, which is what [`ToneMapping`] applies to.
+    pub fn is_linear(&self) -> bool {
+        matches!(self, ColorSpace::LinearSrgb | ColorSpace::ScRgbLinear)
+    }
+}
+
+/// The color space a window's swap chain outputs, reported by [`Window::output_color_space`].
+/// No platform creates HDR swap chains yet, so every window outputs SDR sRGB.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum OutputColorSpace {
+    /// Standard dynamic range sRGB, values above 1.0 are clipped (default)
+    #[default]
+    Srgb,
+}
+
+/// How the colors of a texture in a linear [`ColorSpace`], which may exceed 1.0, are mapped into
+/// the range an SDR window displays.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ToneMapping {
+    /// The curve compressing the exposed colors into range
+    pub operator: ToneMapOperator,
+    /// Factor the linear colors are multiplied by before the curve, 1.0 by default
+    pub exposure: f32,
+}
+
+impl Default for ToneMapping {
+    fn default() -> Self {
+        Self {
+            operator: ToneMapOperator::default(),
+            exposure: 1.,
+        }
+    }
+}
+
+/// The curve a [`ToneMapping`] applies.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ToneMapOperator {
+    /// Clip colors above 1.0, which keeps everything below it exact
+    Clamp,
+    /// Reinhard's `x / (1 + x)`, which compresses highlights smoothly instead of clipping them
+    /// (default)
+    #[default]
+    Reinhard,
+}
+
+/// How a GPU texture is sampled when it is drawn at a size other than its native resolution.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TextureFilter {
+    /// Bilinear filtering, smooth when scaled (default)
+    #[default]
+    Linear,
+    /// Nearest-neighbor sampling, keeps hard texel edges (e.g. pixel art)
+    Nearest,
+}
+
+/// How the alpha channel of a GPU texture is interpreted when compositing it over GPUI content.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AlphaMode {
+    /// Color channels are already multiplied by alpha (default)
+    #[default]
+    Premultiplied,
+    /// Color channels are independent of alpha and are multiplied in the shader
+    Straight,
+    /// Alpha is ignored and the texture is drawn fully opaque
+    Opaque,
+}
+
+/// How an [`AlphaMode::Opaque`] canvas responds to opacity inherited from its ancestors.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum OpaqueCanvasOpacity {
+    /// Blend with the inherited opacity while it is below 1, so the canvas fades along with its
+    /// parent, and draw opaque again once the fade completes (default)
+    #[default]
+    Fade,
+    /// Always draw fully opaque. An ancestor at zero opacity still hides the canvas.
+    Ignore,
+}
+
+/// Clockwise rotation applied to a GPU texture when it is displayed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CanvasRotation {
+    /// Displayed as stored (default)
+    #[default]
+    None,
+    /// Rotated a quarter turn clockwise
+    Cw90,
+    /// Rotated a half turn
+    Cw180,
+    /// Rotated three quarter turns clockwise
+    Cw270,
+}
+
+impl CanvasRotation {
+    /// Number of clockwise quarter turns, in `0..4`.
+    pub fn quarter_turns(&self) -> u32 {
+        match self {
+            CanvasRotation::None => 0,
+            CanvasRotation::Cw90 => 1,
+            CanvasRotation::Cw180 => 2,
+            CanvasRotation::Cw270 => 3,
+        }
+    }
+
+    /// Whether the displayed width and height are swapped relative to the texture.
+    pub fn swaps_axes(&self) -> bool {
+        matches!(self, CanvasRotation::Cw90 | CanvasRotation::Cw270)
+    }
+}
+
+/// Options controlling how [`Window::paint_gpu_texture`] draws a GPU texture.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GpuTextureOptions {
+    /// How the texture is fitted within the painted bounds
+    pub object_fit: ObjectFit,
+    /// Sampler used when the texture is scaled
+    pub filter: TextureFilter,
+    /// How the texture's alpha channel is composited
+    pub alpha_mode: AlphaMode,
+    /// Whether an opaque texture fades with inherited opacity
+    pub opacity_behavior: OpaqueCanvasOpacity,
+    /// Sub-rectangle of the texture to display, in texels measured from the top-left of the
+    /// upright image. `None` displays the whole texture.
+    pub source_rect: Option<Bounds<DevicePixels>>,
+    /// Flip the texture vertically, on top of any flip implied by the handle's [`TextureOrigin`]
+    pub flip_y: bool,
+    /// Rotation applied after cropping and flipping, before object fit
+    pub rotation: CanvasRotation,
+    /// Draw the texture at its native size, one texel per device pixel, centered in the
+    /// element. Takes precedence over `object_fit`.
+    pub pixel_perfect: bool,
+    /// Whether the texture is desaturated before it is tinted
+    pub grayscale: bool,
+    /// Color the texture is multiplied by. Its alpha scales the texture's alpha.
+    pub tint: Option<Hsla>,
+    /// Draw the texture's red channel as the coverage of this color, e.g. for a GPU-computed
+    /// heatmap or selection mask. [`GpuTextureFormat::R8`] textures are always drawn this way,
+    /// in white when no color is given.
+    pub mask_color: Option<Hsla>,
+    /// Map the colors of a texture in a linear [`ColorSpace`] into SDR range. `None` clamps
+    /// them.
+    pub tone_mapping: Option<ToneMapping>,
+}
+
+impl Default for GpuTextureOptions {
+    fn default() -> Self {
+        Self {
+            object_fit: ObjectFit::Contain,
+            filter: TextureFilter::default(),
+            alpha_mode: AlphaMode::default(),
+            opacity_behavior: OpaqueCanvasOpacity::default(),
+            source_rect: None,
+            flip_y: false,
+            rotation: CanvasRotation::None,
+            pixel_perfect: false,
+            grayscale: false,
+            tint: None,
+            mask_color: None,
+            tone_mapping: None,
+        }
+    }
 }
 
 impl GpuTextureHandle {
@@ -52,6 +363,10 @@ impl GpuTextureHandle {
             width,
             height,
             format: GpuTextureFormat::RGBA8,
+            origin: TextureOrigin::TopLeft,
+            yuv_conversion: YuvConversion::default(),
+            color_space: ColorSpace::default(),
+            generation: 0,
         }
     }
 
@@ -67,33 +382,331 @@ impl GpuTextureHandle {
             width,
             height,
             format,
+            origin: TextureOrigin::TopLeft,
+            yuv_conversion: YuvConversion::default(),
+            color_space: ColorSpace::default(),
+            generation: 0,
         }
     }
 
-    /// Get the size in bytes of a single pixel for this format
+    /// Declare how the texture's samples convert to RGB, for YUV formats. The matrix and range
+    /// are usually reported by the decoder that produced the frames.
+    pub fn with_yuv_conversion(mut self, yuv_conversion: YuvConversion) -> Self {
+        self.yuv_conversion = yuv_conversion;
+        self
+    }
+
+    /// Declare how the texture's RGB values are encoded, so that they are converted to what the
+    /// window outputs when drawn.
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    /// Set the generation distinguishing this texture from earlier ones behind the same handle
+    /// value, see [`GpuTextureHandle::generation`].
+    pub fn with_generation(mut self, generation: u64) -> Self {
+        self.generation = generation;
+        self
+    }
+
+    /// Declare which corner the texture's first row starts at.
+    pub fn with_origin(mut self, origin: TextureOrigin) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Whether the texture must be flipped vertically to display upright.
+    pub fn is_bottom_up(&self) -> bool {
+        self.origin == TextureOrigin::BottomLeft
+    }
+
+    /// Get the size in bytes of a single pixel for this format. For [`GpuTextureFormat::Nv12`]
+    /// this is the size of a luma sample; the chroma plane adds half as much again.
     pub fn bytes_per_pixel(&self) -> u32 {
         match self.format {
             GpuTextureFormat::RGBA8 => 4,
             GpuTextureFormat::BGRA8 => 4,
             GpuTextureFormat::RGBA16F => 8,
+            GpuTextureFormat::Nv12 => 1,
+            GpuTextureFormat::R8 => 1,
         }
     }
 
     /// Get the total size in bytes of the texture
     pub fn size_in_bytes(&self) -> usize {
-        (self.width * self.height * self.bytes_per_pixel()) as usize
+        let (width, height) = (self.width as usize, self.height as usize);
+        let size = width
+            .saturating_mul(height)
+            .saturating_mul(self.bytes_per_pixel() as usize);
+        match self.format {
+            // Two bytes for every 2x2 block of luma samples.
+            GpuTextureFormat::Nv12 => {
+                size.saturating_add(width.div_ceil(2).saturating_mul(height.div_ceil(2)) * 2)
+            }
+            _ => size,
+        }
+    }
+
+    /// Whether the texture has no pixels to sample, e.g. before the producer has sized it.
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Whether the handle can plausibly be opened: it refers to a native object and has a size.
+    /// A valid handle can still fail to open if the producer released it.
+    pub fn is_valid(&self) -> bool {
+        self.native_handle > 0 && !self.is_empty()
+    }
+
+    /// Get the dimensions of the texture in device pixels
+    pub fn size(&self) -> Size<DevicePixels> {
+        size(
+            DevicePixels::from(self.width),
+            DevicePixels::from(self.height),
+        )
+    }
+
+    /// Checks that `data_len` bytes, with rows starting `stride` bytes apart, cover `region` and
+    /// that `region` lies within the texture.
+    pub(crate) fn validate_pixel_write(
+        &self,
+        region: Bounds<DevicePixels>,
+        data_len: usize,
+        stride: usize,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            !self.format.is_yuv(),
+            "cannot write pixels to {:?} textures",
+            self.format
+        );
+        let texture_bounds = Bounds::new(point(DevicePixels(0), DevicePixels(0)), self.size());
+        anyhow::ensure!(
+            region.size.width.0 > 0
+                && region.size.height.0 > 0
+                && texture_bounds.contains(&region.origin)
+                && region.right() <= texture_bounds.right()
+                && region.bottom() <= texture_bounds.bottom(),
+            "{region:?} does not fit a {}x{} texture",
+            self.width,
+            self.height
+        );
+        let row_len = region.size.width.0 as usize * self.bytes_per_pixel() as usize;
+        anyhow::ensure!(
+            stride >= row_len,
+            "a stride of {stride} bytes is shorter than a {row_len} byte row"
+        );
+        let required_len = stride * (region.size.height.0 as usize - 1) + row_len;
+        anyhow::ensure!(
+            data_len >= required_len,
+            "{region:?} needs {required_len} bytes of pixel data, got {data_len}"
+        );
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl GpuTextureHandle {
+    /// Look up the IOSurface this handle's ID refers to, e.g. to render into it from OpenGL by
+    /// binding it to a `GL_TEXTURE_RECTANGLE` texture with `CGLTexImageIOSurface2D`. Returns a
+    /// retained `IOSurfaceRef` the caller releases with `CFRelease`, or `None` if no surface has
+    /// that ID.
+    pub fn lookup_io_surface(&self) -> Option<*mut std::ffi::c_void> {
+        #[link(name = "IOSurface", kind = "framework")]
+        unsafe extern "C" {
+            fn IOSurfaceLookup(id: u32) -> *mut std::ffi::c_void;
+        }
+
+        let io_surface = unsafe { IOSurfaceLookup(self.native_handle as u32) };
+        (!io_surface.is_null()).then_some(io_surface)
+    }
+}
+
+/// Converts `height` rows of `width` pixels in `src_format`, each starting `src_stride` bytes
+/// after the previous one, to `dst_format`. Returns the converted rows and their stride: tightly
+/// packed when converted, or `data` itself when the formats already match.
+pub(crate) fn convert_pixel_rows(
+    data: &[u8],
+    src_stride: usize,
+    width: usize,
+    height: usize,
+    src_format: GpuTextureFormat,
+    dst_format: GpuTextureFormat,
+) -> anyhow::Result<(Cow<'_, [u8]>, usize)> {
+    if src_format == dst_format {
+        return Ok((Cow::Borrowed(data), src_stride));
+    }
+    anyhow::ensure!(
+        matches!(
+            (src_format, dst_format),
+            (GpuTextureFormat::RGBA8, GpuTextureFormat::BGRA8)
+                | (GpuTextureFormat::BGRA8, GpuTextureFormat::RGBA8)
+        ),
+        "cannot convert {src_format:?} pixels to {dst_format:?}"
+    );
+    let row_len = width * 4;
+    anyhow::ensure!(
+        row_len > 0
+            && height > 0
+            && src_stride >= row_len
+            && data.len() >= src_stride * (height - 1) + row_len,
+        "{} bytes with a stride of {src_stride} do not hold {width}x{height} pixels",
+        data.len()
+    );
+
+    // Swapping red and blue converts either way. Fixed-size chunks let the inner loop vectorize.
+    let mut converted = vec![0; row_len * height];
+    for (dst_row, src_row) in converted
+        .chunks_exact_mut(row_len)
+        .zip(data.chunks(src_stride))
+    {
+        for (dst, src) in dst_row
+            .chunks_exact_mut(4)
+            .zip(src_row[..row_len].chunks_exact(4))
+        {
+            dst.copy_from_slice(&[src[2], src[1], src[0], src[3]]);
+        }
+    }
+    Ok((Cow::Owned(converted), row_len))
+}
+
+/// Lifecycle state a producer reports for its [`GpuCanvasSource`].
+///
+/// This lets the UI tell apart a producer that intentionally stopped presenting frames (nothing
+/// changed) from one that is expected to present but has gone silent or failed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ProducerStatus {
+    /// The producer is alive but has nothing new to present (default)
+    #[default]
+    Idle,
+    /// The producer is actively rendering and new frames are expected
+    Rendering,
+    /// The producer failed with an application-defined error code
+    Error(u32),
+}
+
+impl ProducerStatus {
+    const IDLE: u64 = 0;
+    const RENDERING: u64 = 1;
+    const ERROR: u64 = 2;
+
+    /// Whether new frames are expected, i.e. whether a lack of frames should count as stale.
+    pub fn expects_frames(&self) -> bool {
+        matches!(self, ProducerStatus::Rendering)
+    }
+
+    pub(crate) fn to_bits(self) -> u64 {
+        match self {
+            ProducerStatus::Idle => Self::IDLE,
+            ProducerStatus::Rendering => Self::RENDERING,
+            ProducerStatus::Error(code) => (Self::ERROR << 32) | code as u64,
+        }
+    }
+
+    pub(crate) fn from_bits(bits: u64) -> Self {
+        match bits >> 32 {
+            Self::ERROR => ProducerStatus::Error(bits as u32),
+            _ if bits == Self::RENDERING => ProducerStatus::Rendering,
+            _ => ProducerStatus::Idle,
+        }
     }
 }
 
-unsafe impl Send for GpuTextureHandle {}
-unsafe impl Sync for GpuTextureHandle {}
+/// How the frames a producer presented through a [`GpuCanvasSource`] reached the screen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PresentStats {
+    /// Frame index of the most recent frame GPUI has drawn, `None` before the first
+    pub last_presented_frame: Option<u64>,
+    /// Mean time from a frame being presented to GPUI first drawing it
+    pub average_present_latency: Option<Duration>,
+    /// Frames that were replaced by a newer present before GPUI drew them
+    pub skipped_frames: u64,
+}
+
+/// Metadata stamped on one of a [`GpuCanvasSource`]'s buffers when it is presented.
+#[derive(Default)]
+struct FrameMetadata {
+    frame_index: AtomicU64,
+    /// Nanoseconds since the source's epoch
+    presented_at: AtomicU64,
+}
+
+/// A frame queued with [`GpuCanvasSource::swap_buffers_at`], waiting to be displayed.
+struct PendingPresent {
+    buffer: usize,
+    /// The earliest time the frame may be displayed
+    target: Instant,
+}
+
+/// State shared between every clone of a [`GpuCanvasSource`], across producer and UI threads.
+struct GpuCanvasSourceState {
+    /// Current active buffer index (0 or 1)
+    active_buffer: AtomicUsize,
+    /// Last [`ProducerStatus`] reported by the producer, in its packed form
+    producer_status: AtomicU64,
+    /// Last producer status delivered to an `on_producer_status_changed` listener
+    observed_status: AtomicU64,
+    /// Whether the producer has published a frame since the source was created
+    presented: AtomicBool,
+    /// Last device size measured for an `on_resize` listener, packed as width and height halves,
+    /// 0 before the first measurement and while collapsed
+    observed_size: AtomicU64,
+    /// Sequence number handed out by the next call to `next_input_sequence`
+    next_input_sequence: AtomicU64,
+    /// Last input sequence the producer reported consuming in a presented frame, 0 for none
+    displayed_input_sequence: AtomicU64,
+    /// Inputs forwarded to the producer that no displayed frame reflects yet, oldest first
+    pending_inputs: Mutex<VecDeque<(u64, Instant)>>,
+    /// Incremented every time the producer presents a frame
+    content_version: AtomicU64,
+    /// Content version most recently drawn by a canvas displaying this source
+    painted_content_version: AtomicU64,
+    /// Woken when a frame is presented, one per window connected with `connect_to_window`
+    content_observers: Mutex<Vec<smol::channel::Sender<()>>>,
+    /// Reference point for the timestamps in `frames`
+    epoch: Instant,
+    /// Metadata for the frame currently held by each buffer
+    frames: [FrameMetadata; 2],
+    /// Frame index of the last frame drawn, valid once `painted_content_version` is non-zero
+    last_painted_frame: AtomicU64,
+    /// Sum and count of present-to-draw latencies, in nanoseconds
+    present_latency_total: AtomicU64,
+    present_latency_samples: AtomicU64,
+    skipped_frames: AtomicU64,
+    /// Draws that found no frame newer than the one drawn before
+    repeated_frames: AtomicU64,
+    /// Last stalled state delivered to an `on_stalled` listener
+    observed_stalled: AtomicBool,
+    /// Whether a task is waiting to redraw a canvas with a stall threshold once it stalls
+    stall_watch_running: AtomicBool,
+    /// Whether a canvas in a visible window displayed this source in its latest frame
+    consumer_active: AtomicBool,
+    /// The most recent present of a window latched with `latch_presents`
+    last_present: Mutex<Option<PresentInfo>>,
+    /// The frame queued with `swap_buffers_at`, if it has not been displayed yet
+    pending_present: Mutex<Option<PendingPresent>>,
+    /// Nanoseconds since the epoch a task will redraw at to display a pending present, 0 for none
+    present_timer_target: AtomicU64,
+    /// Content version at which the back buffer last received a copy of the active one, so CPU
+    /// writes land on top of the displayed frame
+    back_buffer_synced_version: AtomicU64,
+    /// Run once the last clone of the source is dropped, to free buffers GPUI allocated for it
+    on_release: Mutex<Option<Box<dyn FnOnce() + Send>>>,
+}
+
+impl Drop for GpuCanvasSourceState {
+    fn drop(&mut self) {
+        if let Some(on_release) = self.on_release.get_mut().take() {
+            on_release();
+        }
+    }
+}
 
 /// Double-buffered GPU texture source for flicker-free rendering.
 /// One buffer is written by the producer while the other is read by GPUI.
 #[derive(Clone)]
 pub struct GpuCanvasSource {
-    /// Current active buffer index (0 or 1)
-    active_buffer: Arc<std::sync::atomic::AtomicUsize>,
+    state: Arc<GpuCanvasSourceState>,
     /// The two shared GPU texture handles
     buffers: [GpuTextureHandle; 2],
 }
@@ -102,26 +715,566 @@ impl GpuCanvasSource {
     /// Create a new double-buffered GPU canvas source.
     pub fn new(buffer0: GpuTextureHandle, buffer1: GpuTextureHandle) -> Self {
         Self {
-            active_buffer: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+            state: Arc::new(GpuCanvasSourceState {
+                active_buffer: AtomicUsize::new(0),
+                producer_status: AtomicU64::new(ProducerStatus::Idle.to_bits()),
+                observed_status: AtomicU64::new(ProducerStatus::Idle.to_bits()),
+                observed_size: AtomicU64::new(0),
+                presented: AtomicBool::new(false),
+                next_input_sequence: AtomicU64::new(1),
+                displayed_input_sequence: AtomicU64::new(0),
+                pending_inputs: Mutex::new(VecDeque::new()),
+                content_version: AtomicU64::new(0),
+                painted_content_version: AtomicU64::new(0),
+                content_observers: Mutex::new(Vec::new()),
+                epoch: Instant::now(),
+                frames: Default::default(),
+                last_painted_frame: AtomicU64::new(0),
+                present_latency_total: AtomicU64::new(0),
+                present_latency_samples: AtomicU64::new(0),
+                skipped_frames: AtomicU64::new(0),
+                repeated_frames: AtomicU64::new(0),
+                observed_stalled: AtomicBool::new(false),
+                stall_watch_running: AtomicBool::new(false),
+                consumer_active: AtomicBool::new(false),
+                last_present: Mutex::new(None),
+                pending_present: Mutex::new(None),
+                present_timer_target: AtomicU64::new(0),
+                back_buffer_synced_version: AtomicU64::new(0),
+                on_release: Mutex::new(None),
+            }),
             buffers: [buffer0, buffer1],
         }
     }
 
+    /// Create a source for a producer that draws RGBA pixels on the CPU, e.g. a plotting widget.
+    /// Its buffers are allocated by `window`'s renderer, see
+    /// [`Window::create_gpu_canvas_source`]; draw frames with [`GpuCanvasSource::write_frame`].
+    pub fn from_cpu(window: &Window, size: Size<DevicePixels>) -> anyhow::Result<Self> {
+        window.create_gpu_canvas_source(size, GpuTextureFormat::RGBA8)
+    }
+
+    /// Upload a frame of tightly packed RGBA rows into the back buffer and display it. `window`
+    /// must be the one that allocated the buffers.
+    pub fn write_frame(&self, window: &Window, rgba: &[u8]) -> anyhow::Result<()> {
+        let stride = self.back_buffer().width as usize * 4;
+        window.write_gpu_canvas_frame(self, GpuTextureFormat::RGBA8, rgba, stride)?;
+        self.swap_buffers();
+        Ok(())
+    }
+
+    /// Like [`GpuCanvasSource::write_frame`], drawing the frame with `draw`, which is given a
+    /// cleared RGBA buffer and the number of bytes between the starts of its rows.
+    pub fn write_frame_with(
+        &self,
+        window: &Window,
+        draw: impl FnOnce(&mut [u8], usize),
+    ) -> anyhow::Result<()> {
+        let texture = self.back_buffer();
+        let row_pitch = texture.width as usize * 4;
+        let mut rgba = vec![0; row_pitch * texture.height as usize];
+        draw(&mut rgba, row_pitch);
+        self.write_frame(window, &rgba)
+    }
+
     /// Get the currently active buffer for reading.
     pub fn active_buffer(&self) -> &GpuTextureHandle {
-        let index = self.active_buffer.load(std::sync::atomic::Ordering::Acquire);
+        let index = self.state.active_buffer.load(Ordering::Acquire);
         &self.buffers[index % 2]
     }
 
+    /// Get the buffer that is not active, which the producer renders the next frame into.
+    pub fn back_buffer(&self) -> &GpuTextureHandle {
+        let index = self.state.active_buffer.load(Ordering::Acquire);
+        &self.buffers[(index % 2) ^ 1]
+    }
+
+    /// Whether the back buffer still holds an older frame than the active one. Both buffers
+    /// start out blank, so this stays false until the first present.
+    pub(crate) fn back_buffer_needs_sync(&self) -> bool {
+        self.state
+            .back_buffer_synced_version
+            .load(Ordering::Acquire)
+            != self.content_version()
+    }
+
+    /// Records that the back buffer was brought up to date with the active one at `version`.
+    pub(crate) fn mark_back_buffer_synced(&self, version: u64) {
+        self.state
+            .back_buffer_synced_version
+            .store(version, Ordering::Release);
+    }
+
     /// Swap to the other buffer (call this from the producer thread after rendering).
     pub fn swap_buffers(&self) {
-        self.active_buffer
-            .fetch_xor(1, std::sync::atomic::Ordering::Release);
+        self.present(None, None, Instant::now());
+    }
+
+    /// Like [`GpuCanvasSource::swap_buffers`], also stamping the presented frame with the
+    /// producer's own frame index, as reported by [`GpuCanvasSource::present_stats`]. Frames
+    /// presented without one are numbered by [`GpuCanvasSource::content_version`].
+    pub fn swap_buffers_with_frame_index(&self, frame_index: u64) {
+        self.present(None, Some(frame_index), Instant::now());
     }
-    
+
     /// Set the active buffer index directly (0 or 1).
     pub fn set_active_buffer(&self, index: usize) {
-        self.active_buffer.store(index % 2, std::sync::atomic::Ordering::Release);
+        self.present(Some(index % 2), None, Instant::now());
+    }
+
+    /// Like [`GpuCanvasSource::swap_buffers`], but the frame is not displayed before `target`,
+    /// e.g. a video frame's presentation time. Canvases keep showing the current frame until
+    /// they draw at or after `target`.
+    ///
+    /// The queued frame waits in the back buffer, so only one frame can be pending at a time.
+    /// Until [`GpuCanvasSource::has_pending_present`] turns false, rendering into the back
+    /// buffer overwrites the pending frame. Queuing it again replaces the pending present, and
+    /// the replaced frame counts as dropped.
+    pub fn swap_buffers_at(&self, target: Instant) {
+        let back = (self.state.active_buffer.load(Ordering::Acquire) % 2) ^ 1;
+        let replaced = self.state.pending_present.lock().replace(PendingPresent {
+            buffer: back,
+            target,
+        });
+        if replaced.is_some() {
+            self.state.skipped_frames.fetch_add(1, Ordering::Relaxed);
+        }
+        // Connected windows redraw to schedule displaying the frame.
+        self.notify_content_observers();
+    }
+
+    /// Whether a frame queued with [`GpuCanvasSource::swap_buffers_at`] has not been displayed
+    /// yet.
+    pub fn has_pending_present(&self) -> bool {
+        self.state.pending_present.lock().is_some()
+    }
+
+    /// Publish the queued frame if its target has passed by `now`. Returns the target of the
+    /// frame if it is still waiting.
+    fn promote_due_presents(&self, now: Instant) -> Option<Instant> {
+        let mut pending_present = self.state.pending_present.lock();
+        let due = pending_present.take_if(|pending| pending.target <= now);
+        let next_target = pending_present.as_ref().map(|pending| pending.target);
+        drop(pending_present);
+
+        if let Some(due) = due {
+            self.present(Some(due.buffer), None, due.target);
+        }
+        next_target
+    }
+
+    /// Redraw `window` at `target`, so a frame queued for then is displayed even if nothing
+    /// else redraws the window. Only the earliest requested redraw is kept waiting.
+    fn redraw_at(&self, target: Instant, window: &Window, cx: &App) {
+        let target_nanos = self.nanos_since_epoch(target).max(1);
+        let scheduled = self.state.present_timer_target.fetch_update(
+            Ordering::AcqRel,
+            Ordering::Acquire,
+            |scheduled| (scheduled == 0 || target_nanos < scheduled).then_some(target_nanos),
+        );
+        if scheduled.is_err() {
+            return;
+        }
+        let source = self.clone();
+        window
+            .spawn(cx, async move |cx| {
+                cx.background_executor()
+                    .timer(target.saturating_duration_since(Instant::now()))
+                    .await;
+                // A later request for an earlier redraw has replaced this one.
+                if source
+                    .state
+                    .present_timer_target
+                    .compare_exchange(target_nanos, 0, Ordering::AcqRel, Ordering::Acquire)
+                    .is_ok()
+                {
+                    cx.update(|window, _| window.refresh()).ok();
+                }
+            })
+            .detach();
+    }
+
+    /// Publish `buffer`, or the buffer that is not active when `None`. Swapping to the back
+    /// buffer is a compare-exchange, so concurrent swaps each flip the active buffer rather than
+    /// racing to publish the same one.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            skip_all,
+            fields(content_version = self.content_version() + 1, ?buffer, ?frame_index)
+        )
+    )]
+    fn present(&self, buffer: Option<usize>, frame_index: Option<u64>, presented_at: Instant) {
+        let frame_index = frame_index.unwrap_or(self.content_version() + 1);
+        let presented_at = self.nanos_since_epoch(presented_at);
+        if let Some(buffer) = buffer {
+            self.stamp_frame(buffer, frame_index, presented_at);
+            self.state.active_buffer.store(buffer, Ordering::Release);
+        } else {
+            let mut active = self.state.active_buffer.load(Ordering::Acquire);
+            loop {
+                let back = (active % 2) ^ 1;
+                self.stamp_frame(back, frame_index, presented_at);
+                match self.state.active_buffer.compare_exchange_weak(
+                    active,
+                    back,
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                ) {
+                    Ok(_) => break,
+                    Err(current) => active = current,
+                }
+            }
+        }
+
+        self.state.presented.store(true, Ordering::Release);
+        let previous_version = self.state.content_version.fetch_add(1, Ordering::AcqRel);
+        if previous_version > 0
+            && self.state.painted_content_version.load(Ordering::Acquire) != previous_version
+        {
+            self.state.skipped_frames.fetch_add(1, Ordering::Relaxed);
+        }
+        self.notify_content_observers();
+    }
+
+    fn notify_content_observers(&self) {
+        // A full channel already has a redraw pending, which will pick up this frame too.
+        self.state.content_observers.lock().retain(|observer| {
+            !matches!(
+                observer.try_send(()),
+                Err(smol::channel::TrySendError::Closed(_))
+            )
+        });
+    }
+
+    fn stamp_frame(&self, buffer: usize, frame_index: u64, presented_at: u64) {
+        let frame = &self.state.frames[buffer];
+        frame.frame_index.store(frame_index, Ordering::Relaxed);
+        frame.presented_at.store(presented_at, Ordering::Relaxed);
+    }
+
+    /// Whether a [`gpu_canvas`] in a visible window is displaying this source. It turns false when
+    /// the window is minimized or occluded, or when the canvas is no longer rendered, so a
+    /// producer can cheaply poll it to skip frames nobody would see. When several windows display
+    /// the source, this follows whichever changed most recently.
+    pub fn consumer_active(&self) -> bool {
+        self.state.consumer_active.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn set_consumer_active(&self, active: bool) {
+        self.state.consumer_active.store(active, Ordering::Release);
+    }
+
+    /// Run `on_release` once every clone of the source has been dropped, on the thread that
+    /// dropped the last one. Replaces any callback set before.
+    pub(crate) fn on_release(&self, on_release: impl FnOnce() + Send + 'static) {
+        *self.state.on_release.lock() = Some(Box::new(on_release));
+    }
+
+    /// Whether `other` is a clone of this source.
+    pub(crate) fn ptr_eq(&self, other: &GpuCanvasSource) -> bool {
+        Arc::ptr_eq(&self.state, &other.state)
+    }
+
+    /// Incremented every time the producer presents a frame, starting from 0.
+    pub fn content_version(&self) -> u64 {
+        self.state.content_version.load(Ordering::Acquire)
+    }
+
+    /// Redraw `window` whenever the producer presents a frame, so canvases showing this source
+    /// update without the app polling or calling `notify`. Frames presented before the window
+    /// gets to draw are coalesced into one redraw, and an idle producer causes none. Dropping
+    /// the returned task disconnects the window.
+    pub fn connect_to_window(&self, window: &Window, cx: &App) -> Task<()> {
+        let content_presented = self.observe_content();
+        let source = self.clone();
+        window.spawn(cx, async move |cx| {
+            while content_presented.recv().await.is_ok() {
+                if !source.has_unpainted_content() && !source.has_pending_present() {
+                    continue;
+                }
+                if cx.update(|window, _| window.refresh()).is_err() {
+                    break;
+                }
+            }
+        })
+    }
+
+    fn observe_content(&self) -> smol::channel::Receiver<()> {
+        let (tx, rx) = smol::channel::bounded(1);
+        self.state.content_observers.lock().push(tx);
+        rx
+    }
+
+    /// Records that the current content version has been drawn. Runs on the paint path, so it
+    /// only touches atomics.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            skip_all,
+            fields(content_version = self.content_version())
+        )
+    )]
+    fn mark_content_painted(&self, painted_at: Instant) {
+        let version = self.content_version();
+        let previous = self
+            .state
+            .painted_content_version
+            .swap(version, Ordering::AcqRel);
+        if previous == version {
+            self.state.repeated_frames.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        let frame = &self.state.frames[self.state.active_buffer.load(Ordering::Acquire) % 2];
+        self.state
+            .last_painted_frame
+            .store(frame.frame_index.load(Ordering::Relaxed), Ordering::Relaxed);
+        let latency = self
+            .nanos_since_epoch(painted_at)
+            .saturating_sub(frame.presented_at.load(Ordering::Relaxed));
+        self.state
+            .present_latency_total
+            .fetch_add(latency, Ordering::Relaxed);
+        self.state
+            .present_latency_samples
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// How presented frames have reached the screen so far. Safe to call from any thread.
+    pub fn present_stats(&self) -> PresentStats {
+        let painted = self.state.painted_content_version.load(Ordering::Acquire) > 0;
+        let latency_samples = self.state.present_latency_samples.load(Ordering::Relaxed);
+        PresentStats {
+            last_presented_frame: painted
+                .then(|| self.state.last_painted_frame.load(Ordering::Relaxed)),
+            average_present_latency: (latency_samples > 0).then(|| {
+                Duration::from_nanos(
+                    self.state.present_latency_total.load(Ordering::Relaxed) / latency_samples,
+                )
+            }),
+            skipped_frames: self.state.skipped_frames.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Record every frame `window` presents, so that producer threads can pace themselves to the
+    /// compositor through [`GpuCanvasSource::last_present`] without holding references into the
+    /// app. Dropping the returned subscription stops recording.
+    pub fn latch_presents(&self, window: &Window) -> Subscription {
+        let state = self.state.clone();
+        window.on_present(move |info| *state.last_present.lock() = Some(info))
+    }
+
+    /// The most recent present of a window passed to [`GpuCanvasSource::latch_presents`]. Safe
+    /// to call from any thread.
+    pub fn last_present(&self) -> Option<PresentInfo> {
+        *self.state.last_present.lock()
+    }
+
+    /// Frames the producer presented that were replaced by a newer one before GPUI drew them,
+    /// i.e. [`PresentStats::skipped_frames`]. Growing means the producer renders faster than
+    /// the window redraws.
+    pub fn dropped_frames(&self) -> u64 {
+        self.state.skipped_frames.load(Ordering::Relaxed)
+    }
+
+    /// How many times a canvas drew this source without a new frame having been presented since
+    /// it was last drawn. Growing means the window redraws faster than the producer renders.
+    pub fn repeated_frames(&self) -> u64 {
+        self.state.repeated_frames.load(Ordering::Relaxed)
+    }
+
+    /// How many of the frames the producer presented a canvas has drawn. Compared with
+    /// [`GpuCanvasSource::content_version`], this tells the producer's frame rate apart from the
+    /// rate frames reach the screen.
+    pub fn painted_frames(&self) -> u64 {
+        self.state.present_latency_samples.load(Ordering::Relaxed)
+    }
+
+    /// Whether the producer reports [`ProducerStatus::Rendering`] but has not presented a frame
+    /// for longer than `max_age`, measured from its last present or, before the first, from the
+    /// source's creation. A producer that is idle or has reported an error is never stale.
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        self.is_stale_at(max_age, Instant::now())
+    }
+
+    fn is_stale_at(&self, max_age: Duration, now: Instant) -> bool {
+        self.producer_status().expects_frames() && self.time_until_stale(max_age, now).is_none()
+    }
+
+    /// How long until the producer becomes stale if it presents nothing more, or `None` if it
+    /// already has been quiet for longer than `max_age`.
+    fn time_until_stale(&self, max_age: Duration, now: Instant) -> Option<Duration> {
+        let frame = &self.state.frames[self.state.active_buffer.load(Ordering::Acquire) % 2];
+        let age = self
+            .nanos_since_epoch(now)
+            .saturating_sub(frame.presented_at.load(Ordering::Relaxed));
+        max_age.checked_sub(Duration::from_nanos(age))
+    }
+
+    /// Returns the stalled state if it differs from the last one delivered to a listener,
+    /// marking it as delivered.
+    fn take_stall_change(&self, stalled: bool) -> Option<bool> {
+        let previous = self.state.observed_stalled.swap(stalled, Ordering::AcqRel);
+        (previous != stalled).then_some(stalled)
+    }
+
+    /// Redraw `window` once the producer has been quiet for `max_age`, so stall handling runs
+    /// even though a stalled producer triggers no redraws of its own. At most one watch runs
+    /// per source; it ends after that redraw or when the window closes.
+    fn watch_for_stall(&self, max_age: Duration, window: &Window, cx: &App) {
+        if self.state.stall_watch_running.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let source = self.clone();
+        window
+            .spawn(cx, async move |cx| {
+                while let Some(remaining) = source.time_until_stale(max_age, Instant::now()) {
+                    cx.background_executor()
+                        .timer(remaining + Duration::from_millis(1))
+                        .await;
+                }
+                source
+                    .state
+                    .stall_watch_running
+                    .store(false, Ordering::Release);
+                cx.update(|window, _| window.refresh()).ok();
+            })
+            .detach();
+    }
+
+    fn nanos_since_epoch(&self, instant: Instant) -> u64 {
+        instant
+            .saturating_duration_since(self.state.epoch)
+            .as_nanos()
+            .try_into()
+            .unwrap_or(u64::MAX)
+    }
+
+    fn has_unpainted_content(&self) -> bool {
+        self.content_version() != self.state.painted_content_version.load(Ordering::Acquire)
+    }
+
+    /// Whether the producer has presented a frame with [`GpuCanvasSource::swap_buffers`] or
+    /// [`GpuCanvasSource::set_active_buffer`]. Until then the buffers hold no rendered content.
+    pub fn has_presented(&self) -> bool {
+        self.state.presented.load(Ordering::Acquire)
+    }
+
+    /// Forget the presented frame, so canvases show their placeholder until the producer
+    /// presents again.
+    pub(crate) fn clear_presented(&self) {
+        self.state.presented.store(false, Ordering::Release);
+    }
+
+    /// Whether the active buffer holds a frame that can be displayed.
+    fn has_displayable_frame(&self) -> bool {
+        self.has_presented() && self.active_buffer().is_valid()
+    }
+
+    /// Number an input that is about to be forwarded to the producer, recording when it was
+    /// sent. The producer passes the number of the last input it consumed to
+    /// [`GpuCanvasSource::swap_buffers_with_input_sequence`] when it presents, which lets
+    /// [`GpuCanvas::on_latency_sample`] measure input-to-display latency.
+    pub fn next_input_sequence(&self) -> u64 {
+        self.next_input_sequence_at(Instant::now())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip_all, ret)
+    )]
+    fn next_input_sequence_at(&self, sent_at: Instant) -> u64 {
+        /// Bounds the bookkeeping when the producer never reports consumed input.
+        const MAX_PENDING_INPUTS: usize = 256;
+
+        let sequence = self
+            .state
+            .next_input_sequence
+            .fetch_add(1, Ordering::Relaxed);
+        let mut pending_inputs = self.state.pending_inputs.lock();
+        if pending_inputs.len() == MAX_PENDING_INPUTS {
+            pending_inputs.pop_front();
+        }
+        pending_inputs.push_back((sequence, sent_at));
+        sequence
+    }
+
+    /// Like [`GpuCanvasSource::swap_buffers`], also recording the sequence number of the last
+    /// input reflected in the frame being presented.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(self)))]
+    pub fn swap_buffers_with_input_sequence(&self, input_sequence: u64) {
+        self.state
+            .displayed_input_sequence
+            .fetch_max(input_sequence, Ordering::Release);
+        self.swap_buffers();
+    }
+
+    /// The sequence number of the last input reflected in the presented frame, if the
+    /// producer reports consumed input.
+    pub fn displayed_input_sequence(&self) -> Option<u64> {
+        let sequence = self.state.displayed_input_sequence.load(Ordering::Acquire);
+        (sequence != 0).then_some(sequence)
+    }
+
+    /// How long the newest input reflected in the presented frame took to reach the screen,
+    /// if a frame reflecting new input has been presented since the last sample.
+    fn take_latency_sample(&self, displayed_at: Instant) -> Option<Duration> {
+        let displayed_sequence = self.displayed_input_sequence()?;
+        let mut pending_inputs = self.state.pending_inputs.lock();
+        let mut newest_displayed = None;
+        while let Some(&(sequence, sent_at)) = pending_inputs.front()
+            && sequence <= displayed_sequence
+        {
+            newest_displayed = Some(sent_at);
+            pending_inputs.pop_front();
+        }
+        newest_displayed.map(|sent_at| displayed_at.saturating_duration_since(sent_at))
+    }
+
+    /// Report the producer's lifecycle state. Cheap enough to call every frame from the producer
+    /// thread.
+    pub fn report_status(&self, status: ProducerStatus) {
+        self.state
+            .producer_status
+            .store(status.to_bits(), Ordering::Release);
+    }
+
+    /// The most recent status reported by the producer.
+    pub fn producer_status(&self) -> ProducerStatus {
+        ProducerStatus::from_bits(self.state.producer_status.load(Ordering::Acquire))
+    }
+
+    /// Returns the current status if it differs from the last one delivered to a listener,
+    /// marking it as delivered.
+    fn take_status_change(&self) -> Option<ProducerStatus> {
+        let bits = self.state.producer_status.load(Ordering::Acquire);
+        let previous = self.state.observed_status.swap(bits, Ordering::AcqRel);
+        (previous != bits).then(|| ProducerStatus::from_bits(bits))
+    }
+
+    /// The size in device pixels most recently measured by a canvas with an `on_resize`
+    /// listener, for producers that poll rather than react to the callback. `None` before the
+    /// first measurement and while the canvas is collapsed.
+    pub fn requested_size(&self) -> Option<Size<DevicePixels>> {
+        let bits = self.state.observed_size.load(Ordering::Acquire);
+        (bits != 0).then(|| {
+            size(
+                DevicePixels((bits >> 32) as i32),
+                DevicePixels(bits as u32 as i32),
+            )
+        })
+    }
+
+    /// Records `device_size` as delivered, returning whether it differs from the previous one.
+    /// `None` records a collapsed canvas.
+    fn take_size_change(&self, device_size: Option<Size<DevicePixels>>) -> bool {
+        let bits = device_size.map_or(0, |device_size| {
+            (u64::from(device_size.width) << 32) | u64::from(device_size.height)
+        });
+        self.state.observed_size.swap(bits, Ordering::AcqRel) != bits
     }
 }
 
@@ -139,63 +1292,952 @@ impl GpuCanvasSource {
 ///     .h_full()
 /// ```
 pub struct GpuCanvas {
+    id: Option<ElementId>,
     source: GpuCanvasSource,
-    object_fit: ObjectFit,
+    options: GpuTextureOptions,
+    on_producer_status_changed: Option<Box<dyn Fn(ProducerStatus, &mut Window, &mut App)>>,
+    on_resize: Option<Box<dyn Fn(Option<Size<DevicePixels>>, &mut Window, &mut App)>>,
+    placeholder: Option<Box<dyn Fn() -> AnyElement>>,
+    on_latency_sample: Option<Box<dyn Fn(Duration, &mut Window, &mut App)>>,
+    stall_threshold: Duration,
+    on_stalled: Option<Box<dyn Fn(bool, &mut Window, &mut App)>>,
+    stalled_overlay: Option<Box<dyn Fn() -> AnyElement>>,
+    mouse_down_listeners: Vec<(MouseButton, CanvasMouseListener<MouseDownEvent>)>,
+    mouse_up_listeners: Vec<(MouseButton, CanvasMouseListener<MouseUpEvent>)>,
+    mouse_move_listeners: Vec<CanvasMouseListener<MouseMoveEvent>>,
+    scroll_wheel_listeners: Vec<CanvasMouseListener<ScrollWheelEvent>>,
+    cursor_style: Option<CursorStyle>,
+    focus_handle: Option<FocusHandle>,
+    input_listeners: Vec<Rc<dyn Fn(&PlatformInput, &mut Window, &mut App)>>,
+    release_focus_keystroke: Option<Keystroke>,
+    pointer_lock_on_click: bool,
+    transition: Option<(GpuCanvasSource, Animation)>,
     style: StyleRefinement,
 }
 
+/// Progress of a [`GpuCanvas::transition_from`] crossfade, kept across frames.
+enum CanvasTransitionState {
+    Running {
+        from: GpuCanvasSource,
+        started_at: Instant,
+    },
+    /// The source faded from, held weakly so that its textures can be released
+    Finished {
+        from: std::sync::Weak<GpuCanvasSourceState>,
+    },
+}
+
+/// A mouse listener on a [`GpuCanvas`]. Besides the event it receives the texel under the
+/// pointer, measured from the top-left of the upright texture, or `None` when the pointer is
+/// over letterboxing rather than the displayed image.
+type CanvasMouseListener<E> = Box<dyn Fn(&E, Option<Point<f32>>, &mut Window, &mut App)>;
+
+/// How long a rendering producer may go without presenting before a [`GpuCanvas`] considers
+/// it stalled, unless set with [`GpuCanvas::stall_threshold`].
+const DEFAULT_STALL_THRESHOLD: Duration = Duration::from_secs(2);
+
 /// Create a new GPU canvas element with the given texture source.
 pub fn gpu_canvas(source: GpuCanvasSource) -> GpuCanvas {
     GpuCanvas {
+        id: None,
         source,
-        object_fit: ObjectFit::Contain,
+        options: GpuTextureOptions::default(),
+        on_producer_status_changed: None,
+        on_resize: None,
+        placeholder: None,
+        on_latency_sample: None,
+        stall_threshold: DEFAULT_STALL_THRESHOLD,
+        on_stalled: None,
+        stalled_overlay: None,
+        mouse_down_listeners: Vec::new(),
+        mouse_up_listeners: Vec::new(),
+        mouse_move_listeners: Vec::new(),
+        scroll_wheel_listeners: Vec::new(),
+        cursor_style: None,
+        focus_handle: None,
+        input_listeners: Vec::new(),
+        release_focus_keystroke: Keystroke::parse("escape").ok(),
+        pointer_lock_on_click: false,
+        transition: None,
         style: Default::default(),
     }
 }
 
 impl GpuCanvas {
+    /// Assign an id to the canvas, which also makes it hit-testable.
+    pub fn id(mut self, id: impl Into<ElementId>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
     /// Set how the GPU texture should fit within the element bounds.
     pub fn object_fit(mut self, object_fit: ObjectFit) -> Self {
-        self.object_fit = object_fit;
+        self.options.object_fit = object_fit;
         self
     }
-}
 
-impl Element for GpuCanvas {
-    type RequestLayoutState = ();
-    type PrepaintState = GpuTextureHandle;
+    /// Set how the GPU texture is sampled when scaled. Defaults to [`TextureFilter::Linear`].
+    pub fn filter(mut self, filter: TextureFilter) -> Self {
+        self.options.filter = filter;
+        self
+    }
 
-    fn id(&self) -> Option<ElementId> {
-        None
+    /// Set how the producer's alpha channel is interpreted. Defaults to
+    /// [`AlphaMode::Premultiplied`].
+    pub fn alpha_mode(mut self, alpha_mode: AlphaMode) -> Self {
+        self.options.alpha_mode = alpha_mode;
+        self
     }
 
-    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
-        None
+    /// Set whether an [`AlphaMode::Opaque`] canvas fades with its ancestors' opacity. Defaults
+    /// to [`OpaqueCanvasOpacity::Fade`]; use [`OpaqueCanvasOpacity::Ignore`] for content that
+    /// must never be blended.
+    pub fn opacity_behavior(mut self, opacity_behavior: OpaqueCanvasOpacity) -> Self {
+        self.options.opacity_behavior = opacity_behavior;
+        self
     }
 
-    fn request_layout(
+    /// Only display the given texel region of the texture, e.g. one viewport of a shared atlas.
+    /// The region is cropped before object fit is applied and is clamped to the texture size.
+    pub fn source_rect(mut self, source_rect: Bounds<DevicePixels>) -> Self {
+        self.options.source_rect = Some(source_rect);
+        self
+    }
+
+    /// Flip the texture vertically. Producers that render bottom-up should prefer declaring
+    /// [`TextureOrigin::BottomLeft`] on their handles, which flips automatically.
+    pub fn flip_y(mut self, flip_y: bool) -> Self {
+        self.options.flip_y = flip_y;
+        self
+    }
+
+    /// Rotate the displayed texture clockwise. Quarter turns swap the aspect ratio used for
+    /// object fit.
+    pub fn rotation(mut self, rotation: CanvasRotation) -> Self {
+        self.options.rotation = rotation;
+        self
+    }
+
+    /// Draw the texture at its native size, one texel per device pixel and centered, instead of
+    /// scaling it with `object_fit`. Pair with [`GpuCanvas::on_resize`] to render at exactly the
+    /// element's size.
+    pub fn pixel_perfect(mut self, pixel_perfect: bool) -> Self {
+        self.options.pixel_perfect = pixel_perfect;
+        self
+    }
+
+    /// Desaturate the texture when it is drawn, e.g. to show an inactive viewport.
+    pub fn grayscale(mut self, grayscale: bool) -> Self {
+        self.options.grayscale = grayscale;
+        self
+    }
+
+    /// Multiply the texture by the given color when it is drawn.
+    pub fn tint(mut self, tint: impl Into<Hsla>) -> Self {
+        self.options.tint = Some(tint.into());
+        self
+    }
+
+    /// Draw the texture's red channel as the coverage of the given color, e.g. to overlay a
+    /// single channel [`GpuTextureFormat::R8`] heatmap or selection mask.
+    pub fn mask_color(mut self, color: impl Into<Hsla>) -> Self {
+        self.options.mask_color = Some(color.into());
+        self
+    }
+
+    /// Tone map the texture when its handle declares a linear [`ColorSpace`], such as the scRGB
+    /// output of an HDR renderer. Windows only output SDR, so linear textures drawn without tone
+    /// mapping are clamped.
+    pub fn tone_mapping(mut self, tone_mapping: ToneMapping) -> Self {
+        self.options.tone_mapping = Some(tone_mapping);
+        self
+    }
+
+    /// Crossfade to this canvas's source from the frame `from` displays, e.g. from the last good
+    /// frame of a producer that restarted. The fade starts once the new source has a frame to
+    /// display and shows `from` instead of the placeholder until then. Both textures are laid
+    /// out with this canvas's fit, crop and rotation and blended in a single draw, so the fade
+    /// is correct over transparent content.
+    ///
+    /// Requires an [`id`](GpuCanvas::id) to track the fade across frames. Once `animation`
+    /// completes the canvas drops `from` and ignores it if it is passed again. Repeating
+    /// animations run once.
+    pub fn transition_from(mut self, from: GpuCanvasSource, animation: Animation) -> Self {
+        self.transition = Some((from, animation));
+        self
+    }
+
+    /// Register a callback invoked during prepaint when the producer has reported a different
+    /// [`ProducerStatus`] since the last notification.
+    pub fn on_producer_status_changed(
+        mut self,
+        listener: impl Fn(ProducerStatus, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.on_producer_status_changed = Some(Box::new(listener));
+        self
+    }
+
+    /// Register a callback invoked during prepaint when the element's size in device pixels
+    /// changes, or when the window moves to a display with a different scale factor even if the
+    /// logical size is unchanged. A producer rendering at exactly this size fills the element
+    /// texel-for-pixel.
+    ///
+    /// When the element collapses below a device pixel, e.g. in a closed splitter pane, the
+    /// callback is invoked once with `None` so the producer can pause, and never with a zero
+    /// size. Expanding again reports the size, even if it is the one reported before. The
+    /// callback fires at most once per frame.
+    ///
+    /// Give the canvas an [`id`](GpuCanvas::id) so each element tracks its own size. Without
+    /// one, changes are detected through the [`GpuCanvasSource`], which is shared by every
+    /// canvas displaying it.
+    pub fn on_resize(
+        mut self,
+        listener: impl Fn(Option<Size<DevicePixels>>, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.on_resize = Some(Box::new(listener));
+        self
+    }
+
+    /// Register a callback invoked during prepaint with the time between forwarding an input,
+    /// numbered with [`GpuCanvasSource::next_input_sequence`], and drawing the first frame the
+    /// producer stamped as reflecting it. Remote producers can use this to decide how far ahead
+    /// to predict local overlays such as the cursor.
+    pub fn on_latency_sample(
+        mut self,
+        listener: impl Fn(Duration, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.on_latency_sample = Some(Box::new(listener));
+        self
+    }
+
+    /// Set how long the producer may go without presenting while it reports
+    /// [`ProducerStatus::Rendering`] before [`GpuCanvas::on_stalled`] and
+    /// [`GpuCanvas::with_stalled_overlay`] consider it stalled. Defaults to two seconds.
+    pub fn stall_threshold(mut self, threshold: Duration) -> Self {
+        self.stall_threshold = threshold;
+        self
+    }
+
+    /// Register a callback invoked during prepaint with `true` when the producer stalls, as
+    /// decided by [`GpuCanvasSource::is_stale`] with the canvas's
+    /// [`stall_threshold`](GpuCanvas::stall_threshold), and with `false` once it presents
+    /// again. The canvas redraws itself when the threshold passes, so a producer process that
+    /// crashed is reported even though it will never present again.
+    pub fn on_stalled(mut self, listener: impl Fn(bool, &mut Window, &mut App) + 'static) -> Self {
+        self.on_stalled = Some(Box::new(listener));
+        self
+    }
+
+    /// Set a function that renders an element drawn over the texture while the producer is
+    /// stalled, in the sense of [`GpuCanvas::on_stalled`].
+    pub fn with_stalled_overlay(mut self, overlay: impl Fn() -> AnyElement + 'static) -> Self {
+        self.stalled_overlay = Some(Box::new(overlay));
+        self
+    }
+
+    fn detects_stalls(&self) -> bool {
+        self.on_stalled.is_some() || self.stalled_overlay.is_some()
+    }
+
+    /// The source being faded from and how far the fade has progressed, until it completes.
+    fn advance_transition(
         &mut self,
-        _global_id: Option<&GlobalElementId>,
+        global_id: Option<&GlobalElementId>,
+        has_frame: bool,
+        window: &mut Window,
+    ) -> Option<(GpuCanvasSource, f32)> {
+        let (from, animation) = self.transition.take()?;
+        let now = Instant::now();
+        window.with_optional_element_state::<CanvasTransitionState, _>(global_id, |state, _| {
+            let Some(state) = state else {
+                return (None, None);
+            };
+            let started_at = match state {
+                Some(CanvasTransitionState::Finished { from: finished })
+                    if std::sync::Weak::as_ptr(&finished) == Arc::as_ptr(&from.state) =>
+                {
+                    return (
+                        None,
+                        Some(CanvasTransitionState::Finished { from: finished }),
+                    );
+                }
+                Some(CanvasTransitionState::Running {
+                    from: running,
+                    started_at,
+                }) if running.ptr_eq(&from) && has_frame => started_at,
+                _ => now,
+            };
+            let delta = now.saturating_duration_since(started_at).as_secs_f32()
+                / animation.duration.as_secs_f32();
+            if animation.duration.is_zero() || delta >= 1. {
+                let finished = CanvasTransitionState::Finished {
+                    from: Arc::downgrade(&from.state),
+                };
+                return (None, Some(finished));
+            }
+            let progress = (animation.easing)(delta);
+            (
+                Some((from.clone(), progress)),
+                Some(CanvasTransitionState::Running { from, started_at }),
+            )
+        })
+    }
+
+    /// Set a function that renders the element shown in place of the texture while the source
+    /// has nothing to display: before the producer's first present, or while the active handle
+    /// is invalid. Without a placeholder the canvas only paints its background until then.
+    pub fn with_placeholder(mut self, placeholder: impl Fn() -> AnyElement + 'static) -> Self {
+        self.placeholder = Some(Box::new(placeholder));
+        self
+    }
+
+    /// Register a listener for the given mouse button being pressed over the canvas.
+    pub fn on_mouse_down(
+        mut self,
+        button: MouseButton,
+        listener: impl Fn(&MouseDownEvent, Option<Point<f32>>, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.mouse_down_listeners.push((button, Box::new(listener)));
+        self
+    }
+
+    /// Register a listener for the given mouse button being released over the canvas.
+    pub fn on_mouse_up(
+        mut self,
+        button: MouseButton,
+        listener: impl Fn(&MouseUpEvent, Option<Point<f32>>, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.mouse_up_listeners.push((button, Box::new(listener)));
+        self
+    }
+
+    /// Register a listener for the mouse moving over the canvas.
+    pub fn on_mouse_move(
+        mut self,
+        listener: impl Fn(&MouseMoveEvent, Option<Point<f32>>, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.mouse_move_listeners.push(Box::new(listener));
+        self
+    }
+
+    /// Register a listener for scroll wheel events over the canvas.
+    pub fn on_scroll_wheel(
+        mut self,
+        listener: impl Fn(&ScrollWheelEvent, Option<Point<f32>>, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.scroll_wheel_listeners.push(Box::new(listener));
+        self
+    }
+
+    /// Set the cursor shown while the pointer is over the canvas.
+    pub fn cursor_style(mut self, cursor_style: CursorStyle) -> Self {
+        self.cursor_style = Some(cursor_style);
+        self
+    }
+
+    /// Make the canvas focusable with the given handle. Clicking the canvas focuses it.
+    pub fn track_focus(mut self, focus_handle: &FocusHandle) -> Self {
+        self.focus_handle = Some(focus_handle.clone());
+        self
+    }
+
+    /// Register a listener that receives raw key down, key up and modifier changes while the
+    /// canvas is focused, before any keybindings are matched. Call `cx.stop_propagation()` to
+    /// consume an event; otherwise it continues through normal dispatch. Requires
+    /// [`GpuCanvas::track_focus`].
+    pub fn on_input(
+        mut self,
+        listener: impl Fn(&PlatformInput, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.input_listeners.push(Rc::new(listener));
+        self
+    }
+
+    /// Set the keystroke that releases focus from the canvas, returning keys to normal
+    /// dispatch. Defaults to `escape`; `None` leaves releasing focus to the application.
+    pub fn release_focus_keystroke(mut self, keystroke: Option<Keystroke>) -> Self {
+        self.release_focus_keystroke = keystroke;
+        self
+    }
+
+    /// Lock the pointer to the canvas when it is clicked, for camera controls that need relative
+    /// motion. While locked, mouse listeners receive [`MouseMotionEvent`](crate::MouseMotionEvent)s
+    /// through [`Window::on_mouse_event`]. The lock is released by the release-focus keystroke,
+    /// when the canvas loses focus, or when the window is deactivated.
+    pub fn pointer_lock_on_click(mut self, pointer_lock_on_click: bool) -> Self {
+        self.pointer_lock_on_click = pointer_lock_on_click;
+        self
+    }
+
+    fn paint_input_listeners(&mut self, window: &mut Window) {
+        let Some(focus_handle) = self.focus_handle.as_ref() else {
+            return;
+        };
+        let listeners = mem::take(&mut self.input_listeners);
+        let release_focus_keystroke = self.release_focus_keystroke.take();
+        if listeners.is_empty() && release_focus_keystroke.is_none() && !self.pointer_lock_on_click
+        {
+            return;
+        }
+        window.on_focused_input(focus_handle, move |input, window, cx| {
+            if let (PlatformInput::KeyDown(event), Some(release)) =
+                (input, release_focus_keystroke.as_ref())
+                && event.keystroke.key == release.key
+                && event.keystroke.modifiers == release.modifiers
+            {
+                window.unlock_pointer();
+                window.blur();
+                cx.stop_propagation();
+                return;
+            }
+            for listener in &listeners {
+                listener(input, window, cx);
+                if !cx.propagate_event {
+                    return;
+                }
+            }
+        });
+    }
+
+    fn is_interactive(&self) -> bool {
+        self.id.is_some()
+            || self.focus_handle.is_some()
+            || self.pointer_lock_on_click
+            || self.cursor_style.is_some()
+            || !self.mouse_down_listeners.is_empty()
+            || !self.mouse_up_listeners.is_empty()
+            || !self.mouse_move_listeners.is_empty()
+            || !self.scroll_wheel_listeners.is_empty()
+    }
+
+    fn paint_mouse_listeners(
+        &mut self,
+        hitbox: &Hitbox,
+        texel_mapping: Option<TexelMapping>,
+        window: &mut Window,
+    ) {
+        if let Some(cursor_style) = self.cursor_style {
+            window.set_cursor_style(cursor_style, hitbox);
+        }
+        if self.focus_handle.is_some() || self.pointer_lock_on_click {
+            let focus_handle = self.focus_handle.clone();
+            let pointer_lock_on_click = self.pointer_lock_on_click;
+            let hitbox = hitbox.clone();
+            window.on_mouse_event(move |_: &MouseDownEvent, phase, window, _| {
+                if phase == DispatchPhase::Bubble && hitbox.is_hovered(window) {
+                    if let Some(focus_handle) = focus_handle.as_ref() {
+                        window.focus(focus_handle);
+                    }
+                    if pointer_lock_on_click && !window.is_pointer_locked() {
+                        window.lock_pointer(hitbox.bounds);
+                    }
+                }
+            });
+        }
+        let texel_position = move |position: Point<Pixels>| {
+            texel_mapping.and_then(|mapping| mapping.texel_position(position))
+        };
+
+        for (button, listener) in mem::take(&mut self.mouse_down_listeners) {
+            let hitbox = hitbox.clone();
+            window.on_mouse_event(move |event: &MouseDownEvent, phase, window, cx| {
+                if phase == DispatchPhase::Bubble
+                    && event.button == button
+                    && hitbox.is_hovered(window)
+                {
+                    listener(event, texel_position(event.position), window, cx);
+                }
+            });
+        }
+        for (button, listener) in mem::take(&mut self.mouse_up_listeners) {
+            let hitbox = hitbox.clone();
+            window.on_mouse_event(move |event: &MouseUpEvent, phase, window, cx| {
+                if phase == DispatchPhase::Bubble
+                    && event.button == button
+                    && hitbox.is_hovered(window)
+                {
+                    listener(event, texel_position(event.position), window, cx);
+                }
+            });
+        }
+        for listener in mem::take(&mut self.mouse_move_listeners) {
+            let hitbox = hitbox.clone();
+            window.on_mouse_event(move |event: &MouseMoveEvent, phase, window, cx| {
+                if phase == DispatchPhase::Bubble && hitbox.is_hovered(window) {
+                    listener(event, texel_position(event.position), window, cx);
+                }
+            });
+        }
+        for listener in mem::take(&mut self.scroll_wheel_listeners) {
+            let hitbox = hitbox.clone();
+            window.on_mouse_event(move |event: &ScrollWheelEvent, phase, window, cx| {
+                if phase == DispatchPhase::Bubble && hitbox.should_handle_scroll(window) {
+                    listener(event, texel_position(event.position), window, cx);
+                }
+            });
+        }
+    }
+}
+
+/// Returns the device-pixel size of the canvas bounds, or `None` when the element has collapsed
+/// below a single device pixel in either dimension (e.g. a fully collapsed splitter pane).
+pub(crate) fn canvas_device_size(
+    bounds: Bounds<Pixels>,
+    scale_factor: f32,
+) -> Option<Size<DevicePixels>> {
+    let scaled = bounds.scale(scale_factor).size;
+    let width = scaled.width.0.floor();
+    let height = scaled.height.0.floor();
+    if !(width >= 1. && height >= 1.) {
+        return None;
+    }
+    Some(size(
+        DevicePixels(width as i32),
+        DevicePixels(height as i32),
+    ))
+}
+
+/// The opacity a texture is drawn with under the given inherited element opacity. Opaque
+/// textures are still composited with premultiplied blending, so fading one only needs the
+/// opacity applied; at full opacity the result is identical to an unblended write.
+pub(crate) fn gpu_texture_opacity(options: &GpuTextureOptions, inherited_opacity: f32) -> f32 {
+    let inherited_opacity = inherited_opacity.clamp(0., 1.);
+    match (options.alpha_mode, options.opacity_behavior) {
+        (AlphaMode::Opaque, OpaqueCanvasOpacity::Ignore) if inherited_opacity > 0. => 1.,
+        _ => inherited_opacity,
+    }
+}
+
+/// The logical size at which a canvas would show `texture` texel-for-pixel, after cropping and
+/// rotation. `None` while the producer has not sized its texture.
+pub(crate) fn intrinsic_canvas_size(
+    texture: &GpuTextureHandle,
+    options: &GpuTextureOptions,
+    scale_factor: f32,
+) -> Option<Size<Pixels>> {
+    if texture.is_empty() {
+        return None;
+    }
+    let source_rect = clamp_source_rect(options.source_rect, texture.size());
+    if source_rect.is_empty() {
+        return None;
+    }
+    let displayed_size = if options.rotation.swaps_axes() {
+        size(source_rect.size.height, source_rect.size.width)
+    } else {
+        source_rect.size
+    };
+    Some(displayed_size.to_pixels(scale_factor))
+}
+
+/// Gives an auto-sized canvas its texture's size, like `img` does for images. The aspect ratio is
+/// always applied unless the style sets its own, so a canvas whose width comes from flex layout
+/// derives its height, and min/max constraints are resolved by layout as usual.
+fn apply_intrinsic_size(style: &mut Style, intrinsic_size: Size<Pixels>) {
+    style
+        .aspect_ratio
+        .get_or_insert(intrinsic_size.width / intrinsic_size.height);
+    if let (Length::Auto, Length::Auto) = (style.size.width, style.size.height) {
+        style.size.width = intrinsic_size.width.into();
+    }
+}
+
+/// Clamps the requested crop to the texture, defaulting to the whole texture when no crop is set.
+pub(crate) fn clamp_source_rect(
+    source_rect: Option<Bounds<DevicePixels>>,
+    texture_size: Size<DevicePixels>,
+) -> Bounds<DevicePixels> {
+    let full_texture = Bounds::new(point(DevicePixels(0), DevicePixels(0)), texture_size);
+    let Some(requested) = source_rect else {
+        return full_texture;
+    };
+    let clamped = requested.intersect(&full_texture);
+    if clamped != requested {
+        log::debug!(
+            "gpu_canvas source_rect {requested:?} exceeds texture size {texture_size:?}, clamped to {clamped:?}"
+        );
+    }
+    clamped
+}
+
+/// Converts a crop measured on the upright image into the texture's stored rows, which run
+/// bottom-up for [`TextureOrigin::BottomLeft`] textures.
+pub(crate) fn source_rect_in_storage(
+    source_rect: Bounds<DevicePixels>,
+    texture_size: Size<DevicePixels>,
+    origin: TextureOrigin,
+) -> Bounds<DevicePixels> {
+    match origin {
+        TextureOrigin::TopLeft => source_rect,
+        TextureOrigin::BottomLeft => Bounds::new(
+            point(
+                source_rect.origin.x,
+                texture_size.height - source_rect.bottom(),
+            ),
+            source_rect.size,
+        ),
+    }
+}
+
+/// Where a (possibly cropped) texture is drawn and which normalized texture coordinates map onto
+/// that quad.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct GpuTextureLayout {
+    pub bounds: Bounds<Pixels>,
+    pub uv: Bounds<f32>,
+}
+
+/// Places the cropped, rotated texture within `bounds`. With `pixel_perfect_scale`, the number
+/// of device pixels per unit of `bounds`, the texture is drawn at its native size instead of
+/// being fitted.
+pub(crate) fn layout_gpu_texture(
+    bounds: Bounds<Pixels>,
+    texture_size: Size<DevicePixels>,
+    source_rect: Bounds<DevicePixels>,
+    object_fit: ObjectFit,
+    rotation: CanvasRotation,
+    pixel_perfect_scale: Option<f32>,
+) -> GpuTextureLayout {
+    let displayed_size = if rotation.swaps_axes() {
+        size(source_rect.size.height, source_rect.size.width)
+    } else {
+        source_rect.size
+    };
+    let bounds = match pixel_perfect_scale {
+        Some(scale_factor) => pixel_perfect_bounds(bounds, displayed_size, scale_factor),
+        None => object_fit.get_bounds(bounds, displayed_size),
+    };
+    if texture_size.width.0 <= 0 || texture_size.height.0 <= 0 {
+        return GpuTextureLayout {
+            bounds,
+            uv: Bounds::default(),
+        };
+    }
+
+    let texture_width = texture_size.width.0 as f32;
+    let texture_height = texture_size.height.0 as f32;
+    GpuTextureLayout {
+        bounds,
+        uv: Bounds::new(
+            point(
+                source_rect.origin.x.0 as f32 / texture_width,
+                source_rect.origin.y.0 as f32 / texture_height,
+            ),
+            size(
+                source_rect.size.width.0 as f32 / texture_width,
+                source_rect.size.height.0 as f32 / texture_height,
+            ),
+        ),
+    }
+}
+
+/// Centers `displayed_size` in `bounds` at one texel per device pixel, snapping the origin to the
+/// device pixel grid so sampling never falls between texels.
+fn pixel_perfect_bounds(
+    bounds: Bounds<Pixels>,
+    displayed_size: Size<DevicePixels>,
+    scale_factor: f32,
+) -> Bounds<Pixels> {
+    let displayed_size = displayed_size.to_pixels(scale_factor);
+    let snap = |start: Pixels, available: Pixels, length: Pixels| {
+        Pixels(
+            ((start + (available - length) / 2.) * scale_factor)
+                .0
+                .round()
+                / scale_factor,
+        )
+    };
+    Bounds::new(
+        point(
+            snap(bounds.origin.x, bounds.size.width, displayed_size.width),
+            snap(bounds.origin.y, bounds.size.height, displayed_size.height),
+        ),
+        displayed_size,
+    )
+}
+
+/// Maps window positions over a painted [`GpuCanvas`] back to texels of its texture.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct TexelMapping {
+    clip_bounds: Bounds<Pixels>,
+    quad_bounds: Bounds<Pixels>,
+    source_rect: Bounds<DevicePixels>,
+    rotation: CanvasRotation,
+    flip_y: bool,
+}
+
+impl TexelMapping {
+    pub(crate) fn new(
+        bounds: Bounds<Pixels>,
+        texture: &GpuTextureHandle,
+        options: &GpuTextureOptions,
+        scale_factor: f32,
+    ) -> Self {
+        let texture_size = texture.size();
+        let source_rect = clamp_source_rect(options.source_rect, texture_size);
+        let layout = layout_gpu_texture(
+            bounds,
+            texture_size,
+            source_rect,
+            options.object_fit,
+            options.rotation,
+            options.pixel_perfect.then_some(scale_factor),
+        );
+        Self {
+            clip_bounds: bounds,
+            quad_bounds: layout.bounds,
+            source_rect,
+            rotation: options.rotation,
+            flip_y: options.flip_y,
+        }
+    }
+
+    /// The texel shown at `position`, measured from the top-left of the upright texture.
+    /// Returns `None` outside the displayed image, e.g. over letterboxing.
+    pub(crate) fn texel_position(&self, position: Point<Pixels>) -> Option<Point<f32>> {
+        if self.quad_bounds.is_empty()
+            || !self.quad_bounds.contains(&position)
+            || !self.clip_bounds.contains(&position)
+        {
+            return None;
+        }
+        let unit = point(
+            (position.x - self.quad_bounds.origin.x) / self.quad_bounds.size.width,
+            (position.y - self.quad_bounds.origin.y) / self.quad_bounds.size.height,
+        );
+        // The handle's origin only affects how rows are stored, not the upright image, so only
+        // the explicit flip is undone here.
+        let texel = orient_unit_point(unit, self.rotation, self.flip_y);
+        Some(point(
+            self.source_rect.origin.x.0 as f32 + texel.x * self.source_rect.size.width.0 as f32,
+            self.source_rect.origin.y.0 as f32 + texel.y * self.source_rect.size.height.0 as f32,
+        ))
+    }
+}
+
+/// Maps a point in the unit square of the displayed quad to the unit square of the (cropped)
+/// texture. Mirrors `orient_unit_vertex` in the backend shaders.
+pub(crate) fn orient_unit_point(
+    display_point: crate::Point<f32>,
+    rotation: CanvasRotation,
+    flip_y: bool,
+) -> crate::Point<f32> {
+    // Showing the image rotated clockwise means sampling the texture counter-clockwise.
+    let mut texture_point = match rotation {
+        CanvasRotation::None => display_point,
+        CanvasRotation::Cw90 => point(display_point.y, 1. - display_point.x),
+        CanvasRotation::Cw180 => point(1. - display_point.x, 1. - display_point.y),
+        CanvasRotation::Cw270 => point(1. - display_point.y, display_point.x),
+    };
+    if flip_y {
+        texture_point.y = 1. - texture_point.y;
+    }
+    texture_point
+}
+
+/// The size a [`GpuCanvas`] last reported to its `on_resize` listener, kept across frames.
+#[derive(Clone, Copy, PartialEq)]
+struct CanvasResizeState {
+    /// `None` while the canvas is collapsed
+    device_size: Option<Size<DevicePixels>>,
+    scale_factor: f32,
+}
+
+/// State carried from [`GpuCanvas`] layout to prepaint and paint.
+pub struct GpuCanvasLayoutState {
+    style: Style,
+    /// Whether the source had a frame to display when the canvas was laid out
+    has_frame: bool,
+    placeholder: Option<AnyElement>,
+    /// Whether the producer was stalled when the canvas was laid out
+    stalled: bool,
+    stalled_overlay: Option<AnyElement>,
+    /// When the frame queued with `swap_buffers_at` becomes due
+    next_present: Option<Instant>,
+    /// The source being faded from and the fade's progress, see [`GpuCanvas::transition_from`]
+    transition: Option<(GpuCanvasSource, f32)>,
+}
+
+/// State carried from [`GpuCanvas`] prepaint to paint.
+pub struct GpuCanvasPrepaintState {
+    texture: Option<GpuTextureHandle>,
+    /// The texture being faded from and the fade's progress
+    transition_from: Option<(GpuTextureHandle, f32)>,
+    hitbox: Option<Hitbox>,
+}
+
+impl Element for GpuCanvas {
+    type RequestLayoutState = GpuCanvasLayoutState;
+    type PrepaintState = GpuCanvasPrepaintState;
+
+    fn id(&self) -> Option<ElementId> {
+        self.id.clone()
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        global_id: Option<&GlobalElementId>,
         _inspector_id: Option<&InspectorElementId>,
         window: &mut Window,
         cx: &mut App,
     ) -> (LayoutId, Self::RequestLayoutState) {
+        let next_present = self.source.promote_due_presents(Instant::now());
         let mut style = Style::default();
         style.refine(&self.style);
-        let layout_id = window.request_layout(style, [], cx);
-        (layout_id, ())
+        if let Some(intrinsic_size) = intrinsic_canvas_size(
+            self.source.active_buffer(),
+            &self.options,
+            window.scale_factor(),
+        ) {
+            apply_intrinsic_size(&mut style, intrinsic_size);
+        }
+        let has_frame = self.source.has_displayable_frame();
+        let transition = self.advance_transition(global_id, has_frame, window);
+        let shows_transition = transition
+            .as_ref()
+            .is_some_and(|(from, _)| from.has_displayable_frame());
+        let mut placeholder = self
+            .placeholder
+            .as_ref()
+            .filter(|_| !has_frame && !shows_transition)
+            .map(|placeholder| placeholder());
+        let stalled = self.detects_stalls() && self.source.is_stale(self.stall_threshold);
+        let mut stalled_overlay = self
+            .stalled_overlay
+            .as_ref()
+            .filter(|_| stalled)
+            .map(|overlay| overlay());
+        let child_layout_ids = placeholder
+            .iter_mut()
+            .chain(stalled_overlay.iter_mut())
+            .map(|child| child.request_layout(window, cx))
+            .collect::<Vec<_>>();
+        let layout_id = window.request_layout(style.clone(), child_layout_ids, cx);
+        (
+            layout_id,
+            GpuCanvasLayoutState {
+                style,
+                has_frame,
+                placeholder,
+                stalled,
+                stalled_overlay,
+                next_present,
+                transition,
+            },
+        )
     }
 
     fn prepaint(
         &mut self,
-        _global_id: Option<&GlobalElementId>,
+        global_id: Option<&GlobalElementId>,
         _inspector_id: Option<&InspectorElementId>,
-        _bounds: Bounds<Pixels>,
-        _request_layout: &mut Self::RequestLayoutState,
-        _window: &mut Window,
-        _cx: &mut App,
+        bounds: Bounds<Pixels>,
+        request_layout: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
     ) -> Self::PrepaintState {
-        self.source.active_buffer().clone()
+        if let Some(listener) = self.on_producer_status_changed.as_ref()
+            && let Some(status) = self.source.take_status_change()
+        {
+            listener(status, window, cx);
+        }
+
+        if let Some(focus_handle) = self.focus_handle.as_ref() {
+            window.set_focus_handle(focus_handle, cx);
+            if self.pointer_lock_on_click
+                && window.is_pointer_locked()
+                && !focus_handle.is_focused(window)
+            {
+                window.unlock_pointer();
+            }
+        }
+        let hitbox = self
+            .is_interactive()
+            .then(|| window.insert_hitbox(bounds, HitboxBehavior::Normal));
+        let scale_factor = window.scale_factor();
+        let device_size = canvas_device_size(bounds, scale_factor);
+        if let Some(listener) = self.on_resize.as_ref() {
+            let source_size_changed = self.source.take_size_change(device_size);
+            let current = CanvasResizeState {
+                device_size,
+                scale_factor,
+            };
+            let resized = window.with_optional_element_state::<CanvasResizeState, _>(
+                global_id,
+                |previous, _| match previous {
+                    Some(previous) => {
+                        let resized = if device_size.is_some() {
+                            previous != Some(current)
+                        } else {
+                            // Only a canvas that reported a size reports collapsing, once.
+                            previous.is_some_and(|previous| previous.device_size.is_some())
+                        };
+                        (resized, Some(current))
+                    }
+                    None => (source_size_changed, None),
+                },
+            );
+            if resized {
+                listener(device_size, window, cx);
+            }
+        }
+
+        if let Some(listener) = self.on_latency_sample.as_ref()
+            && let Some(latency) = self.source.take_latency_sample(Instant::now())
+        {
+            listener(latency, window, cx);
+        }
+
+        if self.detects_stalls() {
+            let stalled = request_layout.stalled;
+            if let Some(listener) = self.on_stalled.as_ref()
+                && let Some(stalled) = self.source.take_stall_change(stalled)
+            {
+                listener(stalled, window, cx);
+            }
+            if !stalled && self.source.producer_status().expects_frames() {
+                self.source
+                    .watch_for_stall(self.stall_threshold, window, cx);
+            }
+        }
+
+        if let Some(next_present) = request_layout.next_present {
+            self.source.redraw_at(next_present, window, cx);
+        }
+
+        if let Some(placeholder) = request_layout.placeholder.as_mut() {
+            placeholder.prepaint(window, cx);
+        }
+        if let Some(overlay) = request_layout.stalled_overlay.as_mut() {
+            overlay.prepaint(window, cx);
+        }
+
+        let texture = self.source.active_buffer();
+        let texture = (request_layout.has_frame && device_size.is_some() && texture.is_valid())
+            .then(|| texture.clone());
+        if texture.is_some() {
+            self.source.mark_content_painted(Instant::now());
+        }
+        window.register_gpu_canvas_source(&self.source);
+
+        let mut transition_from = None;
+        if let Some((from, progress)) = request_layout.transition.as_ref() {
+            window.request_animation_frame();
+            window.register_gpu_canvas_source(from);
+            let from_texture = from.active_buffer();
+            if from.has_displayable_frame() && device_size.is_some() && from_texture.is_valid() {
+                transition_from = Some((from_texture.clone(), *progress));
+            }
+        }
+        GpuCanvasPrepaintState {
+            texture,
+            transition_from,
+            hitbox,
+        }
     }
 
     fn paint(
@@ -203,12 +2245,57 @@ impl Element for GpuCanvas {
         _global_id: Option<&GlobalElementId>,
         _inspector_id: Option<&InspectorElementId>,
         bounds: Bounds<Pixels>,
-        _request_layout: &mut Self::RequestLayoutState,
+        request_layout: &mut Self::RequestLayoutState,
         prepaint: &mut Self::PrepaintState,
         window: &mut Window,
-        _cx: &mut App,
+        cx: &mut App,
     ) {
-        window.paint_gpu_texture(bounds, prepaint.clone(), self.object_fit);
+        let GpuCanvasLayoutState {
+            style,
+            placeholder,
+            stalled_overlay,
+            ..
+        } = request_layout;
+        let corner_radii = style
+            .corner_radii
+            .to_pixels(window.rem_size())
+            .clamp_radii_for_quad_size(bounds.size);
+        if let Some(hitbox) = prepaint.hitbox.as_ref() {
+            let texel_mapping = prepaint.texture.as_ref().map(|texture| {
+                TexelMapping::new(bounds, texture, &self.options, window.scale_factor())
+            });
+            self.paint_mouse_listeners(hitbox, texel_mapping, window);
+        }
+        self.paint_input_listeners(window);
+
+        window.with_element_opacity(style.opacity, |window| {
+            style.paint(bounds, window, cx, |window, cx| {
+                if let Some(placeholder) = placeholder.as_mut() {
+                    placeholder.paint(window, cx);
+                }
+                // Cover and None can overflow the element, which must never draw outside its box.
+                window.with_content_mask(Some(ContentMask { bounds }), |window| {
+                    match (prepaint.texture.take(), prepaint.transition_from.take()) {
+                        (Some(texture), Some((from, progress))) => window
+                            .paint_gpu_texture_transition(
+                                bounds,
+                                corner_radii,
+                                from,
+                                texture,
+                                progress,
+                                &self.options,
+                            ),
+                        (Some(texture), None) | (None, Some((texture, _))) => {
+                            window.paint_gpu_texture(bounds, corner_radii, texture, &self.options)
+                        }
+                        (None, None) => {}
+                    }
+                });
+                if let Some(overlay) = stalled_overlay.as_mut() {
+                    overlay.paint(window, cx);
+                }
+            });
+        });
     }
 }
 
@@ -225,3 +2312,1246 @@ impl Styled for GpuCanvas {
         &mut self.style
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        self as gpui, Context, Entity, ParentElement as _, Render, TestAppContext,
+        VisualTestContext, WindowVisibility, div, prelude::FluentBuilder as _, px,
+    };
+    use std::cell::RefCell;
+
+    fn device_bounds(x: i32, y: i32, width: i32, height: i32) -> Bounds<DevicePixels> {
+        Bounds::new(
+            point(DevicePixels(x), DevicePixels(y)),
+            size(DevicePixels(width), DevicePixels(height)),
+        )
+    }
+
+    #[test]
+    fn test_concurrent_swaps_are_not_lost() {
+        let source = GpuCanvasSource::new(
+            GpuTextureHandle::new(1, 16, 16),
+            GpuTextureHandle::new(2, 16, 16),
+        );
+        let done = Arc::new(AtomicBool::new(false));
+        let reader = std::thread::spawn({
+            let source = source.clone();
+            let done = done.clone();
+            move || {
+                while !done.load(Ordering::Acquire) {
+                    let handle = source.active_buffer().native_handle;
+                    assert!(handle == 1 || handle == 2);
+                }
+            }
+        });
+        let producers = (0..4)
+            .map(|_| {
+                let source = source.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..1000 {
+                        source.swap_buffers();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        done.store(true, Ordering::Release);
+        reader.join().unwrap();
+
+        assert_eq!(source.content_version(), 4000);
+        // An even number of swaps lands back on the first buffer.
+        assert_eq!(source.active_buffer().native_handle, 1);
+    }
+
+    /// Explores every interleaving of two swaps and a reader of the active buffer. Run with
+    /// `RUSTFLAGS="--cfg loom" cargo test -p gpui --lib loom`.
+    #[cfg(loom)]
+    #[test]
+    fn test_loom_concurrent_swaps_are_not_lost() {
+        loom::model(|| {
+            let source = GpuCanvasSource::new(
+                GpuTextureHandle::new(1, 16, 16),
+                GpuTextureHandle::new(2, 16, 16),
+            );
+            let reader = loom::thread::spawn({
+                let source = source.clone();
+                move || {
+                    let handle = source.active_buffer().native_handle;
+                    assert!(handle == 1 || handle == 2);
+                }
+            });
+            let producer = loom::thread::spawn({
+                let source = source.clone();
+                move || source.swap_buffers()
+            });
+            source.swap_buffers();
+            producer.join().unwrap();
+            reader.join().unwrap();
+
+            assert_eq!(source.content_version(), 2);
+            assert_eq!(source.active_buffer().native_handle, 1);
+            assert_eq!(source.back_buffer().native_handle, 2);
+        });
+    }
+
+    struct CanvasView {
+        source: GpuCanvasSource,
+    }
+
+    impl Render for CanvasView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div().size(px(200.)).child(
+                gpu_canvas(self.source.clone())
+                    .object_fit(ObjectFit::Fill)
+                    .size(px(100.)),
+            )
+        }
+    }
+
+    // On macOS the handle is resolved as an IOSurface id while painting, which a made-up handle
+    // is not.
+    #[cfg(not(target_os = "macos"))]
+    #[gpui::test]
+    fn test_painted_gpu_texture_is_recorded(cx: &mut TestAppContext) {
+        let source = GpuCanvasSource::new(
+            GpuTextureHandle::new(7, 64, 64),
+            GpuTextureHandle::new(8, 64, 64),
+        );
+        source.set_active_buffer(1);
+        let (_view, cx) = cx.add_window_view(|_, _| CanvasView {
+            source: source.clone(),
+        });
+        cx.update(|window, cx| {
+            window.draw(cx);
+        });
+
+        let textures = cx.painted_gpu_textures();
+        assert_eq!(textures.len(), 1);
+        assert_eq!(
+            textures[0].bounds,
+            Bounds::new(point(px(0.), px(0.)), size(px(100.), px(100.)))
+        );
+        assert_eq!(textures[0].native_handle, Some(8));
+        assert_eq!(textures[0].object_fit, ObjectFit::Fill);
+        assert_eq!(textures[0].opacity, 1.);
+        assert_eq!(textures[0].transition_from, None);
+    }
+
+    struct TransitionView {
+        from: GpuCanvasSource,
+        source: GpuCanvasSource,
+    }
+
+    impl Render for TransitionView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            gpu_canvas(self.source.clone())
+                .id("canvas")
+                .transition_from(self.from.clone(), Animation::new(Duration::from_secs(60)))
+                .size(px(100.))
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[gpui::test]
+    fn test_transition_shows_previous_source_until_new_one_has_a_frame(cx: &mut TestAppContext) {
+        let from = GpuCanvasSource::new(
+            GpuTextureHandle::new(7, 64, 64),
+            GpuTextureHandle::new(8, 64, 64),
+        );
+        from.set_active_buffer(1);
+        let source = GpuCanvasSource::new(
+            GpuTextureHandle::new(10, 64, 64),
+            GpuTextureHandle::new(11, 64, 64),
+        );
+        let (_view, cx) = cx.add_window_view(|_, _| TransitionView {
+            from: from.clone(),
+            source: source.clone(),
+        });
+        cx.update(|window, cx| {
+            window.draw(cx);
+        });
+
+        let textures = cx.painted_gpu_textures();
+        assert_eq!(textures.len(), 1);
+        assert_eq!(textures[0].native_handle, Some(8));
+        assert_eq!(textures[0].transition_from, None);
+
+        source.swap_buffers();
+        cx.update(|window, cx| {
+            window.refresh();
+            window.draw(cx);
+        });
+
+        let textures = cx.painted_gpu_textures();
+        assert_eq!(textures.len(), 1);
+        assert_eq!(textures[0].native_handle, Some(11));
+        let (from_handle, progress) = textures[0].transition_from.unwrap();
+        assert_eq!(from_handle, Some(8));
+        assert!(progress < 1.);
+    }
+
+    struct ResizeView {
+        source: GpuCanvasSource,
+        sizes: Rc<RefCell<Vec<Option<Size<DevicePixels>>>>>,
+    }
+
+    impl Render for ResizeView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let sizes = self.sizes.clone();
+            gpu_canvas(self.source.clone())
+                .id("canvas")
+                .on_resize(move |device_size, _, _| sizes.borrow_mut().push(device_size))
+                .size(px(100.))
+        }
+    }
+
+    #[gpui::test]
+    fn test_scale_factor_change_is_reported(cx: &mut TestAppContext) {
+        let texture = GpuTextureHandle::new(0, 16, 16);
+        let sizes = Rc::new(RefCell::new(Vec::new()));
+        let (_view, cx) = cx.add_window_view(|_, _| ResizeView {
+            source: GpuCanvasSource::new(texture.clone(), texture),
+            sizes: sizes.clone(),
+        });
+        let scale_factors = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = cx.update(|window, _| {
+            let scale_factors = scale_factors.clone();
+            window.observe_scale_factor(move |scale_factor, _, _| {
+                scale_factors.borrow_mut().push(scale_factor)
+            })
+        });
+        cx.update(|window, cx| {
+            window.draw(cx);
+        });
+
+        cx.simulate_scale_factor_change(3.);
+        cx.update(|window, cx| {
+            window.draw(cx);
+        });
+
+        assert_eq!(*scale_factors.borrow(), vec![3.]);
+        assert_eq!(
+            *sizes.borrow(),
+            vec![
+                Some(size(DevicePixels(200), DevicePixels(200))),
+                Some(size(DevicePixels(300), DevicePixels(300))),
+            ]
+        );
+    }
+
+    struct SplitterView {
+        source: GpuCanvasSource,
+        pane_width: Pixels,
+        sizes: Rc<RefCell<Vec<Option<Size<DevicePixels>>>>>,
+    }
+
+    impl Render for SplitterView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let sizes = self.sizes.clone();
+            div()
+                .flex()
+                .size(px(200.))
+                .child(
+                    div().w(self.pane_width).h_full().child(
+                        gpu_canvas(self.source.clone())
+                            .id("canvas")
+                            .on_resize(move |device_size, _, _| {
+                                sizes.borrow_mut().push(device_size)
+                            })
+                            .size_full(),
+                    ),
+                )
+                .child(div().flex_1())
+        }
+    }
+
+    #[gpui::test]
+    fn test_splitter_collapse_pauses_and_expand_resumes(cx: &mut TestAppContext) {
+        let texture = GpuTextureHandle::new(0, 16, 16);
+        let sizes = Rc::new(RefCell::new(Vec::new()));
+        let (view, cx) = cx.add_window_view(|_, _| SplitterView {
+            source: GpuCanvasSource::new(texture.clone(), texture),
+            pane_width: px(100.),
+            sizes: sizes.clone(),
+        });
+        let set_pane_width = |width: f32, cx: &mut VisualTestContext| {
+            view.update(cx, |view, cx| {
+                view.pane_width = px(width);
+                cx.notify();
+            });
+            cx.update(|window, cx| {
+                window.draw(cx);
+            });
+        };
+        let expanded = Some(size(DevicePixels(200), DevicePixels(400)));
+
+        set_pane_width(100., cx);
+        assert_eq!(*sizes.borrow(), vec![expanded]);
+
+        // Collapsing is reported once, without a zero size, however long it lasts.
+        set_pane_width(0., cx);
+        set_pane_width(0., cx);
+        assert_eq!(*sizes.borrow(), vec![expanded, None]);
+
+        // Expanding to the size reported before the collapse resumes resizing.
+        set_pane_width(100., cx);
+        set_pane_width(50., cx);
+        assert_eq!(
+            *sizes.borrow(),
+            vec![
+                expanded,
+                None,
+                expanded,
+                Some(size(DevicePixels(100), DevicePixels(400))),
+            ]
+        );
+    }
+
+    struct ToggleCanvasView {
+        source: GpuCanvasSource,
+        show: bool,
+    }
+
+    impl Render for ToggleCanvasView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div().size(px(200.)).when(self.show, |this| {
+                this.child(gpu_canvas(self.source.clone()).size(px(100.)))
+            })
+        }
+    }
+
+    #[gpui::test]
+    fn test_consumer_active_follows_visibility(cx: &mut TestAppContext) {
+        let texture = GpuTextureHandle::new(0, 16, 16);
+        let source = GpuCanvasSource::new(texture.clone(), texture);
+        let (view, cx) = cx.add_window_view(|_, _| ToggleCanvasView {
+            source: source.clone(),
+            show: true,
+        });
+        assert!(!source.consumer_active());
+        cx.update(|window, cx| {
+            window.draw(cx);
+        });
+        assert!(source.consumer_active());
+
+        let visibilities = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = cx.update(|window, _| {
+            let visibilities = visibilities.clone();
+            window.observe_visibility(move |visibility, _, _| {
+                visibilities.borrow_mut().push(visibility)
+            })
+        });
+        cx.simulate_visibility_change(WindowVisibility::Minimized);
+        assert!(!source.consumer_active());
+        cx.simulate_visibility_change(WindowVisibility::Visible);
+        assert!(source.consumer_active());
+        assert_eq!(
+            *visibilities.borrow(),
+            vec![WindowVisibility::Minimized, WindowVisibility::Visible]
+        );
+
+        view.update(cx, |view, cx| {
+            view.show = false;
+            cx.notify();
+        });
+        cx.update(|window, cx| {
+            window.draw(cx);
+        });
+        assert!(!source.consumer_active());
+    }
+
+    struct CanvasParentView {
+        canvas: Entity<ToggleCanvasView>,
+    }
+
+    impl Render for CanvasParentView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div().size(px(300.)).child(self.canvas.clone())
+        }
+    }
+
+    #[gpui::test]
+    fn test_cached_canvas_view_keeps_source_registered(cx: &mut TestAppContext) {
+        let texture = GpuTextureHandle::new(0, 16, 16);
+        let source = GpuCanvasSource::new(texture.clone(), texture);
+        let (view, cx) = cx.add_window_view(|_, cx| CanvasParentView {
+            canvas: cx.new(|_| ToggleCanvasView {
+                source: source.clone(),
+                show: true,
+            }),
+        });
+        cx.update(|window, cx| {
+            window.draw(cx);
+        });
+        assert!(source.consumer_active());
+
+        // Only the parent renders again, so the canvas's view replays its cached prepaint.
+        for _ in 0..2 {
+            view.update(cx, |_, cx| cx.notify());
+            cx.update(|window, cx| {
+                window.draw(cx);
+            });
+            assert!(source.consumer_active());
+            cx.update(|window, _| {
+                let sources = window.gpu_canvas_sources().collect::<Vec<_>>();
+                assert_eq!(sources.len(), 1);
+                assert!(sources[0].ptr_eq(&source));
+            });
+        }
+    }
+
+    #[gpui::test]
+    fn test_cpu_source_writes_and_displays_frames(cx: &mut TestAppContext) {
+        let cx = cx.add_empty_window();
+        let source = cx
+            .update(|window, _| {
+                GpuCanvasSource::from_cpu(window, size(DevicePixels(2), DevicePixels(1)))
+            })
+            .unwrap();
+        assert!(!source.has_displayable_frame());
+
+        cx.update(|window, _| source.write_frame(window, &[1, 2, 3, 4, 5, 6, 7, 8]))
+            .unwrap();
+        assert!(source.has_displayable_frame());
+        assert_eq!(
+            cx.gpu_texture_pixels(source.active_buffer()),
+            Some(vec![1, 2, 3, 4, 5, 6, 7, 8])
+        );
+
+        cx.update(|window, _| {
+            source.write_frame_with(window, |rgba, row_pitch| {
+                assert_eq!(row_pitch, 8);
+                rgba[4..].copy_from_slice(&[9; 4]);
+            })
+        })
+        .unwrap();
+        assert_eq!(
+            cx.gpu_texture_pixels(source.active_buffer()),
+            Some(vec![0, 0, 0, 0, 9, 9, 9, 9])
+        );
+
+        // A frame of the wrong size is rejected rather than displayed.
+        assert!(
+            cx.update(|window, _| source.write_frame(window, &[0; 4]))
+                .is_err()
+        );
+        assert_eq!(
+            cx.gpu_texture_pixels(source.active_buffer()),
+            Some(vec![0, 0, 0, 0, 9, 9, 9, 9])
+        );
+    }
+
+    #[gpui::test]
+    fn test_latched_present_info(cx: &mut TestAppContext) {
+        let texture = GpuTextureHandle::new(0, 16, 16);
+        let source = GpuCanvasSource::new(texture.clone(), texture);
+        let (_, cx) = cx.add_window_view(|_, _| ToggleCanvasView {
+            source: source.clone(),
+            show: true,
+        });
+        let subscription = cx.update(|window, _| source.latch_presents(window));
+        assert_eq!(source.last_present(), None);
+
+        cx.update(|window, cx| {
+            window.draw(cx);
+            window.present();
+        });
+        let first = source.last_present().unwrap();
+        assert_eq!(first.frame, 1);
+
+        cx.update(|window, _| window.present());
+        let second = std::thread::spawn({
+            let source = source.clone();
+            move || source.last_present()
+        })
+        .join()
+        .unwrap()
+        .unwrap();
+        assert_eq!(second.frame, 2);
+        assert!(second.presented_at >= first.presented_at);
+
+        drop(subscription);
+        cx.update(|window, _| window.present());
+        assert_eq!(source.last_present(), Some(second));
+    }
+
+    #[test]
+    fn test_handles_are_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<GpuTextureHandle>();
+        assert_send_sync::<GpuCanvasSource>();
+    }
+
+    #[test]
+    fn test_color_space_defaults_to_srgb() {
+        let texture = GpuTextureHandle::new_with_format(1, 16, 16, GpuTextureFormat::RGBA16F);
+        assert_eq!(texture.color_space, ColorSpace::SrgbNonLinear);
+        assert!(!texture.color_space.is_linear());
+
+        let texture = texture.with_color_space(ColorSpace::ScRgbLinear);
+        assert_eq!(texture.color_space, ColorSpace::ScRgbLinear);
+        assert!(texture.color_space.is_linear());
+        assert!(!ColorSpace::Bt709Limited.is_linear());
+    }
+
+    #[test]
+    fn test_producer_status_from_background_thread() {
+        let texture = GpuTextureHandle::new(0, 16, 16);
+        let source = GpuCanvasSource::new(texture.clone(), texture);
+        assert_eq!(source.producer_status(), ProducerStatus::Idle);
+        assert_eq!(source.take_status_change(), None);
+
+        let statuses = [
+            ProducerStatus::Rendering,
+            ProducerStatus::Error(u32::MAX),
+            ProducerStatus::Error(0),
+            ProducerStatus::Idle,
+        ];
+        for status in statuses {
+            let producer = source.clone();
+            std::thread::spawn(move || producer.report_status(status))
+                .join()
+                .unwrap();
+            assert_eq!(source.producer_status(), status);
+            assert_eq!(source.take_status_change(), Some(status));
+            assert_eq!(source.take_status_change(), None);
+        }
+
+        assert!(ProducerStatus::Rendering.expects_frames());
+        assert!(!ProducerStatus::Idle.expects_frames());
+        assert!(!ProducerStatus::Error(3).expects_frames());
+    }
+
+    #[test]
+    fn test_nv12_size_in_bytes() {
+        let nv12 = |width, height| {
+            GpuTextureHandle::new_with_format(1, width, height, GpuTextureFormat::Nv12)
+        };
+        // A 1920x1080 luma plane plus a 960x540 plane of two-byte chroma samples.
+        assert_eq!(nv12(1920, 1080).size_in_bytes(), 1920 * 1080 * 3 / 2);
+        // Odd sizes round the chroma plane up.
+        assert_eq!(nv12(3, 3).size_in_bytes(), 9 + 2 * 2 * 2);
+        assert_eq!(GpuTextureHandle::new(1, 4, 4).size_in_bytes(), 64);
+        assert_eq!(
+            GpuTextureHandle::new_with_format(1, 4, 4, GpuTextureFormat::R8).size_in_bytes(),
+            16
+        );
+    }
+
+    #[test]
+    fn test_validate_pixel_write() {
+        let texture = GpuTextureHandle::new(1, 16, 8);
+        let region = |x, y, width, height| {
+            Bounds::new(
+                point(DevicePixels(x), DevicePixels(y)),
+                size(DevicePixels(width), DevicePixels(height)),
+            )
+        };
+
+        let fits = |texture: &GpuTextureHandle, region, data_len, stride| {
+            texture
+                .validate_pixel_write(region, data_len, stride)
+                .is_ok()
+        };
+
+        assert!(fits(&texture, region(0, 0, 16, 8), 512, 64));
+        // The last row needs no padding after it.
+        assert!(fits(&texture, region(4, 2, 2, 3), 80 + 8, 40));
+        assert!(!fits(&texture, region(4, 2, 2, 3), 80 + 7, 40));
+        assert!(!fits(&texture, region(4, 2, 4, 1), 16, 8));
+        assert!(!fits(&texture, region(12, 0, 8, 1), 1024, 64));
+        assert!(!fits(&texture, region(-1, 0, 4, 1), 1024, 64));
+        assert!(!fits(&texture, region(0, 0, 0, 1), 1024, 64));
+
+        let nv12 = GpuTextureHandle::new_with_format(1, 16, 8, GpuTextureFormat::Nv12);
+        assert!(!fits(&nv12, region(0, 0, 16, 8), 1024, 64));
+    }
+
+    #[test]
+    fn test_convert_pixel_rows() {
+        use GpuTextureFormat::*;
+
+        // Two rows of two pixels, each row padded to 12 bytes.
+        let rgba = [
+            1, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0, 0, //
+            9, 10, 11, 12, 13, 14, 15, 16,
+        ];
+        let (bgra, stride) = convert_pixel_rows(&rgba, 12, 2, 2, RGBA8, BGRA8).unwrap();
+        assert_eq!(stride, 8);
+        assert_eq!(
+            *bgra,
+            [3, 2, 1, 4, 7, 6, 5, 8, 11, 10, 9, 12, 15, 14, 13, 16]
+        );
+        let (round_trip, _) = convert_pixel_rows(&bgra, 8, 2, 2, BGRA8, RGBA8).unwrap();
+        assert_eq!(
+            *round_trip,
+            [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]
+        );
+
+        // Matching formats are passed through untouched.
+        let (same, stride) = convert_pixel_rows(&rgba, 12, 2, 2, RGBA8, RGBA8).unwrap();
+        assert!(matches!(same, Cow::Borrowed(_)));
+        assert_eq!(stride, 12);
+
+        assert!(convert_pixel_rows(&rgba[..19], 12, 2, 2, RGBA8, BGRA8).is_err());
+        assert!(convert_pixel_rows(&rgba, 4, 2, 2, RGBA8, BGRA8).is_err());
+        assert!(convert_pixel_rows(&rgba, 12, 2, 2, RGBA8, RGBA16F).is_err());
+    }
+
+    #[test]
+    fn test_back_buffer_sync_after_present() {
+        let source = GpuCanvasSource::new(
+            GpuTextureHandle::new(0x10, 64, 64),
+            GpuTextureHandle::new(0x20, 64, 64),
+        );
+        assert_eq!(source.back_buffer().native_handle, 0x20);
+        assert!(!source.back_buffer_needs_sync());
+
+        source.swap_buffers();
+        assert_eq!(source.back_buffer().native_handle, 0x10);
+        assert!(source.back_buffer_needs_sync());
+        source.mark_back_buffer_synced(source.content_version());
+        assert!(!source.back_buffer_needs_sync());
+    }
+
+    #[test]
+    fn test_on_release_runs_after_last_clone_drops() {
+        let source = GpuCanvasSource::new(
+            GpuTextureHandle::new(0x10, 64, 64),
+            GpuTextureHandle::new(0x20, 64, 64),
+        );
+        let released = Arc::new(AtomicBool::new(false));
+        source.on_release({
+            let released = released.clone();
+            move || released.store(true, Ordering::Release)
+        });
+
+        let clone = source.clone();
+        drop(source);
+        assert!(!released.load(Ordering::Acquire));
+        drop(clone);
+        assert!(released.load(Ordering::Acquire));
+    }
+
+    #[test]
+    fn test_clamp_source_rect() {
+        let texture_size = size(DevicePixels(1024), DevicePixels(512));
+        assert_eq!(
+            clamp_source_rect(None, texture_size),
+            device_bounds(0, 0, 1024, 512)
+        );
+        assert_eq!(
+            clamp_source_rect(Some(device_bounds(512, 0, 512, 256)), texture_size),
+            device_bounds(512, 0, 512, 256)
+        );
+        assert_eq!(
+            clamp_source_rect(Some(device_bounds(-10, 400, 100, 200)), texture_size),
+            device_bounds(0, 400, 90, 112)
+        );
+        assert!(clamp_source_rect(Some(device_bounds(2000, 0, 10, 10)), texture_size).is_empty());
+    }
+
+    #[test]
+    fn test_layout_gpu_texture_crop_for_each_object_fit() {
+        let texture_size = size(DevicePixels(1024), DevicePixels(512));
+        // The right half of the texture is a 512x512 viewport.
+        let source_rect = device_bounds(512, 0, 512, 512);
+        let bounds = Bounds::new(point(px(0.), px(0.)), size(px(200.), px(100.)));
+        let expected_uv = Bounds::new(point(0.5, 0.), size(0.5, 1.));
+
+        let cases = [
+            (ObjectFit::Fill, bounds),
+            (
+                ObjectFit::Contain,
+                Bounds::new(point(px(50.), px(0.)), size(px(100.), px(100.))),
+            ),
+            (
+                ObjectFit::Cover,
+                Bounds::new(point(px(0.), px(-50.)), size(px(200.), px(200.))),
+            ),
+            (
+                ObjectFit::ScaleDown,
+                Bounds::new(point(px(50.), px(0.)), size(px(100.), px(100.))),
+            ),
+            (
+                ObjectFit::None,
+                Bounds::new(point(px(0.), px(0.)), size(px(512.), px(512.))),
+            ),
+        ];
+        for (object_fit, expected_bounds) in cases {
+            let layout = layout_gpu_texture(
+                bounds,
+                texture_size,
+                source_rect,
+                object_fit,
+                CanvasRotation::None,
+                None,
+            );
+            assert_eq!(layout.bounds, expected_bounds, "{object_fit:?}");
+            assert_eq!(layout.uv, expected_uv, "{object_fit:?}");
+        }
+    }
+
+    #[test]
+    fn test_canvas_device_size_collapse_and_resume() {
+        let origin = point(px(0.), px(0.));
+        let expanded = Bounds::new(origin, size(px(200.), px(100.)));
+        assert_eq!(
+            canvas_device_size(expanded, 2.),
+            Some(size(DevicePixels(400), DevicePixels(200)))
+        );
+
+        for collapsed in [
+            size(px(0.), px(100.)),
+            size(px(200.), px(0.)),
+            size(px(0.4), px(0.4)),
+            size(px(-5.), px(100.)),
+        ] {
+            assert_eq!(canvas_device_size(Bounds::new(origin, collapsed), 2.), None);
+        }
+
+        assert_eq!(
+            canvas_device_size(Bounds::new(origin, size(px(1.), px(1.))), 1.),
+            Some(size(DevicePixels(1), DevicePixels(1)))
+        );
+        assert_eq!(
+            canvas_device_size(expanded, 1.),
+            Some(size(DevicePixels(200), DevicePixels(100)))
+        );
+    }
+
+    #[test]
+    fn test_layout_gpu_texture_rotation_swaps_aspect_ratio() {
+        let texture_size = size(DevicePixels(512), DevicePixels(256));
+        let source_rect = device_bounds(0, 0, 512, 256);
+        let bounds = Bounds::new(point(px(0.), px(0.)), size(px(200.), px(100.)));
+
+        for rotation in [CanvasRotation::None, CanvasRotation::Cw180] {
+            for object_fit in [ObjectFit::Contain, ObjectFit::Cover] {
+                let layout = layout_gpu_texture(
+                    bounds,
+                    texture_size,
+                    source_rect,
+                    object_fit,
+                    rotation,
+                    None,
+                );
+                assert_eq!(layout.bounds, bounds, "{rotation:?} {object_fit:?}");
+            }
+        }
+
+        for rotation in [CanvasRotation::Cw90, CanvasRotation::Cw270] {
+            let contain = layout_gpu_texture(
+                bounds,
+                texture_size,
+                source_rect,
+                ObjectFit::Contain,
+                rotation,
+                None,
+            );
+            assert_eq!(
+                contain.bounds,
+                Bounds::new(point(px(75.), px(0.)), size(px(50.), px(100.))),
+                "{rotation:?}"
+            );
+
+            let cover = layout_gpu_texture(
+                bounds,
+                texture_size,
+                source_rect,
+                ObjectFit::Cover,
+                rotation,
+                None,
+            );
+            assert_eq!(
+                cover.bounds,
+                Bounds::new(point(px(0.), px(-150.)), size(px(200.), px(400.))),
+                "{rotation:?}"
+            );
+            assert_eq!(cover.uv, Bounds::new(point(0., 0.), size(1., 1.)));
+        }
+    }
+
+    #[test]
+    fn test_orient_unit_point() {
+        let top_left = point(0., 0.);
+        let top_right = point(1., 0.);
+        let bottom_left = point(0., 1.);
+        let bottom_right = point(1., 1.);
+
+        // The texel shown at the displayed top-left corner for each orientation.
+        let cases = [
+            (CanvasRotation::None, false, top_left),
+            (CanvasRotation::Cw90, false, bottom_left),
+            (CanvasRotation::Cw180, false, bottom_right),
+            (CanvasRotation::Cw270, false, top_right),
+            (CanvasRotation::None, true, bottom_left),
+            (CanvasRotation::Cw90, true, top_left),
+            (CanvasRotation::Cw180, true, top_right),
+            (CanvasRotation::Cw270, true, bottom_right),
+        ];
+        for (rotation, flip_y, expected) in cases {
+            assert_eq!(
+                orient_unit_point(top_left, rotation, flip_y),
+                expected,
+                "{rotation:?} flip_y={flip_y}"
+            );
+        }
+
+        // A quarter turn clockwise moves the texture's top-left texel to the displayed top-right.
+        assert_eq!(
+            orient_unit_point(top_right, CanvasRotation::Cw90, false),
+            top_left
+        );
+    }
+
+    #[test]
+    fn test_bottom_left_origin_displays_upright() {
+        let texture_size = size(DevicePixels(4), DevicePixels(8));
+        // A marker occupying the top quarter of the upright image.
+        let marker = device_bounds(0, 0, 4, 2);
+
+        assert_eq!(
+            source_rect_in_storage(marker, texture_size, TextureOrigin::TopLeft),
+            marker
+        );
+        // Stored bottom-up, the marker lives in the last rows of the texture.
+        let stored = source_rect_in_storage(marker, texture_size, TextureOrigin::BottomLeft);
+        assert_eq!(stored, device_bounds(0, 6, 4, 2));
+
+        let handle = GpuTextureHandle::new(0, 4, 8).with_origin(TextureOrigin::BottomLeft);
+        let flip_y = GpuTextureOptions::default().flip_y != handle.is_bottom_up();
+        // The displayed top edge samples the end of the stored rows, where the marker's top is.
+        let texel = orient_unit_point(point(0., 0.), CanvasRotation::None, flip_y);
+        let stored_row = stored.origin.y.0 as f32 + texel.y * stored.size.height.0 as f32;
+        assert_eq!(stored_row, 8.);
+    }
+
+    #[test]
+    fn test_texel_position_accounts_for_letterboxing() {
+        // A 200x100 texture contained in a 100x100 element is letterboxed top and bottom.
+        let bounds = Bounds::new(point(px(10.), px(20.)), size(px(100.), px(100.)));
+        let texture = GpuTextureHandle::new(0, 200, 100);
+        let mapping = TexelMapping::new(bounds, &texture, &GpuTextureOptions::default(), 1.);
+
+        assert_eq!(mapping.texel_position(point(px(60.), px(30.))), None);
+        assert_eq!(
+            mapping.texel_position(point(px(10.), px(45.))),
+            Some(point(0., 0.))
+        );
+        assert_eq!(
+            mapping.texel_position(point(px(60.), px(70.))),
+            Some(point(100., 50.))
+        );
+        assert_eq!(mapping.texel_position(point(px(200.), px(70.))), None);
+
+        // Cropping and rotating map back into the upright texture.
+        let options = GpuTextureOptions {
+            source_rect: Some(device_bounds(50, 0, 100, 100)),
+            rotation: CanvasRotation::Cw90,
+            ..Default::default()
+        };
+        let mapping = TexelMapping::new(bounds, &texture, &options, 1.);
+        // The displayed top-left shows the crop's bottom-left texel after a clockwise turn.
+        assert_eq!(
+            mapping.texel_position(point(px(10.), px(20.))),
+            Some(point(50., 100.))
+        );
+    }
+
+    #[test]
+    fn test_intrinsic_size_follows_texture() {
+        let texture = GpuTextureHandle::new(0, 400, 200);
+        let options = GpuTextureOptions::default();
+        assert_eq!(
+            intrinsic_canvas_size(&texture, &options, 2.),
+            Some(size(px(200.), px(100.)))
+        );
+        let rotated = GpuTextureOptions {
+            rotation: CanvasRotation::Cw90,
+            ..Default::default()
+        };
+        assert_eq!(
+            intrinsic_canvas_size(&texture, &rotated, 1.),
+            Some(size(px(200.), px(400.)))
+        );
+        assert_eq!(
+            intrinsic_canvas_size(&GpuTextureHandle::new(0, 0, 0), &options, 1.),
+            None
+        );
+
+        // An unsized canvas takes the texture's width and derives its height from the aspect ratio.
+        let mut style = Style::default();
+        apply_intrinsic_size(&mut style, size(px(200.), px(100.)));
+        assert_eq!(style.size.width, px(200.).into());
+        assert_eq!(style.size.height, Length::Auto);
+        assert_eq!(style.aspect_ratio, Some(2.));
+
+        // Explicit sizes and aspect ratios win.
+        let mut style = Style::default();
+        style.size.height = px(50.).into();
+        style.aspect_ratio = Some(1.);
+        apply_intrinsic_size(&mut style, size(px(200.), px(100.)));
+        assert_eq!(style.size.width, Length::Auto);
+        assert_eq!(style.aspect_ratio, Some(1.));
+    }
+
+    #[test]
+    fn test_opaque_canvas_fades_with_inherited_opacity() {
+        let opaque = GpuTextureOptions {
+            alpha_mode: AlphaMode::Opaque,
+            ..Default::default()
+        };
+        assert_eq!(gpu_texture_opacity(&opaque, 1.), 1.);
+        assert_eq!(gpu_texture_opacity(&opaque, 0.5), 0.5);
+        assert_eq!(gpu_texture_opacity(&opaque, 0.), 0.);
+
+        let ignore = GpuTextureOptions {
+            opacity_behavior: OpaqueCanvasOpacity::Ignore,
+            ..opaque
+        };
+        assert_eq!(gpu_texture_opacity(&ignore, 0.5), 1.);
+        assert_eq!(gpu_texture_opacity(&ignore, 0.), 0.);
+
+        // The behavior only applies to opaque textures.
+        let premultiplied = GpuTextureOptions {
+            opacity_behavior: OpaqueCanvasOpacity::Ignore,
+            ..Default::default()
+        };
+        assert_eq!(gpu_texture_opacity(&premultiplied, 0.5), 0.5);
+    }
+
+    #[test]
+    fn test_pixel_perfect_layout_maps_texels_to_device_pixels() {
+        let scale_factor = 1.5;
+        let bounds = Bounds::new(point(px(10.3), px(10.)), size(px(100.), px(100.)));
+        let texture_size = size(DevicePixels(100), DevicePixels(60));
+        let source_rect = clamp_source_rect(None, texture_size);
+        for object_fit in [ObjectFit::Contain, ObjectFit::Cover, ObjectFit::Fill] {
+            let layout = layout_gpu_texture(
+                bounds,
+                texture_size,
+                source_rect,
+                object_fit,
+                CanvasRotation::None,
+                Some(scale_factor),
+            );
+            let device = layout.bounds.scale(scale_factor);
+            assert!((device.size.width.0 - 100.).abs() < 1e-3, "{object_fit:?}");
+            assert!((device.size.height.0 - 60.).abs() < 1e-3, "{object_fit:?}");
+            assert!((device.origin.x.0 - 40.).abs() < 1e-3, "{device:?}");
+            assert!((device.origin.y.0 - 60.).abs() < 1e-3, "{device:?}");
+        }
+    }
+
+    #[test]
+    fn test_resize_is_reported_once_per_device_size() {
+        let source = GpuCanvasSource::new(
+            GpuTextureHandle::new(0, 0, 0),
+            GpuTextureHandle::new(0, 0, 0),
+        );
+        assert_eq!(source.requested_size(), None);
+
+        let logical_bounds = Bounds::new(point(px(0.), px(0.)), size(px(200.), px(100.)));
+        let at_1x = canvas_device_size(logical_bounds, 1.);
+        assert!(source.take_size_change(at_1x));
+        assert!(!source.take_size_change(at_1x));
+        assert_eq!(source.requested_size(), at_1x);
+
+        // Moving to a 2x display doubles the device size without any layout change.
+        let at_2x = canvas_device_size(logical_bounds, 2.);
+        assert!(source.take_size_change(at_2x));
+        assert_eq!(
+            source.requested_size(),
+            Some(size(DevicePixels(400), DevicePixels(200)))
+        );
+
+        // Collapsing is a change too, but only once.
+        assert!(source.take_size_change(None));
+        assert!(!source.take_size_change(None));
+        assert_eq!(source.requested_size(), None);
+    }
+
+    #[test]
+    fn test_source_has_no_frame_until_first_present() {
+        let source = GpuCanvasSource::new(
+            GpuTextureHandle::new(0x10, 64, 64),
+            GpuTextureHandle::new(0x20, 64, 64),
+        );
+        assert!(!source.has_presented());
+        assert!(!source.has_displayable_frame());
+
+        source.swap_buffers();
+        assert!(source.has_presented());
+        assert!(source.has_displayable_frame());
+
+        // A presented but invalid handle still falls back to the placeholder.
+        let broken = GpuCanvasSource::new(
+            GpuTextureHandle::new(0, 64, 64),
+            GpuTextureHandle::new(0x20, 0, 0),
+        );
+        broken.set_active_buffer(0);
+        assert!(!broken.has_displayable_frame());
+        broken.set_active_buffer(1);
+        assert!(!broken.has_displayable_frame());
+    }
+
+    #[test]
+    fn test_latency_sample_matches_delayed_frames() {
+        let source = GpuCanvasSource::new(
+            GpuTextureHandle::new(0x10, 64, 64),
+            GpuTextureHandle::new(0x20, 64, 64),
+        );
+        let start = Instant::now();
+        let first = source.next_input_sequence_at(start);
+        let second = source.next_input_sequence_at(start + Duration::from_millis(5));
+        let third = source.next_input_sequence_at(start + Duration::from_millis(10));
+        assert_eq!(source.take_latency_sample(start), None);
+
+        // A plain swap carries no input, so there is nothing to measure.
+        source.swap_buffers();
+        assert_eq!(source.take_latency_sample(start), None);
+
+        // The producer's frame reflecting the first two inputs arrives 40ms after the second.
+        source.swap_buffers_with_input_sequence(second);
+        assert_eq!(source.displayed_input_sequence(), Some(second));
+        assert_eq!(
+            source.take_latency_sample(start + Duration::from_millis(45)),
+            Some(Duration::from_millis(40))
+        );
+        assert_eq!(
+            source.take_latency_sample(start + Duration::from_millis(50)),
+            None
+        );
+
+        // Out-of-order stamps never move the displayed sequence backwards.
+        source.swap_buffers_with_input_sequence(first);
+        assert_eq!(source.displayed_input_sequence(), Some(second));
+
+        source.swap_buffers_with_input_sequence(third);
+        assert_eq!(
+            source.take_latency_sample(start + Duration::from_millis(52)),
+            Some(Duration::from_millis(42))
+        );
+    }
+
+    #[test]
+    fn test_presents_are_coalesced_until_painted() {
+        let source = GpuCanvasSource::new(
+            GpuTextureHandle::new(0x10, 64, 64),
+            GpuTextureHandle::new(0x20, 64, 64),
+        );
+        let content_presented = source.observe_content();
+        assert_eq!(source.content_version(), 0);
+        assert!(!source.has_unpainted_content());
+        assert!(content_presented.try_recv().is_err());
+
+        source.swap_buffers();
+        source.swap_buffers();
+        source.set_active_buffer(0);
+        assert_eq!(source.content_version(), 3);
+        assert!(source.has_unpainted_content());
+        assert!(content_presented.try_recv().is_ok());
+        assert!(content_presented.try_recv().is_err());
+
+        source.mark_content_painted(Instant::now());
+        assert!(!source.has_unpainted_content());
+
+        // Disconnected windows are dropped on the next present.
+        drop(content_presented);
+        source.swap_buffers();
+        assert!(source.state.content_observers.lock().is_empty());
+    }
+
+    #[test]
+    fn test_present_stats() {
+        let source = GpuCanvasSource::new(
+            GpuTextureHandle::new(0x10, 64, 64),
+            GpuTextureHandle::new(0x20, 64, 64),
+        );
+        let start = source.state.epoch;
+        let ms = Duration::from_millis;
+        assert_eq!(source.present_stats(), PresentStats::default());
+
+        source.present(None, Some(100), start);
+        source.mark_content_painted(start + ms(4));
+        // Repainting the same frame is not another sample.
+        source.mark_content_painted(start + ms(20));
+        assert_eq!(
+            source.present_stats(),
+            PresentStats {
+                last_presented_frame: Some(100),
+                average_present_latency: Some(ms(4)),
+                skipped_frames: 0,
+            }
+        );
+
+        // Frame 101 is replaced before it is drawn.
+        source.present(None, Some(101), start + ms(30));
+        source.present(None, Some(102), start + ms(32));
+        source.mark_content_painted(start + ms(40));
+        assert_eq!(
+            source.present_stats(),
+            PresentStats {
+                last_presented_frame: Some(102),
+                average_present_latency: Some(ms(6)),
+                skipped_frames: 1,
+            }
+        );
+        assert_eq!(source.active_buffer().native_handle, 0x20);
+    }
+
+    #[test]
+    fn test_swap_buffers_at_waits_for_target() {
+        let source = GpuCanvasSource::new(
+            GpuTextureHandle::new(0x10, 64, 64),
+            GpuTextureHandle::new(0x20, 64, 64),
+        );
+        let start = source.state.epoch;
+        let ms = Duration::from_millis;
+
+        // A paint before the target keeps showing the current frame.
+        source.swap_buffers_at(start + ms(10));
+        assert_eq!(
+            source.promote_due_presents(start + ms(5)),
+            Some(start + ms(10))
+        );
+        assert_eq!(source.active_buffer().native_handle, 0x10);
+        assert!(!source.has_presented());
+
+        // A late paint shows it.
+        assert_eq!(source.promote_due_presents(start + ms(12)), None);
+        assert_eq!(source.active_buffer().native_handle, 0x20);
+        assert!(!source.has_pending_present());
+        source.mark_content_painted(start + ms(12));
+        assert_eq!(source.present_stats().average_present_latency, Some(ms(2)));
+
+        // Queuing a frame while one is pending replaces it, since both are in the back buffer.
+        source.swap_buffers_at(start + ms(20));
+        assert_eq!(source.back_buffer().native_handle, 0x10);
+        source.swap_buffers_at(start + ms(30));
+        assert_eq!(source.dropped_frames(), 1);
+        assert_eq!(
+            source.promote_due_presents(start + ms(25)),
+            Some(start + ms(30))
+        );
+        assert_eq!(source.active_buffer().native_handle, 0x20);
+        assert!(source.has_pending_present());
+
+        assert_eq!(source.promote_due_presents(start + ms(35)), None);
+        assert_eq!(source.active_buffer().native_handle, 0x10);
+        assert_eq!(source.content_version(), 2);
+        assert_eq!(source.dropped_frames(), 1);
+        assert!(!source.has_pending_present());
+    }
+
+    #[test]
+    fn test_dropped_and_repeated_frames() {
+        let texture = GpuTextureHandle::new(0, 16, 16);
+        let source = GpuCanvasSource::new(texture.clone(), texture);
+        let now = Instant::now();
+
+        source.present(None, None, now);
+        source.mark_content_painted(now);
+        source.mark_content_painted(now);
+        source.mark_content_painted(now);
+        assert_eq!(source.dropped_frames(), 0);
+        assert_eq!(source.repeated_frames(), 2);
+
+        source.present(None, None, now);
+        source.present(None, None, now);
+        source.present(None, None, now);
+        source.mark_content_painted(now);
+        assert_eq!(source.dropped_frames(), 2);
+        assert_eq!(source.repeated_frames(), 2);
+    }
+
+    #[test]
+    fn test_is_stale() {
+        let texture = GpuTextureHandle::new(0, 16, 16);
+        let source = GpuCanvasSource::new(texture.clone(), texture);
+        let start = source.state.epoch;
+        let ms = Duration::from_millis;
+
+        // An idle producer is never stale.
+        assert!(!source.is_stale_at(ms(100), start + ms(500)));
+
+        // A rendering producer that has not presented yet ages from the source's creation.
+        source.report_status(ProducerStatus::Rendering);
+        assert!(!source.is_stale_at(ms(100), start + ms(50)));
+        assert!(source.is_stale_at(ms(100), start + ms(500)));
+
+        source.present(None, None, start + ms(450));
+        assert!(!source.is_stale_at(ms(100), start + ms(500)));
+        assert!(source.is_stale_at(ms(100), start + ms(600)));
+
+        source.report_status(ProducerStatus::Error(1));
+        assert!(!source.is_stale_at(ms(100), start + ms(600)));
+    }
+
+    struct StallView {
+        source: GpuCanvasSource,
+        threshold: Duration,
+        reports: Rc<RefCell<Vec<bool>>>,
+    }
+
+    impl Render for StallView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            let reports = self.reports.clone();
+            div().size(px(200.)).child(
+                gpu_canvas(self.source.clone())
+                    .size(px(100.))
+                    .stall_threshold(self.threshold)
+                    .on_stalled(move |stalled, _, _| reports.borrow_mut().push(stalled)),
+            )
+        }
+    }
+
+    #[gpui::test]
+    fn test_on_stalled(cx: &mut TestAppContext) {
+        let texture = GpuTextureHandle::new(0, 16, 16);
+        let source = GpuCanvasSource::new(texture.clone(), texture);
+        source.report_status(ProducerStatus::Rendering);
+        source.swap_buffers();
+        let reports = Rc::new(RefCell::new(Vec::new()));
+        let (view, cx) = cx.add_window_view(|_, _| StallView {
+            source: source.clone(),
+            threshold: Duration::from_secs(3600),
+            reports: reports.clone(),
+        });
+        cx.update(|window, cx| {
+            window.draw(cx);
+        });
+        assert!(reports.borrow().is_empty());
+
+        view.update(cx, |view, cx| {
+            view.threshold = Duration::ZERO;
+            cx.notify();
+        });
+        cx.update(|window, cx| {
+            window.draw(cx);
+        });
+        assert_eq!(*reports.borrow(), vec![true]);
+
+        // Still stalled, so nothing new is reported.
+        view.update(cx, |_, cx| cx.notify());
+        cx.update(|window, cx| {
+            window.draw(cx);
+        });
+        assert_eq!(*reports.borrow(), vec![true]);
+
+        view.update(cx, |view, cx| {
+            view.threshold = Duration::from_secs(3600);
+            cx.notify();
+        });
+        source.swap_buffers();
+        cx.update(|window, cx| {
+            window.draw(cx);
+        });
+        assert_eq!(*reports.borrow(), vec![true, false]);
+    }
+}
@@ -0,0 +1,261 @@
+use crate::{
+    AnyElement, App, Bounds, Context, Element, ElementId, GlobalElementId, GpuCanvasSource,
+    InspectorElementId, IntoElement, LayoutId, ParentElement as _, Pixels, ProducerStatus,
+    SharedString, Styled as _, Task, Window, div, hsla, prelude::FluentBuilder as _, white,
+};
+use std::time::{Duration, Instant};
+
+/// How often the overlay samples the window's sources.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// An overlay listing every [`GpuCanvasSource`] displayed in the window, with the rate its
+/// producer presents frames, the rate they reach the screen, and how many were dropped.
+///
+/// The overlay samples the sources once a second and only redraws when a value changes, so
+/// leaving it on screen does not keep an otherwise idle window drawing.
+pub fn gpu_canvas_diagnostics() -> GpuCanvasDiagnostics {
+    GpuCanvasDiagnostics {
+        id: "gpu-canvas-diagnostics".into(),
+    }
+}
+
+/// An element showing frame statistics for the window's gpu canvas sources, see
+/// [`gpu_canvas_diagnostics`].
+pub struct GpuCanvasDiagnostics {
+    id: ElementId,
+}
+
+impl GpuCanvasDiagnostics {
+    /// Set the element's id, needed to show more than one overlay in a window.
+    pub fn id(mut self, id: impl Into<ElementId>) -> Self {
+        self.id = id.into();
+        self
+    }
+}
+
+/// One row of the overlay.
+#[derive(Clone, Debug, PartialEq)]
+struct CanvasDiagnosticsRow {
+    /// Frames presented per second
+    produced_fps: u32,
+    /// Presented frames drawn per second
+    drawn_fps: u32,
+    dropped_frames: u64,
+    producer_status: ProducerStatus,
+}
+
+/// Counters of one source at the previous sample.
+struct CanvasCounters {
+    source: GpuCanvasSource,
+    presented: u64,
+    painted: u64,
+}
+
+/// Turns the sources' ever-growing counters into per-second rates.
+#[derive(Default)]
+struct CanvasSampler {
+    previous: Vec<CanvasCounters>,
+    sampled_at: Option<Instant>,
+}
+
+impl CanvasSampler {
+    fn sample(&mut self, sources: &[GpuCanvasSource], now: Instant) -> Vec<CanvasDiagnosticsRow> {
+        let elapsed = self
+            .sampled_at
+            .replace(now)
+            .map(|sampled_at| now.saturating_duration_since(sampled_at).as_secs_f64())
+            .filter(|elapsed| *elapsed > 0.);
+        let rate = |current: u64, previous: Option<u64>| match (previous, elapsed) {
+            (Some(previous), Some(elapsed)) => {
+                (current.saturating_sub(previous) as f64 / elapsed).round() as u32
+            }
+            _ => 0,
+        };
+
+        let counters = sources
+            .iter()
+            .map(|source| CanvasCounters {
+                source: source.clone(),
+                presented: source.content_version(),
+                painted: source.painted_frames(),
+            })
+            .collect::<Vec<_>>();
+        let rows = counters
+            .iter()
+            .map(|current| {
+                let previous = self
+                    .previous
+                    .iter()
+                    .find(|previous| previous.source.ptr_eq(&current.source));
+                CanvasDiagnosticsRow {
+                    produced_fps: rate(current.presented, previous.map(|p| p.presented)),
+                    drawn_fps: rate(current.painted, previous.map(|p| p.painted)),
+                    dropped_frames: current.source.dropped_frames(),
+                    producer_status: current.source.producer_status(),
+                }
+            })
+            .collect();
+        self.previous = counters;
+        rows
+    }
+}
+
+/// The overlay's state, kept across frames.
+struct CanvasDiagnosticsState {
+    rows: Vec<CanvasDiagnosticsRow>,
+    _poll: Task<()>,
+}
+
+impl CanvasDiagnosticsState {
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let mut sampler = CanvasSampler::default();
+        let sources = window.gpu_canvas_sources().cloned().collect::<Vec<_>>();
+        let rows = sampler.sample(&sources, Instant::now());
+        let poll = cx.spawn_in(window, async move |this, cx| {
+            loop {
+                cx.background_executor().timer(SAMPLE_INTERVAL).await;
+                let updated = this.update_in(cx, |state, window, cx| {
+                    let sources = window.gpu_canvas_sources().cloned().collect::<Vec<_>>();
+                    let rows = sampler.sample(&sources, Instant::now());
+                    if rows != state.rows {
+                        state.rows = rows;
+                        cx.notify();
+                    }
+                });
+                if updated.is_err() {
+                    break;
+                }
+            }
+        });
+        Self { rows, _poll: poll }
+    }
+
+    fn render(&self) -> AnyElement {
+        let rows = self.rows.iter().enumerate().map(|(index, row)| {
+            let status = match row.producer_status {
+                ProducerStatus::Idle => "idle".to_string(),
+                ProducerStatus::Rendering => "rendering".to_string(),
+                ProducerStatus::Error(code) => format!("error {code}"),
+            };
+            div().child(SharedString::from(format!(
+                "canvas {index}: {} fps presented, {} fps drawn, {} dropped, {status}",
+                row.produced_fps, row.drawn_fps, row.dropped_frames
+            )))
+        });
+        div()
+            .flex()
+            .flex_col()
+            .p_1()
+            .bg(hsla(0., 0., 0., 0.7))
+            .text_color(white())
+            .text_xs()
+            .when(self.rows.is_empty(), |this| {
+                this.child("no gpu canvas sources displayed")
+            })
+            .children(rows)
+            .into_any_element()
+    }
+}
+
+impl Element for GpuCanvasDiagnostics {
+    type RequestLayoutState = AnyElement;
+    type PrepaintState = ();
+
+    fn id(&self) -> Option<ElementId> {
+        Some(self.id.clone())
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let state = window.use_keyed_state("state", cx, CanvasDiagnosticsState::new);
+        let mut child = state.read(cx).render();
+        let layout_id = child.request_layout(window, cx);
+        (layout_id, child)
+    }
+
+    fn prepaint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        _bounds: Bounds<Pixels>,
+        child: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        child.prepaint(window, cx);
+    }
+
+    fn paint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&InspectorElementId>,
+        _bounds: Bounds<Pixels>,
+        child: &mut Self::RequestLayoutState,
+        _prepaint: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        child.paint(window, cx);
+    }
+}
+
+impl IntoElement for GpuCanvasDiagnostics {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GpuTextureHandle;
+
+    #[test]
+    fn test_sampler_reports_rates_since_previous_sample() {
+        let source = GpuCanvasSource::new(
+            GpuTextureHandle::new(1, 64, 64),
+            GpuTextureHandle::new(2, 64, 64),
+        );
+        let mut sampler = CanvasSampler::default();
+        let start = Instant::now();
+
+        let rows = sampler.sample(std::slice::from_ref(&source), start);
+        assert_eq!(rows[0].produced_fps, 0);
+
+        source.report_status(ProducerStatus::Rendering);
+        for _ in 0..30 {
+            source.swap_buffers();
+        }
+        let rows = sampler.sample(
+            std::slice::from_ref(&source),
+            start + Duration::from_millis(500),
+        );
+        assert_eq!(
+            rows,
+            vec![CanvasDiagnosticsRow {
+                produced_fps: 60,
+                drawn_fps: 0,
+                dropped_frames: 29,
+                producer_status: ProducerStatus::Rendering,
+            }]
+        );
+
+        // Sources that stop being displayed drop out of the overlay.
+        assert!(
+            sampler
+                .sample(&[], start + Duration::from_secs(1))
+                .is_empty()
+        );
+    }
+}
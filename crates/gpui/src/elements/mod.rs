@@ -4,6 +4,7 @@ mod canvas;
 mod deferred;
 mod div;
 mod gpu_canvas;
+mod gpu_canvas_diagnostics;
 mod image_cache;
 mod img;
 mod list;
@@ -19,6 +20,7 @@ pub use canvas::*;
 pub use deferred::*;
 pub use div::*;
 pub use gpu_canvas::*;
+pub use gpu_canvas_diagnostics::*;
 pub use image_cache::*;
 pub use img::*;
 pub use list::*;
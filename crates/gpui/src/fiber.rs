@@ -3358,6 +3358,7 @@ struct PaintFrame {
 pub(crate) struct PrepaintState {
     accessed_entities: FxHashSet<EntityId>,
     line_layout_range: Range<LineLayoutIndex>,
+    gpu_canvas_sources_range: Range<usize>,
 }
 
 /// Cached paint commands for a subtree.
@@ -3369,6 +3370,7 @@ pub(crate) struct PaintList {
 #[derive(Clone)]
 pub(crate) struct PrepaintCapture {
     line_layout_start: LineLayoutIndex,
+    gpu_canvas_sources_start: usize,
 }
 
 #[derive(Clone)]
@@ -3777,6 +3779,7 @@ impl FiberRuntimeHandle<'_> {
     pub(crate) fn prepaint_capture(&self) -> PrepaintCapture {
         PrepaintCapture {
             line_layout_start: self.window.text_system().layout_index(),
+            gpu_canvas_sources_start: self.window.next_frame.gpu_canvas_sources.len(),
         }
     }
 
@@ -3794,6 +3797,8 @@ impl FiberRuntimeHandle<'_> {
         PrepaintState {
             accessed_entities,
             line_layout_range: capture.line_layout_start..self.window.text_system().layout_index(),
+            gpu_canvas_sources_range: capture.gpu_canvas_sources_start
+                ..self.window.next_frame.gpu_canvas_sources.len(),
         }
     }
 
@@ -3828,12 +3833,15 @@ impl FiberRuntimeHandle<'_> {
         };
 
         cx.entities.extend_accessed(&state.accessed_entities);
+        let gpu_canvas_sources_range = state.gpu_canvas_sources_range.clone();
 
         // Update the cached ranges on the fiber's prepaint state
         let line_layout_range = self.reuse_line_layouts(state.line_layout_range.clone());
+        let gpu_canvas_sources_range = self.reuse_gpu_canvas_sources(gpu_canvas_sources_range);
         if let Some(cache) = self.window.fiber.tree.paint_cache.get_mut((*fiber_id).into()) {
             if let Some(state) = cache.prepaint_state.as_mut() {
                 state.line_layout_range = line_layout_range;
+                state.gpu_canvas_sources_range = gpu_canvas_sources_range;
             }
         }
 
@@ -3915,6 +3923,20 @@ impl FiberRuntimeHandle<'_> {
         line_layout_start..line_layout_end
     }
 
+    /// Re-register the gpu canvas sources a replayed subtree displayed in the previous frame, so
+    /// they stay active and referenced by the frame.
+    fn reuse_gpu_canvas_sources(&mut self, range: Range<usize>) -> Range<usize> {
+        let start = self.window.next_frame.gpu_canvas_sources.len();
+        let Some(sources) = self.window.rendered_frame.gpu_canvas_sources.get(range) else {
+            return start..start;
+        };
+        self.window
+            .next_frame
+            .gpu_canvas_sources
+            .extend(sources.iter().cloned());
+        start..self.window.next_frame.gpu_canvas_sources.len()
+    }
+
     fn paint_after_children_segment(&mut self, frame: &mut PaintFrame, cx: &mut App) {
         let Some(style) = frame.style.as_ref() else {
             return;
@@ -386,6 +386,7 @@
             cache.prepaint_state = Some(super::PrepaintState {
                 accessed_entities: FxHashSet::default(),
                 line_layout_range: empty_range.clone(),
+                gpu_canvas_sources_range: 0..0,
             });
             cache.paint_list = Some(super::PaintList {
                 line_layout_range: empty_range,
@@ -2894,7 +2894,12 @@ impl DevicePixels {
     /// assert_eq!(total_bytes, 40); // 10 pixels * 4 bytes/pixel = 40 bytes
     /// ```
     pub fn to_bytes(self, bytes_per_pixel: u8) -> u32 {
-        self.0 as u32 * bytes_per_pixel as u32
+        u32::from(self).saturating_mul(bytes_per_pixel as u32)
+    }
+
+    /// Returns the value as a `u32`, or `None` if it is negative.
+    pub fn checked_u32(self) -> Option<u32> {
+        u32::try_from(self.0).ok()
     }
 }
 
@@ -2916,39 +2921,43 @@ impl From<i32> for DevicePixels {
     }
 }
 
+// Conversions to and from unsigned integers saturate rather than wrap: negative
+// values become zero and values beyond `i32::MAX` clamp to it. A wrapped size
+// would otherwise turn into a huge allocation or a negative extent downstream.
+
 impl From<u32> for DevicePixels {
     fn from(device_pixels: u32) -> Self {
-        DevicePixels(device_pixels as i32)
+        DevicePixels(i32::try_from(device_pixels).unwrap_or(i32::MAX))
     }
 }
 
 impl From<DevicePixels> for u32 {
     fn from(device_pixels: DevicePixels) -> Self {
-        device_pixels.0 as u32
+        device_pixels.0.max(0) as u32
     }
 }
 
 impl From<DevicePixels> for u64 {
     fn from(device_pixels: DevicePixels) -> Self {
-        device_pixels.0 as u64
+        device_pixels.0.max(0) as u64
     }
 }
 
 impl From<u64> for DevicePixels {
     fn from(device_pixels: u64) -> Self {
-        DevicePixels(device_pixels as i32)
+        DevicePixels(i32::try_from(device_pixels).unwrap_or(i32::MAX))
     }
 }
 
 impl From<DevicePixels> for usize {
     fn from(device_pixels: DevicePixels) -> Self {
-        device_pixels.0 as usize
+        device_pixels.0.max(0) as usize
     }
 }
 
 impl From<usize> for DevicePixels {
     fn from(device_pixels: usize) -> Self {
-        DevicePixels(device_pixels as i32)
+        DevicePixels(i32::try_from(device_pixels).unwrap_or(i32::MAX))
     }
 }
 
@@ -3919,4 +3928,22 @@ mod tests {
         // Test Case 3: Bounds intersecting with themselves
         assert!(bounds1.intersects(&bounds1));
     }
+
+    #[test]
+    fn test_device_pixels_unsigned_conversions_saturate() {
+        assert_eq!(u32::from(DevicePixels(-1)), 0);
+        assert_eq!(u64::from(DevicePixels(i32::MIN)), 0);
+        assert_eq!(usize::from(DevicePixels(i32::MAX)), i32::MAX as usize);
+
+        assert_eq!(DevicePixels::from(u32::MAX), DevicePixels(i32::MAX));
+        assert_eq!(DevicePixels::from(u64::MAX), DevicePixels(i32::MAX));
+        assert_eq!(DevicePixels::from(usize::MAX), DevicePixels(i32::MAX));
+        assert_eq!(DevicePixels::from(16384_u32), DevicePixels(16384));
+
+        assert_eq!(DevicePixels(-5).checked_u32(), None);
+        assert_eq!(DevicePixels(5).checked_u32(), Some(5));
+
+        assert_eq!(DevicePixels(-10).to_bytes(4), 0);
+        assert_eq!(DevicePixels(i32::MAX).to_bytes(16), u32::MAX);
+    }
 }
@@ -0,0 +1,441 @@
+//! Sharing a [`GpuCanvasSource`] between processes.
+//!
+//! The texture handles of a source can be duplicated into another process, but which buffer is
+//! active lives in the memory of the process that created the source. A small memory-mapped
+//! segment, named so both processes can find it, holds that state instead: the producer process
+//! presents through an [`IpcCanvasProducer`] and the UI process displays an
+//! [`IpcCanvasConsumer`]'s source.
+
+use std::{
+    fs::{File, OpenOptions},
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    thread::JoinHandle,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context as _, Result};
+use memmap2::MmapRaw;
+
+use crate::{App, GpuCanvasSource, GpuTextureHandle, ProducerStatus, Task, Window};
+
+/// Identifies an initialized segment, and its layout version.
+const SEGMENT_MAGIC: u64 = u64::from_le_bytes(*b"GPUICNV1");
+/// How often a producer proves it is alive.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_millis(100);
+/// How long a producer may go without a heartbeat before it is considered dead.
+const PRODUCER_TIMEOUT: Duration = Duration::from_secs(1);
+/// How often a connected consumer checks the segment for new frames.
+const POLL_INTERVAL: Duration = Duration::from_millis(2);
+
+/// The contents of a segment. Every field is an atomic, since both processes access it at once.
+#[repr(C)]
+struct SegmentHeader {
+    /// [`SEGMENT_MAGIC`] once the creator has initialized the segment
+    magic: AtomicU64,
+    /// Index of the buffer the producer last presented
+    active_buffer: AtomicU64,
+    /// Incremented every time the producer presents a frame
+    produced_frames: AtomicU64,
+    /// The producer's [`ProducerStatus`], in its packed form
+    producer_status: AtomicU64,
+    /// Milliseconds since the Unix epoch the producer was last known alive, 0 when no producer
+    /// is attached
+    producer_heartbeat: AtomicU64,
+    /// Whether the consumer may still be displaying each buffer
+    buffer_in_use: [AtomicU64; 2],
+}
+
+/// A mapped segment. The process that created it removes it when dropped.
+struct Segment {
+    map: MmapRaw,
+    /// The segment's file, if this process created it
+    created_path: Option<PathBuf>,
+}
+
+impl Segment {
+    fn create(name: &str) -> Result<Self> {
+        let path = segment_path(name)?;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .with_context(|| format!("creating canvas segment {path:?}"))?;
+        file.set_len(size_of::<SegmentHeader>() as u64)?;
+        let segment = Self {
+            map: MmapRaw::map_raw(&file)?,
+            created_path: Some(path),
+        };
+        segment
+            .header()
+            .magic
+            .store(SEGMENT_MAGIC, Ordering::Release);
+        Ok(segment)
+    }
+
+    fn open(name: &str) -> Result<Self> {
+        let path = segment_path(name)?;
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("opening canvas segment {path:?}"))?;
+        anyhow::ensure!(
+            file.metadata()?.len() >= size_of::<SegmentHeader>() as u64,
+            "canvas segment {path:?} is truncated"
+        );
+        let segment = Self {
+            map: MmapRaw::map_raw(&file)?,
+            created_path: None,
+        };
+        anyhow::ensure!(
+            segment.header().magic.load(Ordering::Acquire) == SEGMENT_MAGIC,
+            "{path:?} is not a canvas segment"
+        );
+        Ok(segment)
+    }
+
+    fn header(&self) -> &SegmentHeader {
+        // SAFETY: the mapping is page aligned, at least as large as the header, and lives as
+        // long as `self`. The header only holds atomics, so it may be shared with other
+        // processes.
+        unsafe { &*(self.map.as_ptr() as *const SegmentHeader) }
+    }
+}
+
+impl Drop for Segment {
+    fn drop(&mut self) {
+        if let Some(path) = self.created_path.take() {
+            std::fs::remove_file(path).ok();
+        }
+    }
+}
+
+fn segment_path(name: &str) -> Result<PathBuf> {
+    anyhow::ensure!(
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+        "canvas segment names may only contain ASCII letters, digits, '-' and '_', got {name:?}"
+    );
+    // Prefer memory-backed storage where the system provides it.
+    let shm = PathBuf::from("/dev/shm");
+    let directory = if cfg!(target_os = "linux") && shm.is_dir() {
+        shm
+    } else {
+        std::env::temp_dir()
+    };
+    Ok(directory.join(format!("gpui-canvas-{name}")))
+}
+
+fn millis_since_unix_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+        .max(1)
+}
+
+/// The producer's end of a [`GpuCanvasSource`] shared between processes. Presents frames into
+/// buffers whose handles were duplicated into this process, and keeps proving the process is
+/// alive so the consumer notices if it dies.
+pub struct IpcCanvasProducer {
+    segment: Arc<Segment>,
+    buffers: [GpuTextureHandle; 2],
+    heartbeat_running: Arc<AtomicBool>,
+    heartbeat: Option<JoinHandle<()>>,
+}
+
+impl IpcCanvasProducer {
+    /// Create the segment named `name` and attach to it.
+    pub fn create(
+        name: &str,
+        buffer0: GpuTextureHandle,
+        buffer1: GpuTextureHandle,
+    ) -> Result<Self> {
+        Ok(Self::attach(Segment::create(name)?, [buffer0, buffer1]))
+    }
+
+    /// Attach to the segment named `name`, created by the consumer.
+    pub fn open(name: &str, buffer0: GpuTextureHandle, buffer1: GpuTextureHandle) -> Result<Self> {
+        Ok(Self::attach(Segment::open(name)?, [buffer0, buffer1]))
+    }
+
+    fn attach(segment: Segment, buffers: [GpuTextureHandle; 2]) -> Self {
+        let segment = Arc::new(segment);
+        // The consumer sees the producer as alive as soon as it has attached.
+        segment.header().producer_heartbeat.store(
+            millis_since_unix_epoch(SystemTime::now()),
+            Ordering::Release,
+        );
+        let heartbeat_running = Arc::new(AtomicBool::new(true));
+        let heartbeat = std::thread::spawn({
+            let segment = segment.clone();
+            let heartbeat_running = heartbeat_running.clone();
+            move || {
+                while heartbeat_running.load(Ordering::Acquire) {
+                    segment.header().producer_heartbeat.store(
+                        millis_since_unix_epoch(SystemTime::now()),
+                        Ordering::Release,
+                    );
+                    std::thread::park_timeout(HEARTBEAT_INTERVAL);
+                }
+            }
+        });
+        Self {
+            segment,
+            buffers,
+            heartbeat_running,
+            heartbeat: Some(heartbeat),
+        }
+    }
+
+    /// The buffer the consumer displays.
+    pub fn active_buffer(&self) -> &GpuTextureHandle {
+        &self.buffers[self.active_index()]
+    }
+
+    /// The buffer to render the next frame into.
+    pub fn back_buffer(&self) -> &GpuTextureHandle {
+        &self.buffers[self.active_index() ^ 1]
+    }
+
+    fn active_index(&self) -> usize {
+        self.segment.header().active_buffer.load(Ordering::Acquire) as usize % 2
+    }
+
+    /// Whether the consumer may still be displaying buffer `index`, because it has not picked up
+    /// a frame presented since. Rendering into it then may show a partially drawn frame.
+    pub fn is_buffer_in_use(&self, index: usize) -> bool {
+        self.segment.header().buffer_in_use[index % 2].load(Ordering::Acquire) != 0
+    }
+
+    /// Present the back buffer, like [`GpuCanvasSource::swap_buffers`].
+    pub fn swap_buffers(&self) {
+        let header = self.segment.header();
+        let back = self.active_index() as u64 ^ 1;
+        header.active_buffer.store(back, Ordering::Release);
+        header.produced_frames.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Report the producer's lifecycle state, like [`GpuCanvasSource::report_status`].
+    pub fn report_status(&self, status: ProducerStatus) {
+        self.segment
+            .header()
+            .producer_status
+            .store(status.to_bits(), Ordering::Release);
+    }
+}
+
+impl Drop for IpcCanvasProducer {
+    fn drop(&mut self) {
+        self.heartbeat_running.store(false, Ordering::Release);
+        if let Some(heartbeat) = self.heartbeat.take() {
+            heartbeat.thread().unpark();
+            heartbeat.join().ok();
+        }
+        // Detaching cleanly leaves the last frame on screen.
+        let header = self.segment.header();
+        header
+            .producer_status
+            .store(ProducerStatus::Idle.to_bits(), Ordering::Release);
+        header.producer_heartbeat.store(0, Ordering::Release);
+    }
+}
+
+/// The consumer's end of a [`GpuCanvasSource`] shared between processes. Mirrors the frames the
+/// producer presents into a local source, which a [`gpu_canvas`](crate::gpu_canvas) displays
+/// like any other.
+///
+/// If the producer process dies without detaching, the source forgets its frame, so canvases
+/// fall back to their placeholder, and reports [`ProducerStatus::Idle`] until a producer
+/// presents again.
+#[derive(Clone)]
+pub struct IpcCanvasConsumer {
+    segment: Arc<Segment>,
+    source: GpuCanvasSource,
+    /// The producer's frame count when the segment was last synced
+    synced_frames: Arc<AtomicU64>,
+}
+
+impl IpcCanvasConsumer {
+    /// Create the segment named `name`, for a producer process to open.
+    pub fn create(
+        name: &str,
+        buffer0: GpuTextureHandle,
+        buffer1: GpuTextureHandle,
+    ) -> Result<Self> {
+        Ok(Self::attach(Segment::create(name)?, buffer0, buffer1))
+    }
+
+    /// Attach to the segment named `name`, created by the producer.
+    pub fn open(name: &str, buffer0: GpuTextureHandle, buffer1: GpuTextureHandle) -> Result<Self> {
+        Ok(Self::attach(Segment::open(name)?, buffer0, buffer1))
+    }
+
+    fn attach(segment: Segment, buffer0: GpuTextureHandle, buffer1: GpuTextureHandle) -> Self {
+        let this = Self {
+            segment: Arc::new(segment),
+            source: GpuCanvasSource::new(buffer0, buffer1),
+            synced_frames: Arc::new(AtomicU64::new(0)),
+        };
+        this.sync();
+        this
+    }
+
+    /// The local source mirroring the producer's frames.
+    pub fn source(&self) -> &GpuCanvasSource {
+        &self.source
+    }
+
+    /// Whether a producer is attached and alive.
+    pub fn producer_alive(&self) -> bool {
+        self.producer_alive_at(SystemTime::now())
+    }
+
+    fn producer_alive_at(&self, now: SystemTime) -> bool {
+        let heartbeat = self
+            .segment
+            .header()
+            .producer_heartbeat
+            .load(Ordering::Acquire);
+        heartbeat != 0
+            && millis_since_unix_epoch(now).saturating_sub(heartbeat)
+                < PRODUCER_TIMEOUT.as_millis() as u64
+    }
+
+    /// Bring the local source up to date with the segment, returning whether anything a canvas
+    /// displays changed.
+    pub fn sync(&self) -> bool {
+        self.sync_at(SystemTime::now())
+    }
+
+    fn sync_at(&self, now: SystemTime) -> bool {
+        let header = self.segment.header();
+        let previous_status = self.source.producer_status();
+        let produced_frames = header.produced_frames.load(Ordering::Acquire);
+        let synced_frames = self.synced_frames.swap(produced_frames, Ordering::AcqRel);
+        let heartbeat = header.producer_heartbeat.load(Ordering::Acquire);
+        if heartbeat != 0 && !self.producer_alive_at(now) {
+            let had_frame = self.source.has_presented();
+            self.source.clear_presented();
+            self.source.report_status(ProducerStatus::Idle);
+            return had_frame || previous_status != ProducerStatus::Idle;
+        }
+
+        let status = ProducerStatus::from_bits(header.producer_status.load(Ordering::Acquire));
+        self.source.report_status(status);
+        let presented = produced_frames != synced_frames;
+        if presented {
+            let active = header.active_buffer.load(Ordering::Acquire) as usize % 2;
+            self.source.set_active_buffer(active);
+            header.buffer_in_use[active].store(1, Ordering::Release);
+            header.buffer_in_use[active ^ 1].store(0, Ordering::Release);
+        }
+        presented || status != previous_status
+    }
+
+    /// Redraw `window` whenever the producer presents a frame or changes status. The segment is
+    /// polled every few milliseconds; dropping the returned task stops polling.
+    pub fn connect_to_window(&self, window: &Window, cx: &App) -> Task<()> {
+        let this = self.clone();
+        window.spawn(cx, async move |cx| {
+            loop {
+                cx.background_executor().timer(POLL_INTERVAL).await;
+                if this.sync() && cx.update(|window, _| window.refresh()).is_err() {
+                    break;
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    /// Set in the child process the cross-process test spawns, to the segment to produce into.
+    const CHILD_SEGMENT_VAR: &str = "GPUI_TEST_IPC_CANVAS_SEGMENT";
+
+    fn buffers() -> (GpuTextureHandle, GpuTextureHandle) {
+        (
+            GpuTextureHandle::new(0x10, 64, 64),
+            GpuTextureHandle::new(0x20, 64, 64),
+        )
+    }
+
+    #[test]
+    fn test_cross_process_presents() {
+        if let Ok(name) = std::env::var(CHILD_SEGMENT_VAR) {
+            let (buffer0, buffer1) = buffers();
+            let producer = IpcCanvasProducer::open(&name, buffer0, buffer1).unwrap();
+            producer.report_status(ProducerStatus::Rendering);
+            producer.swap_buffers();
+            producer.swap_buffers();
+            producer.swap_buffers();
+            // Exit without detaching, as if the process crashed.
+            std::mem::forget(producer);
+            return;
+        }
+
+        let name = format!("test-{}", std::process::id());
+        let (buffer0, buffer1) = buffers();
+        let consumer = IpcCanvasConsumer::create(&name, buffer0, buffer1).unwrap();
+        assert!(!consumer.producer_alive());
+        assert!(!consumer.sync());
+
+        let status = Command::new(std::env::current_exe().unwrap())
+            .args([
+                "--exact",
+                "gpu_canvas_ipc::tests::test_cross_process_presents",
+            ])
+            .env(CHILD_SEGMENT_VAR, &name)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        assert!(consumer.sync());
+        let source = consumer.source();
+        assert!(source.has_presented());
+        assert_eq!(source.active_buffer().native_handle, 0x20);
+        assert_eq!(source.producer_status(), ProducerStatus::Rendering);
+        let header = consumer.segment.header();
+        assert_eq!(header.buffer_in_use[1].load(Ordering::Acquire), 1);
+        assert_eq!(header.buffer_in_use[0].load(Ordering::Acquire), 0);
+
+        // The producer's heartbeat stopped with it.
+        let later = SystemTime::now() + PRODUCER_TIMEOUT * 2;
+        assert!(!consumer.producer_alive_at(later));
+        assert!(consumer.sync_at(later));
+        assert!(!source.has_presented());
+        assert_eq!(source.producer_status(), ProducerStatus::Idle);
+        assert!(!consumer.sync_at(later));
+    }
+
+    #[test]
+    fn test_producer_detaches_cleanly() {
+        let name = format!("test-detach-{}", std::process::id());
+        let (buffer0, buffer1) = buffers();
+        let producer = IpcCanvasProducer::create(&name, buffer0.clone(), buffer1.clone()).unwrap();
+        let consumer = IpcCanvasConsumer::open(&name, buffer0, buffer1).unwrap();
+        assert!(consumer.producer_alive());
+        assert!(IpcCanvasConsumer::open("not a name", buffers().0, buffers().1).is_err());
+
+        assert_eq!(producer.back_buffer().native_handle, 0x20);
+        producer.swap_buffers();
+        assert!(consumer.sync());
+        assert!(producer.is_buffer_in_use(1));
+
+        // A producer that detaches leaves its last frame on screen.
+        drop(producer);
+        let later = SystemTime::now() + PRODUCER_TIMEOUT * 2;
+        consumer.sync_at(later);
+        assert!(consumer.source().has_presented());
+        assert_eq!(consumer.source().active_buffer().native_handle, 0x20);
+    }
+}
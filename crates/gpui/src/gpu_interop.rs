@@ -0,0 +1,74 @@
+//! Types for displaying textures rendered by another graphics API inside GPUI.
+//!
+//! A producer, usually running on its own thread, renders into two shared textures and
+//! publishes them through a [`GpuCanvasSource`]. A [`gpu_canvas`] element displays whichever
+//! buffer is active, without copying it through the CPU.
+//!
+//! Displaying a source, redrawing whenever the producer presents and telling the producer what
+//! size to render at:
+//!
+//! ```rust,no_run
+//! use gpui::gpu_interop::*;
+//! use gpui::{Context, Task, Window, prelude::*};
+//!
+//! struct Viewport {
+//!     source: GpuCanvasSource,
+//!     _redraw: Task<()>,
+//! }
+//!
+//! impl Viewport {
+//!     fn new(source: GpuCanvasSource, window: &mut Window, cx: &mut Context<Self>) -> Self {
+//!         let _redraw = source.connect_to_window(window, cx);
+//!         Self { source, _redraw }
+//!     }
+//! }
+//!
+//! impl Render for Viewport {
+//!     fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+//!         gpu_canvas(self.source.clone())
+//!             .id("viewport")
+//!             .object_fit(ObjectFit::Contain)
+//!             .on_resize(|device_size, _, _| {
+//!                 // Ask the producer to reallocate its buffers at `device_size`, or to pause
+//!                 // while the canvas is collapsed and `device_size` is `None`.
+//!                 let _ = device_size;
+//!             })
+//!             .size_full()
+//!     }
+//! }
+//! ```
+//!
+//! Producing frames from another thread:
+//!
+//! ```rust,no_run
+//! use gpui::gpu_interop::{GpuCanvasSource, GpuTextureHandle, ProducerStatus};
+//!
+//! fn start_producer(front: isize, back: isize) -> GpuCanvasSource {
+//!     let source = GpuCanvasSource::new(
+//!         GpuTextureHandle::new(front, 1920, 1080),
+//!         GpuTextureHandle::new(back, 1920, 1080),
+//!     );
+//!     let producer = source.clone();
+//!     std::thread::spawn(move || {
+//!         producer.report_status(ProducerStatus::Rendering);
+//!         loop {
+//!             // Render into the buffer that is not active, then publish it.
+//!             producer.swap_buffers();
+//!         }
+//!     });
+//!     source
+//! }
+//! ```
+
+pub use crate::{
+    AlphaMode, CanvasRotation, GpuCanvas, GpuCanvasSource, GpuTextureFormat,
+    GpuTextureFormatSupport, GpuTextureHandle, GpuTextureOptions, IpcCanvasConsumer,
+    IpcCanvasProducer, ObjectFit, OpaqueCanvasOpacity, PresentInfo, PresentStats, ProducerStatus,
+    ResizeInfo, SharedTextureHandle, TextureFilter, TextureOrigin, YuvConversion, YuvMatrix,
+    YuvRange, gpu_canvas,
+};
+
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+pub use crate::EglDmaBufImage;
+#[cfg(target_os = "windows")]
+pub use crate::{GraphicsCapture, MediaFoundationFrames, WglDxInterop, WglDxTexture};
@@ -23,6 +23,8 @@ pub(crate) use platform_scheduler::PlatformScheduler;
 mod fiber;
 mod geometry;
 mod global;
+mod gpu_canvas_ipc;
+pub mod gpu_interop;
 mod identity;
 mod input;
 mod inspector;
@@ -87,6 +89,7 @@ pub use executor::*;
 pub(crate) use fiber::*;
 pub use geometry::*;
 pub use global::*;
+pub use gpu_canvas_ipc::*;
 pub use gpui_macros::{AppContext, IntoElement, Render, VisualContext, register_action, test};
 pub use http_client;
 pub(crate) use identity::*;
@@ -1,6 +1,6 @@
 use crate::{
     Bounds, Capslock, Context, Empty, IntoElement, Keystroke, Modifiers, Pixels, Point, Render,
-    Window, point, seal::Sealed,
+    SharedString, Window, point, seal::Sealed,
 };
 use smallvec::SmallVec;
 use std::{any::Any, fmt::Debug, ops::Deref, path::PathBuf};
@@ -407,6 +407,72 @@ impl MouseMoveEvent {
     }
 }
 
+/// Relative pointer motion, reported instead of [`MouseMoveEvent`] while the pointer is locked
+/// with [`Window::lock_pointer`](crate::Window::lock_pointer).
+#[derive(Clone, Debug, Default)]
+pub struct MouseMotionEvent {
+    /// The position of the hidden pointer on the window, which stays within the locked bounds.
+    pub position: Point<Pixels>,
+
+    /// How far the pointer moved since the previous event.
+    pub delta: Point<Pixels>,
+
+    /// The modifiers that were held down when the mouse was moved.
+    pub modifiers: Modifiers,
+}
+
+impl Sealed for MouseMotionEvent {}
+impl InputEvent for MouseMotionEvent {
+    fn to_platform_input(self) -> PlatformInput {
+        PlatformInput::MouseMotion(self)
+    }
+}
+impl MouseEvent for MouseMotionEvent {}
+
+/// Unaccelerated relative motion read directly from a mouse, reported in addition to the regular
+/// mouse events while raw mouse input is enabled with
+/// [`Window::set_raw_mouse_input`](crate::Window::set_raw_mouse_input). Devices may report these
+/// far more often than the display refreshes.
+#[derive(Clone, Debug, Default)]
+pub struct RawMouseMotionEvent {
+    /// How far the device moved since its previous report, in device units rather than pixels.
+    /// Pointer speed and acceleration settings are not applied.
+    pub delta: Point<f32>,
+
+    /// The button that went down in this report, if any.
+    pub pressed_button: Option<MouseButton>,
+
+    /// The button that went up in this report, if any.
+    pub released_button: Option<MouseButton>,
+
+    /// The modifiers that were held down when the report arrived.
+    pub modifiers: Modifiers,
+}
+
+impl Sealed for RawMouseMotionEvent {}
+impl InputEvent for RawMouseMotionEvent {
+    fn to_platform_input(self) -> PlatformInput {
+        PlatformInput::RawMouseMotion(self)
+    }
+}
+impl MouseEvent for RawMouseMotionEvent {}
+
+/// The keyboard layout of the window's input changed. Key events dispatched before this event
+/// were decoded under the previous layout, and those after it under the new one.
+#[derive(Clone, Debug, Default)]
+pub struct KeyboardLayoutChangedEvent {
+    /// The platform identifier of the new layout, as reported by
+    /// [`PlatformKeyboardLayout::id`](crate::PlatformKeyboardLayout::id).
+    pub layout_id: SharedString,
+}
+
+impl Sealed for KeyboardLayoutChangedEvent {}
+impl InputEvent for KeyboardLayoutChangedEvent {
+    fn to_platform_input(self) -> PlatformInput {
+        PlatformInput::KeyboardLayoutChanged(self)
+    }
+}
+
 /// A mouse wheel event from the platform.
 #[derive(Clone, Debug, Default)]
 pub struct ScrollWheelEvent {
@@ -601,6 +667,8 @@ pub enum PlatformInput {
     KeyUp(KeyUpEvent),
     /// The keyboard modifiers were changed.
     ModifiersChanged(ModifiersChangedEvent),
+    /// The keyboard layout was changed.
+    KeyboardLayoutChanged(KeyboardLayoutChangedEvent),
     /// The mouse was pressed.
     MouseDown(MouseDownEvent),
     /// The mouse was released.
@@ -609,6 +677,10 @@ pub enum PlatformInput {
     MousePressure(MousePressureEvent),
     /// The mouse was moved.
     MouseMove(MouseMoveEvent),
+    /// The mouse was moved while the pointer is locked.
+    MouseMotion(MouseMotionEvent),
+    /// Raw relative motion was read from a mouse.
+    RawMouseMotion(RawMouseMotionEvent),
     /// The mouse exited the window.
     MouseExited(MouseExitEvent),
     /// The scroll wheel was used.
@@ -617,15 +689,79 @@ pub enum PlatformInput {
     FileDrop(FileDropEvent),
 }
 
+/// The kind of a [`PlatformInput`], without its payload. Useful as a compact key, for example
+/// to index a fixed-size array of per-kind counters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PlatformInputKind {
+    /// [`PlatformInput::KeyDown`]
+    KeyDown,
+    /// [`PlatformInput::KeyUp`]
+    KeyUp,
+    /// [`PlatformInput::ModifiersChanged`]
+    ModifiersChanged,
+    /// [`PlatformInput::KeyboardLayoutChanged`]
+    KeyboardLayoutChanged,
+    /// [`PlatformInput::MouseDown`]
+    MouseDown,
+    /// [`PlatformInput::MouseUp`]
+    MouseUp,
+    /// [`PlatformInput::MousePressure`]
+    MousePressure,
+    /// [`PlatformInput::MouseMove`]
+    MouseMove,
+    /// [`PlatformInput::MouseMotion`]
+    MouseMotion,
+    /// [`PlatformInput::RawMouseMotion`]
+    RawMouseMotion,
+    /// [`PlatformInput::MouseExited`]
+    MouseExited,
+    /// [`PlatformInput::ScrollWheel`]
+    ScrollWheel,
+    /// [`PlatformInput::FileDrop`]
+    FileDrop,
+}
+
+impl PlatformInputKind {
+    /// The number of kinds, i.e. the length of an array indexed by [`PlatformInputKind::index`].
+    pub const COUNT: usize = 13;
+
+    /// A dense index in `0..PlatformInputKind::COUNT`.
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
 impl PlatformInput {
+    /// The kind of this event, without its payload.
+    pub fn kind(&self) -> PlatformInputKind {
+        match self {
+            PlatformInput::KeyDown(_) => PlatformInputKind::KeyDown,
+            PlatformInput::KeyUp(_) => PlatformInputKind::KeyUp,
+            PlatformInput::ModifiersChanged(_) => PlatformInputKind::ModifiersChanged,
+            PlatformInput::KeyboardLayoutChanged(_) => PlatformInputKind::KeyboardLayoutChanged,
+            PlatformInput::MouseDown(_) => PlatformInputKind::MouseDown,
+            PlatformInput::MouseUp(_) => PlatformInputKind::MouseUp,
+            PlatformInput::MousePressure(_) => PlatformInputKind::MousePressure,
+            PlatformInput::MouseMove(_) => PlatformInputKind::MouseMove,
+            PlatformInput::MouseMotion(_) => PlatformInputKind::MouseMotion,
+            PlatformInput::RawMouseMotion(_) => PlatformInputKind::RawMouseMotion,
+            PlatformInput::MouseExited(_) => PlatformInputKind::MouseExited,
+            PlatformInput::ScrollWheel(_) => PlatformInputKind::ScrollWheel,
+            PlatformInput::FileDrop(_) => PlatformInputKind::FileDrop,
+        }
+    }
+
     pub(crate) fn mouse_event(&self) -> Option<&dyn Any> {
         match self {
             PlatformInput::KeyDown { .. } => None,
             PlatformInput::KeyUp { .. } => None,
             PlatformInput::ModifiersChanged { .. } => None,
+            PlatformInput::KeyboardLayoutChanged(_) => None,
             PlatformInput::MouseDown(event) => Some(event),
             PlatformInput::MouseUp(event) => Some(event),
             PlatformInput::MouseMove(event) => Some(event),
+            PlatformInput::MouseMotion(event) => Some(event),
+            PlatformInput::RawMouseMotion(event) => Some(event),
             PlatformInput::MousePressure(event) => Some(event),
             PlatformInput::MouseExited(event) => Some(event),
             PlatformInput::ScrollWheel(event) => Some(event),
@@ -638,9 +774,12 @@ impl PlatformInput {
             PlatformInput::KeyDown(event) => Some(event),
             PlatformInput::KeyUp(event) => Some(event),
             PlatformInput::ModifiersChanged(event) => Some(event),
+            PlatformInput::KeyboardLayoutChanged(_) => None,
             PlatformInput::MouseDown(_) => None,
             PlatformInput::MouseUp(_) => None,
             PlatformInput::MouseMove(_) => None,
+            PlatformInput::MouseMotion(_) => None,
+            PlatformInput::RawMouseMotion(_) => None,
             PlatformInput::MousePressure(_) => None,
             PlatformInput::MouseExited(_) => None,
             PlatformInput::ScrollWheel(_) => None,
@@ -654,7 +793,8 @@ mod test {
 
     use crate::{
         self as gpui, AppContext as _, Context, FocusHandle, InteractiveElement, IntoElement,
-        KeyBinding, Keystroke, ParentElement, Render, TestAppContext, Window, div,
+        KeyBinding, KeyDownEvent, KeyboardLayoutChangedEvent, Keystroke, ParentElement, Render,
+        SharedString, TestAppContext, Window, div,
     };
 
     struct TestView {
@@ -721,4 +861,51 @@ mod test {
             })
             .unwrap();
     }
+
+    struct LayoutView {
+        focus_handle: FocusHandle,
+        decoded: Vec<(String, SharedString)>,
+    }
+
+    impl Render for LayoutView {
+        fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .track_focus(&self.focus_handle)
+                .on_key_down(cx.listener(|this, event: &KeyDownEvent, window, _| {
+                    this.decoded.push((
+                        event.keystroke.key.clone(),
+                        window.keyboard_layout_id().clone(),
+                    ))
+                }))
+        }
+    }
+
+    #[gpui::test]
+    fn test_keyboard_layout_change_is_ordered_with_key_events(cx: &mut TestAppContext) {
+        let (view, cx) = cx.add_window_view(|_, cx| LayoutView {
+            focus_handle: cx.focus_handle(),
+            decoded: Vec::new(),
+        });
+        let initial_layout = cx.update(|window, cx| {
+            window.focus(&view.read(cx).focus_handle);
+            window.keyboard_layout_id().clone()
+        });
+
+        cx.simulate_keystrokes("a");
+        cx.simulate_event(KeyboardLayoutChangedEvent {
+            layout_id: "00000407".into(),
+        });
+        cx.simulate_keystrokes("b");
+
+        view.read_with(cx, |view, _| {
+            assert_eq!(
+                view.decoded,
+                vec![
+                    ("a".to_string(), initial_layout),
+                    ("b".to_string(), SharedString::from("00000407")),
+                ]
+            );
+        });
+        cx.update(|window, _| assert_eq!(window.keyboard_layout_id(), "00000407"));
+    }
 }
@@ -81,6 +81,8 @@ pub use semantic_version::SemanticVersion;
 #[cfg(any(test, feature = "test-support"))]
 pub(crate) use test::*;
 #[cfg(target_os = "windows")]
+pub use windows::{GraphicsCapture, MediaFoundationFrames, WglDxInterop, WglDxTexture};
+#[cfg(target_os = "windows")]
 pub(crate) use windows::*;
 
 /// External window handle for bring-your-own-window mode
@@ -523,8 +525,13 @@ pub(crate) trait PlatformWindow: HasWindowHandle + HasDisplayHandle {
     fn on_hit_test_window_control(&self, callback: Box<dyn FnMut() -> Option<WindowControlArea>>);
     fn on_close(&self, callback: Box<dyn FnOnce()>);
     fn on_appearance_changed(&self, callback: Box<dyn FnMut()>);
+    fn on_visibility_changed(&self, _callback: Box<dyn FnMut(WindowVisibility)>) {}
     fn draw(&self, scene: &Scene);
     fn completed_frame(&self) {}
+    /// The interval between refreshes of the display the window is on, if known.
+    fn refresh_interval(&self) -> Option<Duration> {
+        None
+    }
     fn sprite_atlas(&self) -> Arc<dyn PlatformAtlas>;
 
     // macOS specific methods
@@ -607,6 +614,61 @@ pub(crate) trait PlatformWindow: HasWindowHandle + HasDisplayHandle {
     }
     fn set_client_inset(&self, _inset: Pixels) {}
     fn gpu_specs(&self) -> Option<GpuSpecs>;
+    fn gpu_texture_format_support(&self) -> Vec<crate::GpuTextureFormatSupport> {
+        Vec::new()
+    }
+    fn create_shared_texture(
+        &self,
+        _size: Size<DevicePixels>,
+        _format: crate::GpuTextureFormat,
+    ) -> Result<crate::GpuTextureHandle> {
+        Err(anyhow::anyhow!("shared textures are not supported on this platform"))
+    }
+    fn shared_textures(&self) -> Vec<crate::GpuTextureHandle> {
+        Vec::new()
+    }
+    fn release_shared_textures(&self, _textures: &[crate::GpuTextureHandle]) {}
+    fn invalidate_shared_texture(&self, _native_handle: isize) {}
+    fn output_color_space(&self) -> crate::OutputColorSpace {
+        crate::OutputColorSpace::Srgb
+    }
+    fn capture_bounds(
+        &self,
+        _bounds: Bounds<DevicePixels>,
+    ) -> oneshot::Receiver<Result<image::RgbaImage>> {
+        let (tx, rx) = oneshot::channel();
+        tx.send(Err(anyhow::anyhow!(
+            "capturing drawn frames is not supported on this platform"
+        )))
+        .ok();
+        rx
+    }
+    fn set_gpu_timing_enabled(&self, _enabled: bool) {}
+    fn gpu_texture_timings(&self, _native_handle: isize) -> Option<crate::GpuTextureTimings> {
+        None
+    }
+    fn write_shared_texture(
+        &self,
+        _texture: &crate::GpuTextureHandle,
+        _copy_from: Option<&crate::GpuTextureHandle>,
+        _region: Bounds<DevicePixels>,
+        _data: &[u8],
+        _stride: usize,
+    ) -> Result<()> {
+        Err(anyhow::anyhow!("shared textures are not supported on this platform"))
+    }
+
+    // Pointer lock, currently Windows only
+    fn lock_pointer(&self, _bounds: Bounds<Pixels>) -> bool {
+        false
+    }
+    fn unlock_pointer(&self) {}
+    fn is_pointer_locked(&self) -> bool {
+        false
+    }
+    fn set_raw_mouse_input(&self, _enabled: bool) -> bool {
+        false
+    }
 
     fn update_ime_position(&self, _bounds: Bounds<Pixels>);
 
@@ -827,7 +889,31 @@ pub(crate) trait PlatformAtlas: Send + Sync {
         key: &AtlasKey,
         build: &mut dyn FnMut() -> Result<Option<(Size<DevicePixels>, Cow<'a, [u8]>)>>,
     ) -> Result<Option<AtlasTile>>;
+    /// The tile stored for `key`, without building it if there is none.
+    fn get(&self, key: &AtlasKey) -> Option<AtlasTile>;
+    fn contains(&self, key: &AtlasKey) -> bool;
+    /// Whether the tile for `key` exists and its pixels have been uploaded to the GPU. Atlases
+    /// that upload at the start of the next frame return `false` until then.
+    fn is_ready(&self, key: &AtlasKey) -> bool;
     fn remove(&self, key: &AtlasKey);
+    /// Remove every tile whose key matches `predicate`, e.g. all glyphs after the UI font
+    /// changes, without the caller having to remember the keys.
+    fn remove_matching(&self, predicate: &dyn Fn(&AtlasKey) -> bool);
+}
+
+/// The largest width or height, in device pixels, that an atlas tile may have.
+/// This matches the texture size limit of every supported backend.
+pub(crate) const MAX_ATLAS_TILE_DIMENSION: i32 = 16384;
+
+/// Rejects tile sizes that no atlas can hold, before they reach the allocator
+/// or get cast to the unsigned types the graphics APIs expect.
+pub(crate) fn validate_atlas_tile_size(size: Size<DevicePixels>) -> Result<()> {
+    anyhow::ensure!(
+        (1..=MAX_ATLAS_TILE_DIMENSION).contains(&size.width.0)
+            && (1..=MAX_ATLAS_TILE_DIMENSION).contains(&size.height.0),
+        "invalid atlas tile size {size:?}"
+    );
+    Ok(())
 }
 
 struct AtlasTextureList<T> {
@@ -865,12 +951,121 @@ impl<T> AtlasTextureList<T> {
     }
 }
 
+/// How an atlas stores polychrome tiles. The renderers don't bind texture arrays yet, so they
+/// all use the default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[allow(dead_code)]
+pub(crate) enum PolychromeStorage {
+    /// A separate 2D texture for every atlas page (default)
+    #[default]
+    Textures,
+    /// One texture array whose layers are `layer_size` texels square, so that every polychrome
+    /// tile can be sampled through a single binding. The array grows as its layers fill up.
+    TextureArray { layer_size: u32 },
+}
+
+/// Allocates tiles across the layers of a texture array, adding layers as they fill up.
+#[cfg_attr(
+    not(any(target_os = "windows", target_os = "macos", test)),
+    allow(dead_code)
+)]
+pub(crate) struct AtlasLayers {
+    layer_size: i32,
+    max_layers: u32,
+    layers: Vec<AtlasLayer>,
+}
+
+struct AtlasLayer {
+    allocator: etagere::BucketedAtlasAllocator,
+    live_tiles: u32,
+}
+
+#[cfg_attr(
+    not(any(target_os = "windows", target_os = "macos", test)),
+    allow(dead_code)
+)]
+impl AtlasLayers {
+    pub(crate) fn new(layer_size: u32, max_layers: u32) -> Self {
+        Self {
+            layer_size: layer_size.min(MAX_ATLAS_TILE_DIMENSION as u32) as i32,
+            max_layers,
+            layers: Vec::new(),
+        }
+    }
+
+    /// The width and height of every layer.
+    pub(crate) fn layer_size(&self) -> Size<DevicePixels> {
+        size(DevicePixels(self.layer_size), DevicePixels(self.layer_size))
+    }
+
+    /// How many layers hold tiles or have held them. The texture array must have at least this
+    /// many.
+    pub(crate) fn layer_count(&self) -> u32 {
+        self.layers.len() as u32
+    }
+
+    /// Allocate a tile of `size` in the first layer with room for it, adding a layer if none
+    /// has. Returns the layer and the tile's id and bounds within it, or `None` if the tile is
+    /// larger than a layer or every layer is full.
+    pub(crate) fn allocate(
+        &mut self,
+        size: Size<DevicePixels>,
+    ) -> Option<(u32, TileId, Bounds<DevicePixels>)> {
+        if size.width.0 > self.layer_size || size.height.0 > self.layer_size {
+            return None;
+        }
+        let etagere_size = etagere::size2(size.width.0, size.height.0);
+        let (layer, allocation) = self
+            .layers
+            .iter_mut()
+            .enumerate()
+            .find_map(|(index, layer)| Some((index, layer.allocator.allocate(etagere_size)?)))
+            .or_else(|| {
+                if self.layers.len() as u32 >= self.max_layers {
+                    return None;
+                }
+                let mut allocator = etagere::BucketedAtlasAllocator::new(etagere::size2(
+                    self.layer_size,
+                    self.layer_size,
+                ));
+                let allocation = allocator.allocate(etagere_size)?;
+                self.layers.push(AtlasLayer {
+                    allocator,
+                    live_tiles: 0,
+                });
+                Some((self.layers.len() - 1, allocation))
+            })?;
+        self.layers[layer].live_tiles += 1;
+        let origin = allocation.rectangle.min;
+        Some((
+            layer as u32,
+            allocation.id.into(),
+            Bounds::new(point(DevicePixels(origin.x), DevicePixels(origin.y)), size),
+        ))
+    }
+
+    /// Free a tile returned by [`AtlasLayers::allocate`]. A layer left without tiles is reset
+    /// as a whole, undoing any fragmentation.
+    pub(crate) fn deallocate(&mut self, layer: u32, tile_id: TileId) {
+        let Some(layer) = self.layers.get_mut(layer as usize) else {
+            return;
+        };
+        layer.allocator.deallocate(tile_id.into());
+        layer.live_tiles = layer.live_tiles.saturating_sub(1);
+        if layer.live_tiles == 0 {
+            layer.allocator.clear();
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[repr(C)]
 pub(crate) struct AtlasTile {
     pub(crate) texture_id: AtlasTextureId,
     pub(crate) tile_id: TileId,
-    pub(crate) padding: u32,
+    /// The layer holding the tile when the texture is a texture array, otherwise 0. Takes the
+    /// place of a padding word, so the struct keeps the layout the shaders expect.
+    pub(crate) layer: u32,
     pub(crate) bounds: Bounds<DevicePixels>,
 }
 
@@ -1333,6 +1528,18 @@ pub enum WindowKind {
     Floating,
 }
 
+/// Whether a window's contents can currently be seen.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum WindowVisibility {
+    /// At least part of the window is on screen.
+    #[default]
+    Visible,
+    /// The window is open but entirely covered by other windows or off screen (macOS only).
+    Occluded,
+    /// The window is minimized or hidden.
+    Minimized,
+}
+
 /// The appearance of the window, as defined by the operating system.
 ///
 /// On macOS, this corresponds to named [`NSAppearance`](https://developer.apple.com/documentation/appkit/nsappearance)
@@ -1922,3 +2129,87 @@ impl From<String> for ClipboardString {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_atlas_layers_grow_and_reuse_layers() {
+        let mut layers = AtlasLayers::new(64, 2);
+        let tile_size = size(DevicePixels(64), DevicePixels(64));
+
+        let (first_layer, first_tile, first_bounds) = layers.allocate(tile_size).unwrap();
+        assert_eq!(first_layer, 0);
+        assert_eq!(first_bounds.size, tile_size);
+        let (second_layer, _, _) = layers.allocate(tile_size).unwrap();
+        assert_eq!(second_layer, 1);
+        assert_eq!(layers.layer_count(), 2);
+
+        // Every layer is full and no more may be added.
+        assert!(layers.allocate(tile_size).is_none());
+        // Tiles bigger than a layer never fit.
+        assert!(
+            layers
+                .allocate(size(DevicePixels(65), DevicePixels(1)))
+                .is_none()
+        );
+
+        // Freeing a tile makes room in its layer without adding another.
+        layers.deallocate(first_layer, first_tile);
+        assert_eq!(layers.allocate(tile_size).unwrap().0, 0);
+        assert_eq!(layers.layer_count(), 2);
+    }
+
+    #[test]
+    fn test_atlas_layers_reset_a_layer_once_its_tiles_are_freed() {
+        let mut layers = AtlasLayers::new(64, 1);
+        let quarter = size(DevicePixels(32), DevicePixels(32));
+        let full = size(DevicePixels(64), DevicePixels(64));
+
+        let tiles = (0..4)
+            .map(|_| layers.allocate(quarter).unwrap())
+            .collect::<Vec<_>>();
+        assert!(tiles.iter().all(|(layer, _, _)| *layer == 0));
+        assert!(layers.allocate(quarter).is_none());
+
+        // A layer with tiles left in it can't fit a full-size tile.
+        for (layer, tile_id, _) in &tiles[..3] {
+            layers.deallocate(*layer, *tile_id);
+        }
+        assert!(layers.allocate(full).is_none());
+
+        // Once the last tile is freed the whole layer is available again.
+        let (layer, tile_id, _) = tiles[3];
+        layers.deallocate(layer, tile_id);
+        let (layer, _, bounds) = layers.allocate(full).unwrap();
+        assert_eq!(layer, 0);
+        assert_eq!(bounds.origin, point(DevicePixels(0), DevicePixels(0)));
+        assert_eq!(layers.layer_count(), 1);
+
+        // Layers that were never added are ignored.
+        layers.deallocate(5, tile_id);
+    }
+
+    #[test]
+    fn test_atlas_get_does_not_build_missing_tiles() {
+        let atlas = TestAtlas::new();
+        let key = AtlasKey::Image(RenderImageParams {
+            image_id: crate::ImageId(1),
+            frame_index: 0,
+        });
+        assert_eq!(atlas.get(&key), None);
+        assert!(!atlas.contains(&key));
+
+        let tile = atlas
+            .get_or_insert_with(&key, &mut || {
+                Ok(Some((
+                    size(DevicePixels(4), DevicePixels(4)),
+                    Cow::Borrowed(&[0; 64][..]),
+                )))
+            })
+            .unwrap();
+        assert_eq!(atlas.get(&key), tile);
+        assert!(atlas.contains(&key));
+    }
+}
@@ -94,6 +94,7 @@ impl PlatformAtlas for BladeAtlas {
             let Some((size, bytes)) = build()? else {
                 return Ok(None);
             };
+            crate::platform::validate_atlas_tile_size(size)?;
             let tile = lock.allocate(size, key.texture_kind());
             lock.upload_texture(tile.texture_id, tile.bounds, &bytes);
             lock.tiles_by_key.insert(key.clone(), tile.clone());
@@ -101,32 +102,65 @@ impl PlatformAtlas for BladeAtlas {
         }
     }
 
+    fn get(&self, key: &AtlasKey) -> Option<AtlasTile> {
+        self.0.lock().tiles_by_key.get(key).cloned()
+    }
+
+    fn contains(&self, key: &AtlasKey) -> bool {
+        self.0.lock().tiles_by_key.contains_key(key)
+    }
+
+    fn is_ready(&self, key: &AtlasKey) -> bool {
+        let lock = self.0.lock();
+        lock.tiles_by_key.get(key).is_some_and(|tile| {
+            !lock
+                .uploads
+                .iter()
+                .any(|upload| upload.id == tile.texture_id && upload.bounds == tile.bounds)
+        })
+    }
+
     fn remove(&self, key: &AtlasKey) {
+        self.0.lock().remove(key);
+    }
+
+    fn remove_matching(&self, predicate: &dyn Fn(&AtlasKey) -> bool) {
         let mut lock = self.0.lock();
+        let keys = lock
+            .tiles_by_key
+            .keys()
+            .filter(|key| predicate(key))
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in &keys {
+            lock.remove(key);
+        }
+    }
+}
 
-        let Some(id) = lock.tiles_by_key.remove(key).map(|tile| tile.texture_id) else {
+impl BladeAtlasState {
+    fn remove(&mut self, key: &AtlasKey) {
+        let Some(id) = self.tiles_by_key.remove(key).map(|tile| tile.texture_id) else {
             return;
         };
 
-        let Some(texture_slot) = lock.storage[id.kind].textures.get_mut(id.index as usize) else {
+        let Some(texture_slot) = self.storage[id.kind].textures.get_mut(id.index as usize) else {
             return;
         };
 
         if let Some(mut texture) = texture_slot.take() {
             texture.decrement_ref_count();
             if texture.is_unreferenced() {
-                lock.storage[id.kind]
+                self.storage[id.kind]
                     .free_list
                     .push(texture.id.index as usize);
-                texture.destroy(&lock.gpu);
+                texture.destroy(&self.gpu);
             } else {
                 *texture_slot = Some(texture);
             }
         }
     }
-}
 
-impl BladeAtlasState {
     fn allocate(&mut self, size: Size<DevicePixels>, texture_kind: AtlasTextureKind) -> AtlasTile {
         {
             let textures = &mut self.storage[texture_kind];
@@ -333,7 +367,7 @@ impl BladeAtlasTexture {
         let tile = AtlasTile {
             texture_id: self.id,
             tile_id: allocation.id.into(),
-            padding: 0,
+            layer: 0,
             bounds: Bounds {
                 origin: allocation.rectangle.min.into(),
                 size,
@@ -55,6 +55,7 @@ pub fn export_texture_as_dmabuf(
             size,
             format: format as u32,
             stride,
+            color_space: crate::ColorSpace::SrgbNonLinear,
         }))
     }
 }
@@ -152,7 +152,7 @@ struct AtlasBounds {
 struct AtlasTile {
     texture_id: AtlasTextureId,
     tile_id: u32,
-    padding: u32,
+    layer: u32,
     bounds: AtlasBounds,
 }
 
@@ -8,7 +8,7 @@ use crate::{
 };
 use anyhow::Result;
 use core_graphics::display::CGDirectDisplayID;
-use std::ffi::c_void;
+use std::{ffi::c_void, time::Duration};
 use util::ResultExt;
 
 pub struct DisplayLink {
@@ -84,6 +84,17 @@ impl DisplayLink {
         }
         Ok(())
     }
+
+    /// The measured interval between refreshes of the display, once the link has been running
+    /// long enough to measure it.
+    pub fn refresh_interval(&mut self) -> Option<Duration> {
+        let seconds = unsafe {
+            self.display_link
+                .as_mut()?
+                .actual_output_video_refresh_period()
+        };
+        (seconds > 0.0).then(|| Duration::from_secs_f64(seconds))
+    }
 }
 
 impl Drop for DisplayLink {
@@ -227,6 +238,9 @@ mod sys {
         ) -> i32;
         pub fn CVDisplayLinkStart(display_link: &mut DisplayLinkRef) -> i32;
         pub fn CVDisplayLinkStop(display_link: &mut DisplayLinkRef) -> i32;
+        pub fn CVDisplayLinkGetActualOutputVideoRefreshPeriod(
+            display_link: &mut DisplayLinkRef,
+        ) -> f64;
         pub fn CVDisplayLinkRelease(display_link: *mut CVDisplayLink);
         pub fn CVDisplayLinkRetain(display_link: *mut CVDisplayLink) -> *mut CVDisplayLink;
     }
@@ -279,5 +293,10 @@ mod sys {
                 Ok(())
             }
         }
+
+        /// Apple docs: [CVDisplayLinkGetActualOutputVideoRefreshPeriod](https://developer.apple.com/documentation/corevideo/1456888-cvdisplaylinkgetactualoutputvide?language=objc)
+        pub unsafe fn actual_output_video_refresh_period(&mut self) -> f64 {
+            unsafe { CVDisplayLinkGetActualOutputVideoRefreshPeriod(self) }
+        }
     }
 }
@@ -1,6 +1,7 @@
 use crate::{
     AtlasKey, AtlasTextureId, AtlasTextureKind, AtlasTile, Bounds, DevicePixels, PlatformAtlas,
-    Point, Size, platform::AtlasTextureList,
+    Point, Size,
+    platform::{AtlasLayers, AtlasTextureList, PolychromeStorage},
 };
 use anyhow::{Context as _, Result};
 use collections::FxHashMap;
@@ -10,20 +11,43 @@ use metal::Device;
 use parking_lot::Mutex;
 use std::borrow::Cow;
 
+// Max array length on all modern Apple GPUs.
+const MAX_TEXTURE_ARRAY_LAYERS: u32 = 2048;
+
 pub(crate) struct MetalAtlas(Mutex<MetalAtlasState>);
 
 impl MetalAtlas {
     pub(crate) fn new(device: Device) -> Self {
+        Self::new_with_polychrome_storage(device, PolychromeStorage::default())
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn new_with_polychrome_storage(
+        device: Device,
+        polychrome_storage: PolychromeStorage,
+    ) -> Self {
         MetalAtlas(Mutex::new(MetalAtlasState {
             device: AssertSend(device),
             monochrome_textures: Default::default(),
             polychrome_textures: Default::default(),
+            polychrome_array: MetalAtlasTextureArray::new(polychrome_storage),
             tiles_by_key: Default::default(),
         }))
     }
 
+    /// The texture holding a tile. When polychrome tiles are stored in a texture array, this
+    /// is the whole array, indexed by [`AtlasTile::layer`].
     pub(crate) fn metal_texture(&self, id: AtlasTextureId) -> metal::Texture {
-        self.0.lock().texture(id).metal_texture.clone()
+        let lock = self.0.lock();
+        if id.kind == AtlasTextureKind::Polychrome
+            && let Some(texture) = lock
+                .polychrome_array
+                .as_ref()
+                .and_then(|array| array.metal_texture.as_ref())
+        {
+            return (**texture).clone();
+        }
+        lock.texture(id).metal_texture.clone()
     }
 }
 
@@ -31,6 +55,9 @@ struct MetalAtlasState {
     device: AssertSend<Device>,
     monochrome_textures: AtlasTextureList<MetalAtlasTexture>,
     polychrome_textures: AtlasTextureList<MetalAtlasTexture>,
+    /// Holds the polychrome tiles instead of `polychrome_textures` when the atlas was created
+    /// with [`PolychromeStorage::TextureArray`].
+    polychrome_array: Option<MetalAtlasTextureArray>,
     tiles_by_key: FxHashMap<AtlasKey, AtlasTile>,
 }
 
@@ -47,25 +74,71 @@ impl PlatformAtlas for MetalAtlas {
             let Some((size, bytes)) = build()? else {
                 return Ok(None);
             };
+            crate::platform::validate_atlas_tile_size(size)?;
             let tile = lock
                 .allocate(size, key.texture_kind())
                 .context("failed to allocate")?;
-            let texture = lock.texture(tile.texture_id);
-            texture.upload(tile.bounds, &bytes);
+            if tile.texture_id.kind == AtlasTextureKind::Polychrome
+                && let Some(array) = lock.polychrome_array.as_ref()
+            {
+                array.upload(&tile, &bytes);
+            } else {
+                let texture = lock.texture(tile.texture_id);
+                texture.upload(tile.bounds, &bytes);
+            }
             lock.tiles_by_key.insert(key.clone(), tile.clone());
             Ok(Some(tile))
         }
     }
 
+    fn get(&self, key: &AtlasKey) -> Option<AtlasTile> {
+        self.0.lock().tiles_by_key.get(key).cloned()
+    }
+
+    fn contains(&self, key: &AtlasKey) -> bool {
+        self.0.lock().tiles_by_key.contains_key(key)
+    }
+
+    fn is_ready(&self, key: &AtlasKey) -> bool {
+        // Tiles are uploaded as soon as they are allocated.
+        self.contains(key)
+    }
+
     fn remove(&self, key: &AtlasKey) {
+        self.0.lock().remove(key);
+    }
+
+    fn remove_matching(&self, predicate: &dyn Fn(&AtlasKey) -> bool) {
         let mut lock = self.0.lock();
-        let Some(id) = lock.tiles_by_key.get(key).map(|v| v.texture_id) else {
+        let keys = lock
+            .tiles_by_key
+            .keys()
+            .filter(|key| predicate(key))
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in &keys {
+            lock.remove(key);
+        }
+    }
+}
+
+impl MetalAtlasState {
+    fn remove(&mut self, key: &AtlasKey) {
+        let Some(tile) = self.tiles_by_key.remove(key) else {
             return;
         };
+        let id = tile.texture_id;
+
+        if id.kind == AtlasTextureKind::Polychrome
+            && let Some(array) = self.polychrome_array.as_mut()
+        {
+            array.layers.deallocate(tile.layer, tile.tile_id);
+            return;
+        }
 
         let textures = match id.kind {
-            AtlasTextureKind::Monochrome => &mut lock.monochrome_textures,
-            AtlasTextureKind::Polychrome => &mut lock.polychrome_textures,
+            AtlasTextureKind::Monochrome => &mut self.monochrome_textures,
+            AtlasTextureKind::Polychrome => &mut self.polychrome_textures,
             AtlasTextureKind::Subpixel => unreachable!(),
         };
 
@@ -82,20 +155,23 @@ impl PlatformAtlas for MetalAtlas {
 
             if texture.is_unreferenced() {
                 textures.free_list.push(id.index as usize);
-                lock.tiles_by_key.remove(key);
             } else {
                 *texture_slot = Some(texture);
             }
         }
     }
-}
 
-impl MetalAtlasState {
     fn allocate(
         &mut self,
         size: Size<DevicePixels>,
         texture_kind: AtlasTextureKind,
     ) -> Option<AtlasTile> {
+        if texture_kind == AtlasTextureKind::Polychrome
+            && let Some(array) = self.polychrome_array.as_mut()
+        {
+            return array.allocate(&self.device, size);
+        }
+
         {
             let textures = match texture_kind {
                 AtlasTextureKind::Monochrome => &mut self.monochrome_textures,
@@ -191,6 +267,114 @@ impl MetalAtlasState {
     }
 }
 
+/// A `D2Array` texture of BGRA8 layers, reallocated with twice the layers when it fills up.
+struct MetalAtlasTextureArray {
+    layers: AtlasLayers,
+    /// `None` until the first tile is allocated
+    metal_texture: Option<AssertSend<metal::Texture>>,
+    /// Used to copy the layers over when the array grows, created on first growth
+    command_queue: Option<AssertSend<metal::CommandQueue>>,
+    /// How many layers `metal_texture` has, at least `layers.layer_count()`
+    capacity: u32,
+}
+
+impl MetalAtlasTextureArray {
+    fn new(storage: PolychromeStorage) -> Option<Self> {
+        match storage {
+            PolychromeStorage::Textures => None,
+            PolychromeStorage::TextureArray { layer_size } => Some(Self {
+                layers: AtlasLayers::new(layer_size, MAX_TEXTURE_ARRAY_LAYERS),
+                metal_texture: None,
+                command_queue: None,
+                capacity: 0,
+            }),
+        }
+    }
+
+    fn allocate(&mut self, device: &Device, size: Size<DevicePixels>) -> Option<AtlasTile> {
+        let (layer, tile_id, bounds) = self.layers.allocate(size)?;
+        if self.layers.layer_count() > self.capacity {
+            self.grow(device, self.layers.layer_count());
+        }
+        Some(AtlasTile {
+            texture_id: AtlasTextureId {
+                index: 0,
+                kind: AtlasTextureKind::Polychrome,
+            },
+            tile_id,
+            layer,
+            bounds,
+        })
+    }
+
+    /// Replace the array with one of at least `min_layers` layers, copying over the existing
+    /// layers.
+    fn grow(&mut self, device: &Device, min_layers: u32) {
+        let capacity = (self.capacity * 2)
+            .max(min_layers)
+            .min(MAX_TEXTURE_ARRAY_LAYERS);
+        let layer_size = self.layers.layer_size();
+        let texture_descriptor = metal::TextureDescriptor::new();
+        texture_descriptor.set_texture_type(metal::MTLTextureType::D2Array);
+        texture_descriptor.set_width(layer_size.width.into());
+        texture_descriptor.set_height(layer_size.height.into());
+        texture_descriptor.set_array_length(capacity as u64);
+        texture_descriptor.set_pixel_format(metal::MTLPixelFormat::BGRA8Unorm);
+        texture_descriptor.set_usage(metal::MTLTextureUsage::ShaderRead);
+        let metal_texture = device.new_texture(&texture_descriptor);
+
+        if let Some(old_texture) = self.metal_texture.as_ref() {
+            let command_queue = self
+                .command_queue
+                .get_or_insert_with(|| AssertSend(device.new_command_queue()));
+            let command_buffer = command_queue.new_command_buffer();
+            let blit_encoder = command_buffer.new_blit_command_encoder();
+            let layer_size =
+                metal::MTLSize::new(layer_size.width.0 as u64, layer_size.height.0 as u64, 1);
+            for layer in 0..self.capacity as u64 {
+                blit_encoder.copy_from_texture(
+                    old_texture,
+                    layer,
+                    0,
+                    metal::MTLOrigin { x: 0, y: 0, z: 0 },
+                    layer_size,
+                    &metal_texture,
+                    layer,
+                    0,
+                    metal::MTLOrigin { x: 0, y: 0, z: 0 },
+                );
+            }
+            blit_encoder.end_encoding();
+            command_buffer.commit();
+            command_buffer.wait_until_completed();
+        }
+
+        self.metal_texture = Some(AssertSend(metal_texture));
+        self.capacity = capacity;
+    }
+
+    fn upload(&self, tile: &AtlasTile, bytes: &[u8]) {
+        let Some(metal_texture) = self.metal_texture.as_ref() else {
+            return;
+        };
+        let bounds = tile.bounds;
+        let region = metal::MTLRegion::new_2d(
+            bounds.origin.x.into(),
+            bounds.origin.y.into(),
+            bounds.size.width.into(),
+            bounds.size.height.into(),
+        );
+        metal_texture.replace_region_in_slice(
+            region,
+            0,
+            tile.layer as u64,
+            bytes.as_ptr() as *const _,
+            bounds.size.width.to_bytes(4) as u64,
+            0,
+        );
+    }
+}
+
 struct MetalAtlasTexture {
     id: AtlasTextureId,
     allocator: BucketedAtlasAllocator,
@@ -208,7 +392,7 @@ impl MetalAtlasTexture {
                 origin: allocation.rectangle.min.into(),
                 size,
             },
-            padding: 0,
+            layer: 0,
         };
         self.live_atlas_keys += 1;
         Some(tile)
@@ -280,7 +464,72 @@ impl From<etagere::Rectangle> for Bounds<DevicePixels> {
     }
 }
 
+/// Marks a Metal object as `Send`, which the `metal` crate's wrappers are not. Only implemented
+/// for objects Apple documents as safe to use from any thread: devices and resources may be
+/// shared between threads, unlike command encoders.
 #[derive(Deref, DerefMut)]
-struct AssertSend<T>(T);
+struct AssertSend<T: ThreadSafeMetalObject>(T);
+
+trait ThreadSafeMetalObject {}
+impl ThreadSafeMetalObject for metal::Device {}
+impl ThreadSafeMetalObject for metal::Texture {}
+impl ThreadSafeMetalObject for metal::CommandQueue {}
 
-unsafe impl<T> Send for AssertSend<T> {}
+unsafe impl<T: ThreadSafeMetalObject> Send for AssertSend<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ImageId, RenderImageParams, size};
+
+    fn image_key(id: usize) -> AtlasKey {
+        AtlasKey::Image(RenderImageParams {
+            image_id: ImageId(id),
+            frame_index: 0,
+        })
+    }
+
+    fn insert(atlas: &MetalAtlas, key: &AtlasKey) -> AtlasTile {
+        atlas
+            .get_or_insert_with(key, &mut || {
+                Ok(Some((
+                    size(DevicePixels(4), DevicePixels(4)),
+                    Cow::Borrowed(&[0; 64][..]),
+                )))
+            })
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_remove_matching_drops_every_matching_key() {
+        let Some(device) = Device::system_default() else {
+            return;
+        };
+        let atlas = MetalAtlas::new(device);
+        let tiles = (0..3)
+            .map(|id| insert(&atlas, &image_key(id)))
+            .collect::<Vec<_>>();
+        assert!(
+            tiles
+                .iter()
+                .all(|tile| tile.texture_id == tiles[0].texture_id)
+        );
+
+        atlas.remove_matching(&|key| *key != image_key(2));
+        assert!(!atlas.contains(&image_key(0)));
+        assert!(!atlas.contains(&image_key(1)));
+        assert_eq!(atlas.get(&image_key(2)), Some(tiles[2].clone()));
+
+        // Removing keys again must not release the texture out from under the remaining tile.
+        atlas.remove(&image_key(0));
+        atlas.remove_matching(&|key| *key == image_key(1));
+        assert_eq!(atlas.get(&image_key(2)), Some(tiles[2].clone()));
+
+        atlas.remove_matching(&|_| true);
+        assert!(!atlas.contains(&image_key(2)));
+        let state = atlas.0.lock();
+        assert_eq!(state.polychrome_textures.free_list, vec![0]);
+        assert!(state.tiles_by_key.is_empty());
+    }
+}
@@ -441,6 +441,7 @@ impl MetalRenderer {
                 io_surface: io_surface_ref,
                 size,
                 format: metal::MTLPixelFormat::BGRA8Unorm as u32,
+                color_space: crate::ColorSpace::SrgbNonLinear,
             }))
         }
     }
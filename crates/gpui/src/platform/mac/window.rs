@@ -6,8 +6,8 @@ use crate::{
     PlatformDisplay, PlatformInput, PlatformWindow, Point, PromptButton, PromptLevel,
     RequestFrameOptions, SharedString, Size, SystemWindowTab, WindowAppearance,
     WindowBackgroundAppearance, WindowBounds, WindowControlArea, WindowKind, WindowParams,
-    dispatch_get_main_queue, dispatch_sys::dispatch_async_f, platform::PlatformInputHandler, point,
-    px, size,
+    WindowVisibility, dispatch_get_main_queue, dispatch_sys::dispatch_async_f,
+    platform::PlatformInputHandler, point, px, size,
 };
 #[cfg(any(test, feature = "test-support"))]
 use anyhow::Result;
@@ -415,6 +415,7 @@ struct MacWindowState {
     should_close_callback: Option<Box<dyn FnMut() -> bool>>,
     close_callback: Option<Box<dyn FnOnce()>>,
     appearance_changed_callback: Option<Box<dyn FnMut()>>,
+    visibility_changed_callback: Option<Box<dyn FnMut(WindowVisibility)>>,
     input_handler: Option<PlatformInputHandler>,
     last_key_equivalent: Option<KeyDownEvent>,
     synthetic_drag_counter: usize,
@@ -628,6 +629,7 @@ impl MacWindow {
                 should_close_callback: None,
                 close_callback: None,
                 appearance_changed_callback: None,
+                visibility_changed_callback: None,
                 input_handler: None,
                 last_key_equivalent: None,
                 synthetic_drag_counter: 0,
@@ -804,6 +806,7 @@ impl MacWindow {
                 should_close_callback: None,
                 close_callback: None,
                 appearance_changed_callback: None,
+                visibility_changed_callback: None,
                 input_handler: None,
                 last_key_equivalent: None,
                 synthetic_drag_counter: 0,
@@ -1552,6 +1555,10 @@ impl PlatformWindow for MacWindow {
         self.0.lock().appearance_changed_callback = Some(callback);
     }
 
+    fn on_visibility_changed(&self, callback: Box<dyn FnMut(WindowVisibility)>) {
+        self.0.lock().visibility_changed_callback = Some(callback);
+    }
+
     fn tabbed_windows(&self) -> Option<Vec<SystemWindowTab>> {
         unsafe {
             let windows: id = msg_send![self.0.lock().native_window, tabbedWindows];
@@ -1621,6 +1628,10 @@ impl PlatformWindow for MacWindow {
         None
     }
 
+    fn refresh_interval(&self) -> Option<Duration> {
+        self.0.lock().display_link.as_mut()?.refresh_interval()
+    }
+
     fn resize_renderer(&self, physical_size: crate::Size<DevicePixels>) -> anyhow::Result<()> {
         self.0.lock().renderer.resize(physical_size)
     }
@@ -2079,8 +2090,8 @@ extern "C" fn handle_view_event(this: &Object, _: Sel, native_event: id) {
 
 extern "C" fn window_did_change_occlusion_state(this: &Object, _: Sel, _: id) {
     let window_state = unsafe { get_window_state(this) };
-    let lock = &mut *window_state.lock();
-    unsafe {
+    let mut lock = window_state.lock();
+    let visibility = unsafe {
         if lock
             .native_window
             .occlusionState()
@@ -2088,9 +2099,21 @@ extern "C" fn window_did_change_occlusion_state(this: &Object, _: Sel, _: id) {
         {
             lock.move_traffic_light();
             lock.start_display_link();
+            WindowVisibility::Visible
         } else {
             lock.stop_display_link();
+            let miniaturized: BOOL = msg_send![lock.native_window, isMiniaturized];
+            if miniaturized == YES {
+                WindowVisibility::Minimized
+            } else {
+                WindowVisibility::Occluded
+            }
         }
+    };
+    if let Some(mut callback) = lock.visibility_changed_callback.take() {
+        drop(lock);
+        callback(visibility);
+        window_state.lock().visibility_changed_callback = Some(callback);
     }
 }
 
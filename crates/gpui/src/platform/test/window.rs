@@ -1,9 +1,11 @@
 use crate::{
-    AnyWindowHandle, AtlasKey, AtlasTextureId, AtlasTile, Bounds, DispatchEventResult, GpuSpecs,
-    Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow,
-    Point, PromptButton, RequestFrameOptions, Size, TestPlatform, TileId, WindowAppearance,
-    WindowBackgroundAppearance, WindowBounds, WindowControlArea, WindowParams,
+    AnyWindowHandle, AtlasKey, AtlasTextureId, AtlasTile, Bounds, DevicePixels,
+    DispatchEventResult, GpuSpecs, GpuTextureFormat, GpuTextureHandle, Pixels, PlatformAtlas,
+    PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow, Point, PromptButton,
+    RequestFrameOptions, Size, TestPlatform, TileId, WindowAppearance, WindowBackgroundAppearance,
+    WindowBounds, WindowControlArea, WindowParams, WindowVisibility,
 };
+use anyhow::{Context as _, Result};
 use collections::HashMap;
 use parking_lot::Mutex;
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
@@ -27,8 +29,14 @@ pub(crate) struct TestWindowState {
     hover_status_change_callback: Option<Box<dyn FnMut(bool)>>,
     resize_callback: Option<Box<dyn FnMut(Size<Pixels>, f32)>>,
     moved_callback: Option<Box<dyn FnMut()>>,
+    visibility_changed_callback: Option<Box<dyn FnMut(WindowVisibility)>>,
     input_handler: Option<PlatformInputHandler>,
     is_fullscreen: bool,
+    scale_factor: f32,
+    /// Textures from `create_shared_texture`, kept as CPU pixels so tests can read what was
+    /// written to them
+    shared_textures: Vec<(GpuTextureHandle, Vec<u8>)>,
+    next_shared_texture: isize,
 }
 
 #[derive(Clone)]
@@ -72,8 +80,12 @@ impl TestWindow {
             hover_status_change_callback: None,
             resize_callback: None,
             moved_callback: None,
+            visibility_changed_callback: None,
             input_handler: None,
             is_fullscreen: false,
+            scale_factor: 2.0,
+            shared_textures: Vec::new(),
+            next_shared_texture: 1,
         })))
     }
 
@@ -89,6 +101,28 @@ impl TestWindow {
         self.0.lock().resize_callback = Some(callback);
     }
 
+    pub fn simulate_scale_factor_change(&mut self, scale_factor: f32) {
+        let mut lock = self.0.lock();
+        let Some(mut callback) = lock.resize_callback.take() else {
+            return;
+        };
+        lock.scale_factor = scale_factor;
+        let size = lock.bounds.size;
+        drop(lock);
+        callback(size, scale_factor);
+        self.0.lock().resize_callback = Some(callback);
+    }
+
+    pub fn simulate_visibility_change(&mut self, visibility: WindowVisibility) {
+        let mut lock = self.0.lock();
+        let Some(mut callback) = lock.visibility_changed_callback.take() else {
+            return;
+        };
+        drop(lock);
+        callback(visibility);
+        self.0.lock().visibility_changed_callback = Some(callback);
+    }
+
     pub(crate) fn simulate_active_status_change(&self, active: bool) {
         let mut lock = self.0.lock();
         let Some(mut callback) = lock.active_status_change_callback.take() else {
@@ -99,6 +133,16 @@ impl TestWindow {
         self.0.lock().active_status_change_callback = Some(callback);
     }
 
+    /// The pixels written to a texture created by `create_shared_texture`, rows tightly packed.
+    pub fn shared_texture_pixels(&self, native_handle: isize) -> Option<Vec<u8>> {
+        self.0
+            .lock()
+            .shared_textures
+            .iter()
+            .find(|(texture, _)| texture.native_handle == native_handle)
+            .map(|(_, pixels)| pixels.clone())
+    }
+
     pub fn simulate_input(&mut self, event: PlatformInput) -> bool {
         let mut lock = self.0.lock();
         let Some(mut callback) = lock.input_callback.take() else {
@@ -134,7 +178,7 @@ impl PlatformWindow for TestWindow {
     }
 
     fn scale_factor(&self) -> f32 {
-        2.0
+        self.0.lock().scale_factor
     }
 
     fn appearance(&self) -> WindowAppearance {
@@ -274,6 +318,10 @@ impl PlatformWindow for TestWindow {
 
     fn on_appearance_changed(&self, _callback: Box<dyn FnMut()>) {}
 
+    fn on_visibility_changed(&self, callback: Box<dyn FnMut(WindowVisibility)>) {
+        self.0.lock().visibility_changed_callback = Some(callback)
+    }
+
     fn draw(&self, _scene: &crate::Scene, _segment_pool: &crate::SceneSegmentPool) {}
 
     fn sprite_atlas(&self) -> sync::Arc<dyn crate::PlatformAtlas> {
@@ -302,6 +350,75 @@ impl PlatformWindow for TestWindow {
     fn gpu_specs(&self) -> Option<GpuSpecs> {
         None
     }
+
+    fn create_shared_texture(
+        &self,
+        size: Size<DevicePixels>,
+        format: GpuTextureFormat,
+    ) -> Result<GpuTextureHandle> {
+        let mut lock = self.0.lock();
+        let texture = GpuTextureHandle::new_with_format(
+            lock.next_shared_texture,
+            size.width.0 as u32,
+            size.height.0 as u32,
+            format,
+        );
+        lock.next_shared_texture += 1;
+        let pixels = vec![0; texture.size_in_bytes()];
+        lock.shared_textures.push((texture.clone(), pixels));
+        Ok(texture)
+    }
+
+    fn shared_textures(&self) -> Vec<GpuTextureHandle> {
+        let lock = self.0.lock();
+        lock.shared_textures
+            .iter()
+            .map(|(texture, _)| texture.clone())
+            .collect()
+    }
+
+    fn release_shared_textures(&self, textures: &[GpuTextureHandle]) {
+        self.0.lock().shared_textures.retain(|(texture, _)| {
+            !textures
+                .iter()
+                .any(|released| released.native_handle == texture.native_handle)
+        });
+    }
+
+    fn write_shared_texture(
+        &self,
+        texture: &GpuTextureHandle,
+        copy_from: Option<&GpuTextureHandle>,
+        region: Bounds<DevicePixels>,
+        data: &[u8],
+        stride: usize,
+    ) -> Result<()> {
+        let mut lock = self.0.lock();
+        let find = |textures: &[(GpuTextureHandle, Vec<u8>)], native_handle: isize| {
+            textures
+                .iter()
+                .position(|(texture, _)| texture.native_handle == native_handle)
+                .with_context(|| format!("no shared texture 0x{native_handle:X}"))
+        };
+        let target = find(&lock.shared_textures, texture.native_handle)?;
+        if let Some(copy_from) = copy_from {
+            let source = find(&lock.shared_textures, copy_from.native_handle)?;
+            let pixels = lock.shared_textures[source].1.clone();
+            lock.shared_textures[target].1 = pixels;
+        }
+
+        let bytes_per_pixel = texture.bytes_per_pixel() as usize;
+        let texture_stride = texture.width as usize * bytes_per_pixel;
+        let row_len = region.size.width.0 as usize * bytes_per_pixel;
+        let pixels = &mut lock.shared_textures[target].1;
+        for row in 0..region.size.height.0 as usize {
+            let offset = (region.origin.y.0 as usize + row) * texture_stride
+                + region.origin.x.0 as usize * bytes_per_pixel;
+            pixels[offset..offset + row_len]
+                .copy_from_slice(&data[row * stride..row * stride + row_len]);
+        }
+        Ok(())
+    }
 }
 
 pub(crate) struct TestAtlasState {
@@ -337,6 +454,7 @@ impl PlatformAtlas for TestAtlas {
         let Some((size, _)) = build()? else {
             return Ok(None);
         };
+        crate::platform::validate_atlas_tile_size(size)?;
 
         let mut state = self.0.lock();
         state.next_id += 1;
@@ -352,7 +470,7 @@ impl PlatformAtlas for TestAtlas {
                     kind: crate::AtlasTextureKind::Monochrome,
                 },
                 tile_id: TileId(tile_id),
-                padding: 0,
+                layer: 0,
                 bounds: crate::Bounds {
                     origin: Point::default(),
                     size,
@@ -363,8 +481,25 @@ impl PlatformAtlas for TestAtlas {
         Ok(Some(state.tiles[key].clone()))
     }
 
+    fn get(&self, key: &AtlasKey) -> Option<crate::AtlasTile> {
+        self.0.lock().tiles.get(key).cloned()
+    }
+
+    fn contains(&self, key: &AtlasKey) -> bool {
+        self.0.lock().tiles.contains_key(key)
+    }
+
+    fn is_ready(&self, key: &AtlasKey) -> bool {
+        self.contains(key)
+    }
+
     fn remove(&self, key: &AtlasKey) {
         let mut state = self.0.lock();
         state.tiles.remove(key);
     }
+
+    fn remove_matching(&self, predicate: &dyn Fn(&AtlasKey) -> bool) {
+        let mut state = self.0.lock();
+        state.tiles.retain(|key, _| !predicate(key));
+    }
 }
@@ -7,7 +7,12 @@ mod directx_renderer;
 mod dispatcher;
 mod display;
 mod events;
+mod frame_capture;
+mod gl_interop;
+mod gpu_timer;
+mod graphics_capture;
 mod keyboard;
+mod media_foundation;
 mod platform;
 mod system_settings;
 mod util;
@@ -24,7 +29,12 @@ pub(crate) use directx_renderer::*;
 pub(crate) use dispatcher::*;
 pub(crate) use display::*;
 pub(crate) use events::*;
+pub(crate) use frame_capture::*;
+pub use gl_interop::{WglDxInterop, WglDxTexture};
+pub(crate) use gpu_timer::*;
+pub use graphics_capture::GraphicsCapture;
 pub(crate) use keyboard::*;
+pub use media_foundation::MediaFoundationFrames;
 pub(crate) use platform::*;
 pub(crate) use system_settings::*;
 pub(crate) use util::*;
@@ -11,18 +11,35 @@ use windows::Win32::Graphics::{
 
 use crate::{
     AtlasKey, AtlasTextureId, AtlasTextureKind, AtlasTile, Bounds, DevicePixels, PlatformAtlas,
-    Point, Size, platform::AtlasTextureList,
+    Point, Size,
+    platform::{AtlasLayers, AtlasTextureList, PolychromeStorage},
 };
 
+// Max array size for DirectX. See:
+// https://learn.microsoft.com/en-us/windows/win32/direct3d11/overviews-direct3d-11-resources-limits
+const MAX_TEXTURE_ARRAY_LAYERS: u32 = 2048;
+
 pub(crate) struct DirectXAtlas(Mutex<DirectXAtlasState>);
 
+/// A tile's pixels, waiting for the render thread to copy them into the tile's texture.
+struct PendingUpload {
+    tile: AtlasTile,
+    bytes: Vec<u8>,
+}
+
 struct DirectXAtlasState {
     device: ID3D11Device,
     device_context: ID3D11DeviceContext,
     monochrome_textures: AtlasTextureList<DirectXAtlasTexture>,
     polychrome_textures: AtlasTextureList<DirectXAtlasTexture>,
     subpixel_textures: AtlasTextureList<DirectXAtlasTexture>,
+    /// Holds the polychrome tiles instead of `polychrome_textures` when the atlas was created
+    /// with [`PolychromeStorage::TextureArray`].
+    polychrome_array: Option<DirectXAtlasTextureArray>,
     tiles_by_key: FxHashMap<AtlasKey, AtlasTile>,
+    /// Uploads queued by `get_or_insert_with`, which may run on any thread. They are flushed
+    /// through the immediate context, which is not thread-safe, in [`DirectXAtlas::before_frame`].
+    uploads: Vec<PendingUpload>,
 }
 
 struct DirectXAtlasTexture {
@@ -34,23 +51,68 @@ struct DirectXAtlasTexture {
     live_atlas_keys: u32,
 }
 
+/// A `Texture2DArray` of BGRA8 layers, reallocated with twice the layers when it fills up.
+struct DirectXAtlasTextureArray {
+    layers: AtlasLayers,
+    /// `None` until the first tile is allocated
+    texture: Option<ID3D11Texture2D>,
+    view: [Option<ID3D11ShaderResourceView>; 1],
+    /// How many layers `texture` has, at least `layers.layer_count()`
+    capacity: u32,
+}
+
 impl DirectXAtlas {
     pub(crate) fn new(device: &ID3D11Device, device_context: &ID3D11DeviceContext) -> Self {
+        Self::new_with_polychrome_storage(device, device_context, PolychromeStorage::default())
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn new_with_polychrome_storage(
+        device: &ID3D11Device,
+        device_context: &ID3D11DeviceContext,
+        polychrome_storage: PolychromeStorage,
+    ) -> Self {
         DirectXAtlas(Mutex::new(DirectXAtlasState {
             device: device.clone(),
             device_context: device_context.clone(),
             monochrome_textures: Default::default(),
             polychrome_textures: Default::default(),
             subpixel_textures: Default::default(),
+            polychrome_array: DirectXAtlasTextureArray::new(polychrome_storage),
             tiles_by_key: Default::default(),
+            uploads: Vec::new(),
         }))
     }
 
+    /// Copy the pixels of the tiles allocated since the last frame into their textures. Must be
+    /// called on the render thread before drawing a frame that may sample them.
+    pub(crate) fn before_frame(&self) {
+        let mut lock = self.0.lock();
+        let uploads = std::mem::take(&mut lock.uploads);
+        for upload in uploads {
+            let tile = &upload.tile;
+            if tile.texture_id.kind == AtlasTextureKind::Polychrome
+                && let Some(array) = lock.polychrome_array.as_ref()
+            {
+                array.upload(&lock.device_context, tile, &upload.bytes);
+            } else if let Some(texture) = lock.try_texture(tile.texture_id) {
+                texture.upload(&lock.device_context, tile.bounds, &upload.bytes);
+            }
+        }
+    }
+
+    /// The view to sample a tile's texture through. When polychrome tiles are stored in a
+    /// texture array, this is a view of the whole array, indexed by [`AtlasTile::layer`].
     pub(crate) fn get_texture_view(
         &self,
         id: AtlasTextureId,
     ) -> [Option<ID3D11ShaderResourceView>; 1] {
         let lock = self.0.lock();
+        if id.kind == AtlasTextureKind::Polychrome
+            && let Some(array) = lock.polychrome_array.as_ref()
+        {
+            return array.view.clone();
+        }
         let tex = lock.texture(id);
         tex.view.clone()
     }
@@ -66,7 +128,11 @@ impl DirectXAtlas {
         lock.monochrome_textures = AtlasTextureList::default();
         lock.polychrome_textures = AtlasTextureList::default();
         lock.subpixel_textures = AtlasTextureList::default();
+        if let Some(array) = lock.polychrome_array.as_mut() {
+            *array = DirectXAtlasTextureArray::with_layer_size(array.layers.layer_size().width.0);
+        }
         lock.tiles_by_key.clear();
+        lock.uploads.clear();
     }
 }
 
@@ -85,27 +151,71 @@ impl PlatformAtlas for DirectXAtlas {
             let Some((size, bytes)) = build()? else {
                 return Ok(None);
             };
+            crate::platform::validate_atlas_tile_size(size)?;
             let tile = lock
                 .allocate(size, key.texture_kind())
                 .ok_or_else(|| anyhow::anyhow!("failed to allocate"))?;
-            let texture = lock.texture(tile.texture_id);
-            texture.upload(&lock.device_context, tile.bounds, &bytes);
+            lock.uploads.push(PendingUpload {
+                tile: tile.clone(),
+                bytes: bytes.into_owned(),
+            });
             lock.tiles_by_key.insert(key.clone(), tile.clone());
             Ok(Some(tile))
         }
     }
 
+    fn get(&self, key: &AtlasKey) -> Option<AtlasTile> {
+        self.0.lock().tiles_by_key.get(key).cloned()
+    }
+
+    fn contains(&self, key: &AtlasKey) -> bool {
+        self.0.lock().tiles_by_key.contains_key(key)
+    }
+
+    fn is_ready(&self, key: &AtlasKey) -> bool {
+        let lock = self.0.lock();
+        lock.tiles_by_key
+            .get(key)
+            .is_some_and(|tile| !lock.uploads.iter().any(|upload| upload.tile == *tile))
+    }
+
     fn remove(&self, key: &AtlasKey) {
+        self.0.lock().remove(key);
+    }
+
+    fn remove_matching(&self, predicate: &dyn Fn(&AtlasKey) -> bool) {
         let mut lock = self.0.lock();
+        let keys = lock
+            .tiles_by_key
+            .keys()
+            .filter(|key| predicate(key))
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in &keys {
+            lock.remove(key);
+        }
+    }
+}
 
-        let Some(id) = lock.tiles_by_key.remove(key).map(|tile| tile.texture_id) else {
+impl DirectXAtlasState {
+    fn remove(&mut self, key: &AtlasKey) {
+        let Some(tile) = self.tiles_by_key.remove(key) else {
             return;
         };
+        self.uploads.retain(|upload| upload.tile != tile);
+        let id = tile.texture_id;
+
+        if id.kind == AtlasTextureKind::Polychrome
+            && let Some(array) = self.polychrome_array.as_mut()
+        {
+            array.layers.deallocate(tile.layer, tile.tile_id);
+            return;
+        }
 
         let textures = match id.kind {
-            AtlasTextureKind::Monochrome => &mut lock.monochrome_textures,
-            AtlasTextureKind::Polychrome => &mut lock.polychrome_textures,
-            AtlasTextureKind::Subpixel => &mut lock.subpixel_textures,
+            AtlasTextureKind::Monochrome => &mut self.monochrome_textures,
+            AtlasTextureKind::Polychrome => &mut self.polychrome_textures,
+            AtlasTextureKind::Subpixel => &mut self.subpixel_textures,
         };
 
         let Some(texture_slot) = textures.textures.get_mut(id.index as usize) else {
@@ -116,20 +226,24 @@ impl PlatformAtlas for DirectXAtlas {
             texture.decrement_ref_count();
             if texture.is_unreferenced() {
                 textures.free_list.push(texture.id.index as usize);
-                lock.tiles_by_key.remove(key);
+                self.tiles_by_key.remove(key);
             } else {
                 *texture_slot = Some(texture);
             }
         }
     }
-}
 
-impl DirectXAtlasState {
     fn allocate(
         &mut self,
         size: Size<DevicePixels>,
         texture_kind: AtlasTextureKind,
     ) -> Option<AtlasTile> {
+        if texture_kind == AtlasTextureKind::Polychrome
+            && let Some(array) = self.polychrome_array.as_mut()
+        {
+            return array.allocate(&self.device, &self.device_context, size);
+        }
+
         {
             let textures = match texture_kind {
                 AtlasTextureKind::Monochrome => &mut self.monochrome_textures,
@@ -187,8 +301,8 @@ impl DirectXAtlasState {
             }
         }
         let texture_desc = D3D11_TEXTURE2D_DESC {
-            Width: size.width.0 as u32,
-            Height: size.height.0 as u32,
+            Width: u32::from(size.width),
+            Height: u32::from(size.height),
             MipLevels: 1,
             ArraySize: 1,
             Format: pixel_format,
@@ -244,6 +358,16 @@ impl DirectXAtlasState {
         }
     }
 
+    /// The texture with `id`, unless it has been freed.
+    fn try_texture(&self, id: AtlasTextureId) -> Option<&DirectXAtlasTexture> {
+        let textures = match id.kind {
+            AtlasTextureKind::Monochrome => &self.monochrome_textures,
+            AtlasTextureKind::Polychrome => &self.polychrome_textures,
+            AtlasTextureKind::Subpixel => &self.subpixel_textures,
+        };
+        textures.textures.get(id.index as usize)?.as_ref()
+    }
+
     fn texture(&self, id: AtlasTextureId) -> &DirectXAtlasTexture {
         match id.kind {
             crate::AtlasTextureKind::Monochrome => &self.monochrome_textures[id.index as usize]
@@ -259,6 +383,140 @@ impl DirectXAtlasState {
     }
 }
 
+impl DirectXAtlasTextureArray {
+    fn new(storage: PolychromeStorage) -> Option<Self> {
+        match storage {
+            PolychromeStorage::Textures => None,
+            PolychromeStorage::TextureArray { layer_size } => {
+                Some(Self::with_layer_size(layer_size as i32))
+            }
+        }
+    }
+
+    fn with_layer_size(layer_size: i32) -> Self {
+        Self {
+            layers: AtlasLayers::new(layer_size as u32, MAX_TEXTURE_ARRAY_LAYERS),
+            texture: None,
+            view: [None],
+            capacity: 0,
+        }
+    }
+
+    fn allocate(
+        &mut self,
+        device: &ID3D11Device,
+        device_context: &ID3D11DeviceContext,
+        size: Size<DevicePixels>,
+    ) -> Option<AtlasTile> {
+        let (layer, tile_id, bounds) = self.layers.allocate(size)?;
+        if self.layers.layer_count() > self.capacity
+            && self
+                .grow(device, device_context, self.layers.layer_count())
+                .is_none()
+        {
+            self.layers.deallocate(layer, tile_id);
+            return None;
+        }
+        Some(AtlasTile {
+            texture_id: AtlasTextureId {
+                index: 0,
+                kind: AtlasTextureKind::Polychrome,
+            },
+            tile_id,
+            layer,
+            bounds,
+        })
+    }
+
+    /// Replace the array with one of at least `min_layers` layers, copying over the existing
+    /// layers. Returns `None` if the device is lost, leaving the array as it was.
+    fn grow(
+        &mut self,
+        device: &ID3D11Device,
+        device_context: &ID3D11DeviceContext,
+        min_layers: u32,
+    ) -> Option<()> {
+        let capacity = (self.capacity * 2)
+            .max(min_layers)
+            .min(MAX_TEXTURE_ARRAY_LAYERS);
+        let layer_size = self.layers.layer_size();
+        let texture_desc = D3D11_TEXTURE2D_DESC {
+            Width: u32::from(layer_size.width),
+            Height: u32::from(layer_size.height),
+            MipLevels: 1,
+            ArraySize: capacity,
+            Format: DXGI_FORMAT_B8G8R8A8_UNORM,
+            SampleDesc: DXGI_SAMPLE_DESC {
+                Count: 1,
+                Quality: 0,
+            },
+            Usage: D3D11_USAGE_DEFAULT,
+            BindFlags: D3D11_BIND_SHADER_RESOURCE.0 as u32,
+            CPUAccessFlags: 0,
+            MiscFlags: 0,
+        };
+        let mut texture: Option<ID3D11Texture2D> = None;
+        unsafe {
+            device
+                .CreateTexture2D(&texture_desc, None, Some(&mut texture))
+                .ok()?;
+        }
+        let texture = texture.unwrap();
+        let view = unsafe {
+            let mut view = None;
+            device
+                .CreateShaderResourceView(&texture, None, Some(&mut view))
+                .ok()?;
+            [view]
+        };
+        if let Some(old_texture) = self.texture.as_ref() {
+            // With a single mip level, each layer is the subresource of the same index.
+            for layer in 0..self.capacity {
+                unsafe {
+                    device_context.CopySubresourceRegion(
+                        &texture,
+                        layer,
+                        0,
+                        0,
+                        0,
+                        old_texture,
+                        layer,
+                        None,
+                    );
+                }
+            }
+        }
+        self.texture = Some(texture);
+        self.view = view;
+        self.capacity = capacity;
+        Some(())
+    }
+
+    fn upload(&self, device_context: &ID3D11DeviceContext, tile: &AtlasTile, bytes: &[u8]) {
+        let Some(texture) = self.texture.as_ref() else {
+            return;
+        };
+        let bounds = tile.bounds;
+        unsafe {
+            device_context.UpdateSubresource(
+                texture,
+                tile.layer,
+                Some(&D3D11_BOX {
+                    left: u32::from(bounds.left()),
+                    top: u32::from(bounds.top()),
+                    front: 0,
+                    right: u32::from(bounds.right()),
+                    bottom: u32::from(bounds.bottom()),
+                    back: 1,
+                }),
+                bytes.as_ptr() as _,
+                bounds.size.width.to_bytes(4),
+                0,
+            );
+        }
+    }
+}
+
 impl DirectXAtlasTexture {
     fn allocate(&mut self, size: Size<DevicePixels>) -> Option<AtlasTile> {
         let allocation = self.allocator.allocate(size.into())?;
@@ -269,7 +527,7 @@ impl DirectXAtlasTexture {
                 origin: allocation.rectangle.min.into(),
                 size,
             },
-            padding: 0,
+            layer: 0,
         };
         self.live_atlas_keys += 1;
         Some(tile)
@@ -286,11 +544,11 @@ impl DirectXAtlasTexture {
                 &self.texture,
                 0,
                 Some(&D3D11_BOX {
-                    left: bounds.left().0 as u32,
-                    top: bounds.top().0 as u32,
+                    left: u32::from(bounds.left()),
+                    top: u32::from(bounds.top()),
                     front: 0,
-                    right: bounds.right().0 as u32,
-                    bottom: bounds.bottom().0 as u32,
+                    right: u32::from(bounds.right()),
+                    bottom: u32::from(bounds.bottom()),
                     back: 1,
                 }),
                 bytes.as_ptr() as _,
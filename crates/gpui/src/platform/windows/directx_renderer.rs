@@ -5,9 +5,10 @@ use std::{
 
 use ::util::ResultExt;
 use anyhow::{Context, Result};
+use collections::FxHashMap;
 use windows::{
     Win32::{
-        Foundation::HWND,
+        Foundation::{CloseHandle, HANDLE, HWND},
         Graphics::{
             Direct3D::*,
             Direct3D11::*,
@@ -16,7 +17,7 @@ use windows::{
             Dxgi::{Common::*, *},
         },
     },
-    core::Interface,
+    core::{Interface, PCWSTR},
 };
 
 use crate::{
@@ -30,6 +31,9 @@ pub(crate) const DISABLE_DIRECT_COMPOSITION: &str = "GPUI_DISABLE_DIRECT_COMPOSI
 const RENDER_TARGET_FORMAT: DXGI_FORMAT = DXGI_FORMAT_B8G8R8A8_UNORM;
 // This configuration is used for MSAA rendering on paths only, and it's guaranteed to be supported by DirectX 11.
 const PATH_MULTISAMPLE_COUNT: u32 = 4;
+/// Frames a cached shared texture view survives without being drawn. Producers recreate their
+/// textures on resize, and the views would otherwise keep the released ones alive.
+const SHARED_TEXTURE_EVICTION_FRAMES: u64 = 120;
 
 pub(crate) struct FontInfo {
     pub gamma_ratios: [f32; 4],
@@ -45,6 +49,55 @@ pub(crate) struct DirectXRenderer {
     pipelines: DirectXRenderPipelines,
     direct_composition: Option<DirectComposition>,
     font_info: &'static FontInfo,
+    /// Views of the shared textures drawn recently, with the frame each was last drawn in
+    shared_texture_views: FxHashMap<SharedTextureKey, (SharedTextureViews, u64)>,
+    frame: u64,
+    /// Textures allocated by `create_shared_texture`, with the handles given out for them. They
+    /// live until `release_shared_textures` is called for them, once their source is dropped, or
+    /// until the renderer is dropped with the window.
+    owned_shared_textures: Vec<(ID3D11Texture2D, GpuTextureHandle)>,
+    gpu_timer: GpuTimer,
+    frame_captures: FrameCaptures,
+}
+
+/// Identifies the resource behind a cached shared texture view. Producers may recreate a texture
+/// behind the same handle value, or the OS may reuse the value for an unrelated texture, so the
+/// size, format and the producer's generation are part of the key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct SharedTextureKey {
+    nt_handle: isize,
+    width: u32,
+    height: u32,
+    format: GpuTextureFormat,
+    generation: u64,
+}
+
+impl SharedTextureKey {
+    fn new(source: &crate::scene::SurfaceSource) -> Option<Self> {
+        let crate::scene::SurfaceSource::SharedTexture {
+            nt_handle,
+            width,
+            height,
+            format,
+            generation,
+        } = *source;
+        (width != 0 && height != 0).then_some(Self {
+            nt_handle,
+            width,
+            height,
+            format,
+            generation,
+        })
+    }
+}
+
+/// Views for sampling a shared texture. Planar YUV textures are read through one view per plane.
+#[derive(Clone)]
+struct SharedTextureViews {
+    /// The whole texture, or the luma plane of a YUV texture
+    color: ID3D11ShaderResourceView,
+    /// The chroma plane of a YUV texture
+    chroma: Option<ID3D11ShaderResourceView>,
 }
 
 /// Direct3D objects
@@ -83,11 +136,13 @@ struct DirectXRenderPipelines {
     underline_pipeline: PipelineState<Underline>,
     mono_sprites: PipelineState<MonochromeSprite>,
     poly_sprites: PipelineState<PolychromeSprite>,
+    gpu_textures: PipelineState<GpuTextureSprite>,
 }
 
 struct DirectXGlobalElements {
     global_params_buffer: [Option<ID3D11Buffer>; 1],
     sampler: [Option<ID3D11SamplerState>; 1],
+    nearest_sampler: [Option<ID3D11SamplerState>; 1],
 }
 
 struct DirectComposition {
@@ -172,6 +227,11 @@ impl DirectXRenderer {
             pipelines,
             direct_composition,
             font_info: Self::get_font_info(),
+            shared_texture_views: FxHashMap::default(),
+            frame: 0,
+            owned_shared_textures: Vec::new(),
+            gpu_timer: GpuTimer::default(),
+            frame_captures: FrameCaptures::default(),
         })
     }
 
@@ -213,6 +273,7 @@ impl DirectXRenderer {
             handle: handle.0 as *mut std::ffi::c_void,
             size,
             format: desc.Format.0 as u32,
+            color_space: ColorSpace::SrgbNonLinear,
         }))
     }
 
@@ -287,6 +348,10 @@ impl DirectXRenderer {
                 .log_err();
 
             ManuallyDrop::drop(&mut self.resources);
+            // Views and queries created on the lost device must not outlive it.
+            self.shared_texture_views.clear();
+            self.gpu_timer.reset();
+            self.frame_captures.reset();
             self.devices.device_context.OMSetRenderTargets(None, None);
             self.devices.device_context.ClearState();
             self.devices.device_context.Flush();
@@ -344,6 +409,7 @@ impl DirectXRenderer {
         let count = DRAW_COUNT.fetch_add(1, Ordering::Relaxed);
 
         self.pre_draw()?;
+        self.atlas.before_frame();
         for batch in scene.batches() {
             match batch {
                 PrimitiveBatch::Shadows(shadows) => self.draw_shadows(shadows),
@@ -371,6 +437,17 @@ impl DirectXRenderer {
                     scene.polychrome_sprites.len(),
                     scene.surfaces.len(),))?;
         }
+        self.gpu_timer.end_frame(&self.devices.device_context);
+        self.evict_unused_shared_textures();
+        self.frame_captures.after_draw(
+            &self.devices.device,
+            &self.devices.device_context,
+            &self.resources.render_target,
+            size(
+                DevicePixels(self.resources.width as i32),
+                DevicePixels(self.resources.height as i32),
+            ),
+        );
         self.present()
     }
 
@@ -631,162 +708,183 @@ impl DirectXRenderer {
 
     fn draw_surfaces(&mut self, surfaces: &[PaintSurface]) -> Result<()> {
         use crate::scene::SurfaceSource;
-        use windows::Win32::Graphics::Direct3D11::*;
-        use std::collections::HashMap;
-        use std::sync::OnceLock;
 
         if surfaces.is_empty() {
             return Ok(());
         }
 
-        static SHARED_TEXTURE_CACHE: OnceLock<std::sync::Mutex<HashMap<isize, ID3D11ShaderResourceView>>> = OnceLock::new();
-
         for surface in surfaces {
             match &surface.source {
                 #[cfg(target_os = "windows")]
-                SurfaceSource::SharedTexture { nt_handle, width, height } => {                    
-                    // Get or create shader resource view - use try_lock to avoid freezing
-                    let cache_mutex = SHARED_TEXTURE_CACHE.get_or_init(|| {
-                        std::sync::Mutex::new(HashMap::new())
-                    });
-                    
-                    let mut cache = match cache_mutex.try_lock() {
-                        Ok(cache) => cache,
-                        Err(_) => {
-                            println!("[DX-RENDERER] ⚠️  Cache busy, skipping");
-                            continue;
-                        }
+                SurfaceSource::SharedTexture { width, height, .. } => {
+                    let Some(key) = SharedTextureKey::new(&surface.source) else {
+                        continue;
                     };
-                    
-                    let srv = if let Some(srv) = cache.get(nt_handle) {
-                        // println!("[DX-RENDERER] ✅ Using cached SRV for handle 0x{:X}", nt_handle);
-                        srv.clone()
-                    } else {
-                        println!("[DX-RENDERER] 🔨 Creating new SRV for handle 0x{:X}...", nt_handle);
-                        // Open DX12 shared texture using DX11Device1::OpenSharedResource1
-                        // This is required for cross-API (DX12 -> DX11) sharing
-                        let device1: ID3D11Device1 = match self.devices.device.cast() {
-                            Ok(d) => d,
-                            Err(e) => {
-                                println!("[DX-RENDERER] ❌ Failed to get ID3D11Device1: {:?}", e);
-                                continue;
-                            }
-                        };
-
-                        let texture: ID3D11Texture2D = match unsafe {
-                            device1.OpenSharedResource1(
-                                windows::Win32::Foundation::HANDLE(*nt_handle as _),
-                            )
-                        } {
-                            Ok(tex) => {
-                                println!("[DX-RENDERER] ✅ Opened shared texture handle 0x{:X}", nt_handle);
-                                tex
-                            },
-                            Err(e) => {
-                                println!("[DX-RENDERER] ❌ Failed to open DX12 shared texture 0x{:X}: {:?}", nt_handle, e);
-                                continue;
-                            }
-                        };
-                        
-                        // Read back a few pixels to verify content
-                        unsafe {
-                            let mut desc = Default::default();
-                            texture.GetDesc(&mut desc);
-                            println!("[DX-RENDERER] 📊 Texture desc: {}x{}, format: {:?}, usage: {:?}", 
-                                desc.Width, desc.Height, desc.Format, desc.Usage);
-                        }
-
-                        // Create shader resource view
-                        let srv_desc = D3D11_SHADER_RESOURCE_VIEW_DESC {
-                            Format: RENDER_TARGET_FORMAT,
-                            ViewDimension: D3D11_SRV_DIMENSION_TEXTURE2D,
-                            Anonymous: D3D11_SHADER_RESOURCE_VIEW_DESC_0 {
-                                Texture2D: D3D11_TEX2D_SRV {
-                                    MostDetailedMip: 0,
-                                    MipLevels: 1,
-                                },
-                            },
-                        };
-
-                        let mut srv: Option<ID3D11ShaderResourceView> = None;
-                        if unsafe {
-                            self.devices.device.CreateShaderResourceView(&texture, Some(&srv_desc), Some(&mut srv))
-                        }.is_err() || srv.is_none() {
-                            println!("[DX-RENDERER] ❌ Failed to create SRV!");
-                            continue;
-                        }
-                        
-                        let srv = srv.unwrap();
-                        println!("[DX-RENDERER] ✅ Created SRV for handle 0x{:X}", nt_handle);
-                        cache.insert(*nt_handle, srv.clone());
-                        srv
+                    if surface.bounds.is_empty() {
+                        continue;
+                    }
+                    let Some(views) = self.shared_texture_views(key) else {
+                        continue;
                     };
-                    
-                    drop(cache); // Release lock before drawing
 
                     // Calculate display bounds
                     let texture_size = crate::size(
-                        DevicePixels::from(*width as i32), 
-                        DevicePixels::from(*height as i32)
+                        DevicePixels::from(*width as i32),
+                        DevicePixels::from(*height as i32),
                     );
-                    
-                    let scale_factor = self.resources.viewport[0].Width / self.resources.width as f32;
+
+                    let scale_factor =
+                        self.resources.viewport[0].Width / self.resources.width as f32;
                     let bounds_pixels = surface.bounds.map(|sp| Pixels(sp.0));
-                    
-                    let display_bounds = surface.object_fit.get_bounds(
+
+                    let layout = crate::layout_gpu_texture(
                         bounds_pixels,
                         texture_size,
+                        surface.source_rect,
+                        surface.object_fit,
+                        surface.rotation,
+                        // Surface bounds are already in device pixels.
+                        surface.pixel_perfect.then_some(1.),
                     );
-                    
-                    // Create sprite - match the structure used by draw_polychrome_sprites
-                    let sprite = PolychromeSprite {
+                    if layout.bounds.is_empty() {
+                        continue;
+                    }
+
+                    // While crossfading, the quad covers both textures, each laid out on its own.
+                    let mut transition = None;
+                    if let Some(from) = surface.transition.as_ref()
+                        && let Some(from_key) = SharedTextureKey::new(&from.source)
+                        && let Some(from_views) = self.shared_texture_views(from_key)
+                    {
+                        let from_layout = crate::layout_gpu_texture(
+                            bounds_pixels,
+                            crate::size(
+                                DevicePixels(from_key.width as i32),
+                                DevicePixels(from_key.height as i32),
+                            ),
+                            from.source_rect,
+                            surface.object_fit,
+                            surface.rotation,
+                            surface.pixel_perfect.then_some(1.),
+                        );
+                        if !from_layout.bounds.is_empty() {
+                            transition = Some((from, from_layout, from_views.color));
+                        }
+                    }
+                    let texture_bounds = layout.bounds.scale(scale_factor);
+
+                    let yuv_conversion = surface.yuv_conversion.unwrap_or_default();
+                    let sprite = GpuTextureSprite {
                         order: surface.order,
-                        pad: 0,
-                        opacity: 1.0,
-                        bounds: display_bounds.scale(scale_factor),
-                        content_mask: surface.content_mask.clone(),
-                        corner_radii: Corners::default(),
-                        tile: AtlasTile {
-                            texture_id: AtlasTextureId { 
-                                index: 0,
-                                kind: crate::AtlasTextureKind::Polychrome,
-                            },
-                            tile_id: TileId(0),
-                            padding: 0,
-                            bounds: crate::Bounds {
-                                origin: crate::point(DevicePixels(0), DevicePixels(0)),
-                                size: crate::size(DevicePixels(*width as i32), DevicePixels(*height as i32)),
-                            },
+                        alpha_mode: match surface.alpha_mode {
+                            crate::AlphaMode::Premultiplied => 0,
+                            crate::AlphaMode::Straight => 1,
+                            crate::AlphaMode::Opaque => 2,
+                        },
+                        bounds: match &transition {
+                            Some((_, from_layout, _)) => {
+                                layout.bounds.union(&from_layout.bounds).scale(scale_factor)
+                            }
+                            None => texture_bounds,
                         },
-                        grayscale: false,
+                        content_mask: surface.content_mask.bounds,
+                        clip_bounds: surface.bounds,
+                        corner_radii: surface.corner_radii,
+                        uv: layout.uv,
+                        orientation: surface.rotation.quarter_turns()
+                            | if surface.flip_y { 1 << 2 } else { 0 },
+                        grayscale: surface.grayscale as u32,
+                        opacity: surface.opacity,
+                        tint: surface.tint.unwrap_or_else(crate::white),
+                        // The texture's own format decides whether it needs converting, in case
+                        // the handle declared the wrong one.
+                        yuv_matrix: match (views.chroma.is_some(), yuv_conversion.matrix) {
+                            (false, _) => 0,
+                            (true, crate::YuvMatrix::Bt601) => 1,
+                            (true, crate::YuvMatrix::Bt709) => 2,
+                        },
+                        yuv_full_range: (yuv_conversion.range == crate::YuvRange::Full) as u32,
+                        mask: surface.mask_color.is_some() as u32,
+                        mask_color: surface.mask_color.unwrap_or_else(crate::white),
+                        texture_bounds,
+                        transition_progress: transition
+                            .as_ref()
+                            .map_or(1., |(from, _, _)| from.progress),
+                        transition_orientation: transition.as_ref().map_or(0, |(from, _, _)| {
+                            surface.rotation.quarter_turns() | if from.flip_y { 1 << 2 } else { 0 }
+                        }),
+                        transition_bounds: transition
+                            .as_ref()
+                            .map_or(texture_bounds, |(_, from_layout, _)| {
+                                from_layout.bounds.scale(scale_factor)
+                            }),
+                        transition_uv: transition
+                            .as_ref()
+                            .map_or(layout.uv, |(_, from_layout, _)| from_layout.uv),
+                        color_transform: self
+                            .color_transform(surface.color_space, surface.tone_mapping),
+                        transition_color_transform: transition.as_ref().map_or(
+                            0,
+                            |(from, _, _)| {
+                                self.color_transform(from.color_space, surface.tone_mapping)
+                            },
+                        ),
+                        exposure: surface
+                            .tone_mapping
+                            .map_or(1., |tone_mapping| tone_mapping.exposure),
+                    };
+                    let sampler = match surface.filter {
+                        crate::TextureFilter::Linear => &self.globals.sampler,
+                        crate::TextureFilter::Nearest => &self.globals.nearest_sampler,
                     };
 
                     // Update and draw
-                    if self.pipelines.poly_sprites.update_buffer(
-                        &self.devices.device,
-                        &self.devices.device_context,
-                        &[sprite],
-                    ).is_err() {
-                        println!("[DX-RENDERER] ❌ Failed to update buffer!");
+                    if self
+                        .pipelines
+                        .gpu_textures
+                        .update_buffer(
+                            &self.devices.device,
+                            &self.devices.device_context,
+                            &[sprite],
+                        )
+                        .is_err()
+                    {
+                        log::error!("Failed to update the gpu texture sprite buffer");
                         continue;
                     }
 
-                    if let Err(e) = self.pipelines.poly_sprites.draw_with_texture(
+                    unsafe {
+                        self.devices
+                            .device_context
+                            .PSSetShaderResources(2, Some(&[views.chroma]));
+                        self.devices.device_context.PSSetShaderResources(
+                            3,
+                            Some(&[transition.map(|(_, _, from_view)| from_view)]),
+                        );
+                    }
+                    let drawn = self.gpu_timer.time(
+                        &self.devices.device,
                         &self.devices.device_context,
-                        &[Some(srv)],
-                        &self.resources.viewport,
-                        &self.globals.global_params_buffer,
-                        &self.globals.sampler,
-                        1,
-                    ) {
-                        println!("[DX-RENDERER] ❌ Draw failed: {:?}", e);
-                    } else {
-                        // Draw succeeded
+                        key.nt_handle,
+                        GpuTimedWork::Draw,
+                        || {
+                            self.pipelines.gpu_textures.draw_with_texture(
+                                &self.devices.device_context,
+                                &[Some(views.color)],
+                                &self.resources.viewport,
+                                &self.globals.global_params_buffer,
+                                sampler,
+                                1,
+                            )
+                        },
+                    );
+                    if let Err(e) = drawn {
+                        log::error!("Failed to draw shared texture 0x{:X}: {e:?}", key.nt_handle);
                     }
                 }
                 #[allow(unreachable_patterns)]
                 _ => {
-                    println!("[DX-RENDERER] ⚠️  Non-Windows surface source");
+                    log::warn!("Skipping a surface source the DirectX renderer can't draw");
                 }
             }
         }
@@ -794,6 +892,108 @@ impl DirectXRenderer {
         Ok(())
     }
 
+    /// Views of the shared texture identified by `key`, opening it and creating them on first use.
+    fn shared_texture_views(&mut self, key: SharedTextureKey) -> Option<SharedTextureViews> {
+        let nt_handle = key.nt_handle;
+        if let Some((views, last_used)) = self.shared_texture_views.get_mut(&key) {
+            *last_used = self.frame;
+            return Some(views.clone());
+        }
+        // Open DX12 shared texture using DX11Device1::OpenSharedResource1
+        // This is required for cross-API (DX12 -> DX11) sharing
+        let device1: ID3D11Device1 = match self.devices.device.cast() {
+            Ok(d) => d,
+            Err(e) => {
+                log::error!("Failed to get ID3D11Device1: {e:?}");
+                return None;
+            }
+        };
+
+        let texture: ID3D11Texture2D = match unsafe {
+            device1.OpenSharedResource1(windows::Win32::Foundation::HANDLE(nt_handle as _))
+        } {
+            Ok(texture) => texture,
+            Err(e) => {
+                log::error!("Failed to open shared texture 0x{nt_handle:X}: {e:?}");
+                return None;
+            }
+        };
+
+        // View the texture in its own format; producers may share RGBA, BGRA
+        // or half-float textures, or NV12 read through a view per plane.
+        let mut texture_desc = D3D11_TEXTURE2D_DESC::default();
+        unsafe { texture.GetDesc(&mut texture_desc) };
+        let create_view = |format: DXGI_FORMAT| {
+            let srv_desc = D3D11_SHADER_RESOURCE_VIEW_DESC {
+                Format: format,
+                ViewDimension: D3D11_SRV_DIMENSION_TEXTURE2D,
+                Anonymous: D3D11_SHADER_RESOURCE_VIEW_DESC_0 {
+                    Texture2D: D3D11_TEX2D_SRV {
+                        MostDetailedMip: 0,
+                        MipLevels: 1,
+                    },
+                },
+            };
+            let mut srv = None;
+            unsafe {
+                self.devices.device.CreateShaderResourceView(
+                    &texture,
+                    Some(&srv_desc),
+                    Some(&mut srv),
+                )
+            }
+            .ok()
+            .and(srv)
+        };
+        let views = if texture_desc.Format == DXGI_FORMAT_NV12 {
+            let luma = create_view(DXGI_FORMAT_R8_UNORM);
+            let chroma = create_view(DXGI_FORMAT_R8G8_UNORM);
+            luma.zip(chroma).map(|(luma, chroma)| SharedTextureViews {
+                color: luma,
+                chroma: Some(chroma),
+            })
+        } else {
+            create_view(shader_resource_format(texture_desc.Format)).map(|color| {
+                SharedTextureViews {
+                    color,
+                    chroma: None,
+                }
+            })
+        };
+        let Some(views) = views else {
+            log::error!("Failed to create views for shared texture 0x{nt_handle:X}");
+            return None;
+        };
+        log::debug!(
+            "Opened shared texture 0x{nt_handle:X}: {}x{}, {:?}",
+            texture_desc.Width,
+            texture_desc.Height,
+            texture_desc.Format
+        );
+
+        // A new texture behind the same handle means the old resource was released.
+        self.shared_texture_views
+            .retain(|cached, _| cached.nt_handle != nt_handle);
+        self.shared_texture_views
+            .insert(key, (views.clone(), self.frame));
+        Some(views)
+    }
+
+    /// How the shader converts colors in `color_space` to the swap chain's 8-bit sRGB, see
+    /// `transform_color` in shaders.hlsl.
+    fn color_transform(&self, color_space: ColorSpace, tone_mapping: Option<ToneMapping>) -> u32 {
+        match color_space {
+            ColorSpace::SrgbNonLinear => 0,
+            ColorSpace::LinearSrgb | ColorSpace::ScRgbLinear => {
+                match tone_mapping.map(|tone_mapping| tone_mapping.operator) {
+                    None | Some(ToneMapOperator::Clamp) => 1,
+                    Some(ToneMapOperator::Reinhard) => 2,
+                }
+            }
+            ColorSpace::Bt709Limited => 3,
+        }
+    }
+
     pub(crate) fn gpu_specs(&self) -> Result<GpuSpecs> {
         let desc = unsafe { self.devices.adapter.GetDesc1() }?;
         let is_software_emulated = (desc.Flags & DXGI_ADAPTER_FLAG_SOFTWARE.0 as u32) != 0;
@@ -823,6 +1023,164 @@ impl DirectXRenderer {
         })
     }
 
+    pub(crate) fn gpu_texture_format_support(&self) -> Vec<GpuTextureFormatSupport> {
+        GpuTextureFormat::ALL
+            .into_iter()
+            .map(|format| {
+                let flags = unsafe { self.devices.device.CheckFormatSupport(dxgi_format(format)) }
+                    .log_err()
+                    .unwrap_or(0);
+                let supports = |flag: D3D11_FORMAT_SUPPORT| flags & flag.0 as u32 != 0;
+                GpuTextureFormatSupport {
+                    format,
+                    sample: supports(D3D11_FORMAT_SUPPORT_SHADER_SAMPLE),
+                    render_target: supports(D3D11_FORMAT_SUPPORT_RENDER_TARGET),
+                    cpu_access: supports(D3D11_FORMAT_SUPPORT_CPU_LOCKABLE),
+                }
+            })
+            .collect()
+    }
+
+    /// Allocates a texture a producer on another device can render into and this renderer can
+    /// sample, returning a handle carrying its shared NT handle.
+    pub(crate) fn create_shared_texture(
+        &mut self,
+        size: Size<DevicePixels>,
+        format: GpuTextureFormat,
+    ) -> Result<GpuTextureHandle> {
+        let (texture, nt_handle) = create_shared_texture(&self.devices.device, size, format)?;
+        let handle = GpuTextureHandle::new_with_format(
+            nt_handle.0 as isize,
+            u32::from(size.width),
+            u32::from(size.height),
+            format,
+        );
+        self.owned_shared_textures.push((texture, handle.clone()));
+        Ok(handle)
+    }
+
+    pub(crate) fn set_gpu_timing_enabled(&mut self, enabled: bool) {
+        self.gpu_timer.set_enabled(enabled);
+    }
+
+    pub(crate) fn gpu_texture_timings(&self, nt_handle: isize) -> Option<GpuTextureTimings> {
+        self.gpu_timer.timings(nt_handle)
+    }
+
+    /// Handles to the textures allocated by `create_shared_texture`.
+    pub(crate) fn shared_textures(&self) -> Vec<GpuTextureHandle> {
+        self.owned_shared_textures
+            .iter()
+            .map(|(_, handle)| handle.clone())
+            .collect()
+    }
+
+    /// Drops the views of shared textures that were not drawn for
+    /// `SHARED_TEXTURE_EVICTION_FRAMES` frames, and starts the next frame.
+    fn evict_unused_shared_textures(&mut self) {
+        let frame = self.frame;
+        self.shared_texture_views.retain(|_, (_, last_used)| {
+            frame.saturating_sub(*last_used) < SHARED_TEXTURE_EVICTION_FRAMES
+        });
+        self.frame += 1;
+    }
+
+    /// Drops the views cached for the texture behind `nt_handle`, whatever its size, format or
+    /// generation, because its producer destroyed it.
+    pub(crate) fn invalidate_shared_texture(&mut self, nt_handle: isize) {
+        self.shared_texture_views
+            .retain(|key, _| key.nt_handle != nt_handle);
+    }
+
+    /// Frees textures allocated by `create_shared_texture`, along with any views cached for
+    /// them. Handles the renderer did not allocate are ignored.
+    pub(crate) fn release_shared_textures(&mut self, textures: &[GpuTextureHandle]) {
+        let is_released = |handle: isize| {
+            textures
+                .iter()
+                .any(|texture| texture.native_handle == handle)
+        };
+        self.shared_texture_views
+            .retain(|key, _| !is_released(key.nt_handle));
+        self.gpu_timer.forget(is_released);
+        self.owned_shared_textures.retain(|(_, handle)| {
+            if !is_released(handle.native_handle) {
+                return true;
+            }
+            unsafe { CloseHandle(HANDLE(handle.native_handle as _)) }.log_err();
+            false
+        });
+    }
+
+    /// Copies `bounds` of the next frame drawn to the CPU, resolving once the GPU has finished
+    /// the copy.
+    pub(crate) fn capture_bounds(
+        &mut self,
+        bounds: Bounds<DevicePixels>,
+    ) -> futures::channel::oneshot::Receiver<Result<image::RgbaImage>> {
+        self.frame_captures.request(bounds)
+    }
+
+    /// Uploads `data` into `region` of `texture`, after copying `copy_from` over the whole
+    /// texture if given. The region and data have been validated by the caller.
+    pub(crate) fn write_shared_texture(
+        &mut self,
+        texture: &GpuTextureHandle,
+        copy_from: Option<&GpuTextureHandle>,
+        region: Bounds<DevicePixels>,
+        data: &[u8],
+        stride: usize,
+    ) -> Result<()> {
+        let target = self.open_shared_texture(texture.native_handle)?;
+        let source = copy_from
+            .map(|copy_from| self.open_shared_texture(copy_from.native_handle))
+            .transpose()?;
+        let device_context = &self.devices.device_context;
+        let destination = D3D11_BOX {
+            left: region.left().0 as u32,
+            top: region.top().0 as u32,
+            front: 0,
+            right: region.right().0 as u32,
+            bottom: region.bottom().0 as u32,
+            back: 1,
+        };
+        self.gpu_timer.time(
+            &self.devices.device,
+            device_context,
+            texture.native_handle,
+            GpuTimedWork::Copy,
+            || unsafe {
+                if let Some(source) = &source {
+                    device_context.CopyResource(&target, source);
+                }
+                device_context.UpdateSubresource(
+                    &target,
+                    0,
+                    Some(&destination),
+                    data.as_ptr().cast(),
+                    stride as u32,
+                    0,
+                );
+            },
+        );
+        Ok(())
+    }
+
+    /// Returns the texture behind a shared NT handle, opening it on this renderer's device unless
+    /// the renderer allocated it.
+    fn open_shared_texture(&self, nt_handle: isize) -> Result<ID3D11Texture2D> {
+        if let Some((texture, _)) = self
+            .owned_shared_textures
+            .iter()
+            .find(|(_, owned_handle)| owned_handle.native_handle == nt_handle)
+        {
+            return Ok(texture.clone());
+        }
+        let device: ID3D11Device1 = self.devices.device.cast()?;
+        unsafe { device.OpenSharedResource1(HANDLE(nt_handle as _)) }
+            .with_context(|| format!("opening shared texture 0x{nt_handle:X}"))
+    }
+
     pub(crate) fn get_font_info() -> &'static FontInfo {
         static CACHED_FONT_INFO: OnceLock<FontInfo> = OnceLock::new();
         CACHED_FONT_INFO.get_or_init(|| unsafe {
@@ -1008,6 +1366,13 @@ impl DirectXRenderPipelines {
             16,
             create_blend_state(device)?,
         )?;
+        let gpu_textures = PipelineState::new(
+            device,
+            "gpu_texture_pipeline",
+            ShaderModule::GpuTexture,
+            4,
+            create_blend_state_for_gpu_texture(device)?,
+        )?;
 
         Ok(Self {
             shadow_pipeline,
@@ -1017,6 +1382,7 @@ impl DirectXRenderPipelines {
             underline_pipeline,
             mono_sprites,
             poly_sprites,
+            gpu_textures,
         })
     }
 }
@@ -1059,31 +1425,40 @@ impl DirectXGlobalElements {
             [buffer]
         };
 
-        let sampler = unsafe {
-            let desc = D3D11_SAMPLER_DESC {
-                Filter: D3D11_FILTER_MIN_MAG_MIP_LINEAR,
-                AddressU: D3D11_TEXTURE_ADDRESS_WRAP,
-                AddressV: D3D11_TEXTURE_ADDRESS_WRAP,
-                AddressW: D3D11_TEXTURE_ADDRESS_WRAP,
-                MipLODBias: 0.0,
-                MaxAnisotropy: 1,
-                ComparisonFunc: D3D11_COMPARISON_ALWAYS,
-                BorderColor: [0.0; 4],
-                MinLOD: 0.0,
-                MaxLOD: D3D11_FLOAT32_MAX,
-            };
-            let mut output = None;
-            device.CreateSamplerState(&desc, Some(&mut output))?;
-            [output]
-        };
+        let sampler = create_sampler(device, D3D11_FILTER_MIN_MAG_MIP_LINEAR)?;
+        let nearest_sampler = create_sampler(device, D3D11_FILTER_MIN_MAG_MIP_POINT)?;
 
         Ok(Self {
             global_params_buffer,
             sampler,
+            nearest_sampler,
         })
     }
 }
 
+fn create_sampler(
+    device: &ID3D11Device,
+    filter: D3D11_FILTER,
+) -> Result<[Option<ID3D11SamplerState>; 1]> {
+    unsafe {
+        let desc = D3D11_SAMPLER_DESC {
+            Filter: filter,
+            AddressU: D3D11_TEXTURE_ADDRESS_WRAP,
+            AddressV: D3D11_TEXTURE_ADDRESS_WRAP,
+            AddressW: D3D11_TEXTURE_ADDRESS_WRAP,
+            MipLODBias: 0.0,
+            MaxAnisotropy: 1,
+            ComparisonFunc: D3D11_COMPARISON_ALWAYS,
+            BorderColor: [0.0; 4],
+            MinLOD: 0.0,
+            MaxLOD: D3D11_FLOAT32_MAX,
+        };
+        let mut output = None;
+        device.CreateSamplerState(&desc, Some(&mut output))?;
+        Ok([output])
+    }
+}
+
 #[derive(Debug, Default)]
 #[repr(C)]
 struct GlobalParams {
@@ -1228,8 +1603,40 @@ struct PathSprite {
     bounds: Bounds<ScaledPixels>,
 }
 
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct GpuTextureSprite {
+    order: u32,
+    alpha_mode: u32,
+    bounds: Bounds<ScaledPixels>,
+    content_mask: Bounds<ScaledPixels>,
+    clip_bounds: Bounds<ScaledPixels>,
+    corner_radii: Corners<ScaledPixels>,
+    uv: Bounds<f32>,
+    orientation: u32,
+    grayscale: u32,
+    opacity: f32,
+    tint: Hsla,
+    yuv_matrix: u32,
+    yuv_full_range: u32,
+    mask: u32,
+    mask_color: Hsla,
+    texture_bounds: Bounds<ScaledPixels>,
+    transition_progress: f32,
+    transition_orientation: u32,
+    transition_bounds: Bounds<ScaledPixels>,
+    transition_uv: Bounds<f32>,
+    color_transform: u32,
+    transition_color_transform: u32,
+    exposure: f32,
+}
+
 impl Drop for DirectXRenderer {
     fn drop(&mut self) {
+        self.shared_texture_views.clear();
+        for (_, handle) in self.owned_shared_textures.drain(..) {
+            unsafe { CloseHandle(HANDLE(handle.native_handle as _)) }.log_err();
+        }
         #[cfg(debug_assertions)]
         report_live_objects(&self.devices.device).ok();
         unsafe {
@@ -1247,6 +1654,72 @@ impl Drop for DirectXResources {
     }
 }
 
+fn dxgi_format(format: GpuTextureFormat) -> DXGI_FORMAT {
+    match format {
+        GpuTextureFormat::RGBA8 => DXGI_FORMAT_R8G8B8A8_UNORM,
+        GpuTextureFormat::BGRA8 => DXGI_FORMAT_B8G8R8A8_UNORM,
+        GpuTextureFormat::RGBA16F => DXGI_FORMAT_R16G16B16A16_FLOAT,
+        GpuTextureFormat::Nv12 => DXGI_FORMAT_NV12,
+        GpuTextureFormat::R8 => DXGI_FORMAT_R8_UNORM,
+    }
+}
+
+/// Allocates a texture on `device` that another device can open through the returned NT handle,
+/// for rendering into or sampling. The caller closes the handle once the texture is no longer
+/// shared.
+pub(crate) fn create_shared_texture(
+    device: &ID3D11Device,
+    size: Size<DevicePixels>,
+    format: GpuTextureFormat,
+) -> Result<(ID3D11Texture2D, HANDLE)> {
+    crate::platform::validate_atlas_tile_size(size)?;
+    let (width, height) = (u32::from(size.width), u32::from(size.height));
+    anyhow::ensure!(
+        !format.is_yuv() || (width % 2 == 0 && height % 2 == 0),
+        "{format:?} textures must have an even width and height, got {size:?}"
+    );
+    let desc = D3D11_TEXTURE2D_DESC {
+        Width: width,
+        Height: height,
+        MipLevels: 1,
+        ArraySize: 1,
+        Format: dxgi_format(format),
+        SampleDesc: DXGI_SAMPLE_DESC {
+            Count: 1,
+            Quality: 0,
+        },
+        Usage: D3D11_USAGE_DEFAULT,
+        BindFlags: (D3D11_BIND_SHADER_RESOURCE.0 | D3D11_BIND_RENDER_TARGET.0) as u32,
+        CPUAccessFlags: 0,
+        MiscFlags: (D3D11_RESOURCE_MISC_SHARED.0 | D3D11_RESOURCE_MISC_SHARED_NTHANDLE.0) as u32,
+    };
+    let mut texture = None;
+    unsafe { device.CreateTexture2D(&desc, None, Some(&mut texture)) }
+        .with_context(|| format!("creating {format:?} shared texture of size {size:?}"))?;
+    let texture = texture.context("CreateTexture2D returned no texture")?;
+    let resource: IDXGIResource1 = texture.cast()?;
+    let nt_handle = unsafe {
+        resource.CreateSharedHandle(
+            None,
+            DXGI_SHARED_RESOURCE_READ | DXGI_SHARED_RESOURCE_WRITE,
+            PCWSTR::null(),
+        )
+    }
+    .context("creating shared texture handle")?;
+    Ok((texture, nt_handle))
+}
+
+/// The format to sample a shared texture with. Typeless textures are read as the matching
+/// normalized or float format.
+fn shader_resource_format(texture_format: DXGI_FORMAT) -> DXGI_FORMAT {
+    match texture_format {
+        DXGI_FORMAT_R8G8B8A8_TYPELESS => DXGI_FORMAT_R8G8B8A8_UNORM,
+        DXGI_FORMAT_B8G8R8A8_TYPELESS => DXGI_FORMAT_B8G8R8A8_UNORM,
+        DXGI_FORMAT_R16G16B16A16_TYPELESS => DXGI_FORMAT_R16G16B16A16_FLOAT,
+        format => format,
+    }
+}
+
 #[inline]
 fn get_comp_device(dxgi_device: &IDXGIDevice) -> Result<IDCompositionDevice> {
     Ok(unsafe { DCompositionCreateDevice(dxgi_device)? })
@@ -1533,6 +2006,26 @@ fn create_blend_state(device: &ID3D11Device) -> Result<ID3D11BlendState> {
     }
 }
 
+/// Shared GPU textures are composited as premultiplied color; the shader converts straight and
+/// opaque sources before blending.
+#[inline]
+fn create_blend_state_for_gpu_texture(device: &ID3D11Device) -> Result<ID3D11BlendState> {
+    let mut desc = D3D11_BLEND_DESC::default();
+    desc.RenderTarget[0].BlendEnable = true.into();
+    desc.RenderTarget[0].BlendOp = D3D11_BLEND_OP_ADD;
+    desc.RenderTarget[0].BlendOpAlpha = D3D11_BLEND_OP_ADD;
+    desc.RenderTarget[0].SrcBlend = D3D11_BLEND_ONE;
+    desc.RenderTarget[0].SrcBlendAlpha = D3D11_BLEND_ONE;
+    desc.RenderTarget[0].DestBlend = D3D11_BLEND_INV_SRC_ALPHA;
+    desc.RenderTarget[0].DestBlendAlpha = D3D11_BLEND_ONE;
+    desc.RenderTarget[0].RenderTargetWriteMask = D3D11_COLOR_WRITE_ENABLE_ALL.0 as u8;
+    unsafe {
+        let mut state = None;
+        device.CreateBlendState(&desc, Some(&mut state))?;
+        Ok(state.unwrap())
+    }
+}
+
 #[inline]
 fn create_blend_state_for_path_rasterization(device: &ID3D11Device) -> Result<ID3D11BlendState> {
     // If the feature level is set to greater than D3D_FEATURE_LEVEL_9_3, the display
@@ -1691,6 +2184,7 @@ pub(crate) mod shader_resources {
         PathSprite,
         MonochromeSprite,
         PolychromeSprite,
+        GpuTexture,
         EmojiRasterization,
     }
 
@@ -1761,6 +2255,10 @@ pub(crate) mod shader_resources {
                     ShaderTarget::Vertex => POLYCHROME_SPRITE_VERTEX_BYTES,
                     ShaderTarget::Fragment => POLYCHROME_SPRITE_FRAGMENT_BYTES,
                 },
+                ShaderModule::GpuTexture => match target {
+                    ShaderTarget::Vertex => GPU_TEXTURE_VERTEX_BYTES,
+                    ShaderTarget::Fragment => GPU_TEXTURE_FRAGMENT_BYTES,
+                },
                 ShaderModule::EmojiRasterization => match target {
                     ShaderTarget::Vertex => EMOJI_RASTERIZATION_VERTEX_BYTES,
                     ShaderTarget::Fragment => EMOJI_RASTERIZATION_FRAGMENT_BYTES,
@@ -1850,6 +2348,7 @@ pub(crate) mod shader_resources {
                 ShaderModule::PathSprite => "path_sprite",
                 ShaderModule::MonochromeSprite => "monochrome_sprite",
                 ShaderModule::PolychromeSprite => "polychrome_sprite",
+                ShaderModule::GpuTexture => "gpu_texture",
                 ShaderModule::EmojiRasterization => "emoji_rasterization",
             }
         }
@@ -10,7 +10,7 @@ use windows::{
         UI::{
             Controls::*,
             HiDpi::*,
-            Input::{Ime::*, KeyboardAndMouse::*},
+            Input::{Ime::*, KeyboardAndMouse::*, *},
             WindowsAndMessaging::*,
         },
     },
@@ -62,6 +62,7 @@ impl WindowsWindowInner {
             WM_DESTROY => self.handle_destroy_msg(handle),
             WM_MOUSEMOVE => self.handle_mouse_move_msg(handle, lparam, wparam),
             WM_MOUSELEAVE | WM_NCMOUSELEAVE => self.handle_mouse_leave_msg(),
+            WM_INPUT => self.handle_raw_input_msg(lparam),
             WM_NCMOUSEMOVE => self.handle_nc_mouse_move_msg(handle, lparam),
             // Treat double click as a second single click, since we track the double clicks ourselves.
             // If you don't interact with any elements, this will fall through to the windows default
@@ -176,6 +177,7 @@ impl WindowsWindowInner {
             self.state
                 .restore_from_minimized
                 .set(self.state.callbacks.request_frame.take());
+            self.report_visibility(WindowVisibility::Minimized);
             return Some(0);
         }
 
@@ -190,6 +192,7 @@ impl WindowsWindowInner {
                 .callbacks
                 .request_frame
                 .set(Some(restore_from_minimized));
+            self.report_visibility(WindowVisibility::Visible);
         } else {
             should_resize_renderer = true;
         }
@@ -271,6 +274,9 @@ impl WindowsWindowInner {
 
     fn handle_destroy_msg(&self, handle: HWND) -> Option<isize> {
         let callback = { self.state.callbacks.close.take() };
+        if self.state.raw_mouse_input.take() {
+            set_raw_mouse_input_window(handle, false).log_err();
+        }
         // Re-enable parent window if this was a modal dialog
         if let Some(parent_hwnd) = self.parent_hwnd {
             unsafe {
@@ -316,17 +322,86 @@ impl WindowsWindowInner {
         };
         let x = lparam.signed_loword() as f32;
         let y = lparam.signed_hiword() as f32;
-        let input = PlatformInput::MouseMove(MouseMoveEvent {
-            position: logical_point(x, y, scale_factor),
-            pressed_button,
-            modifiers: current_modifiers(),
-        });
+        let input = if let Some(lock) = self.state.pointer_lock.get() {
+            let mut cursor = POINT {
+                x: x as i32,
+                y: y as i32,
+            };
+            unsafe { ClientToScreen(handle, &mut cursor).ok().log_err() };
+            let delta_x = (cursor.x - lock.center.x) as f32;
+            let delta_y = (cursor.y - lock.center.y) as f32;
+            if delta_x == 0. && delta_y == 0. {
+                // The move generated by re-centering the cursor below.
+                self.state.callbacks.input.set(Some(func));
+                return Some(0);
+            }
+            unsafe { SetCursorPos(lock.center.x, lock.center.y).log_err() };
+            PlatformInput::MouseMotion(MouseMotionEvent {
+                position: logical_point(x, y, scale_factor),
+                delta: logical_point(delta_x, delta_y, scale_factor),
+                modifiers: current_modifiers(),
+            })
+        } else {
+            PlatformInput::MouseMove(MouseMoveEvent {
+                position: logical_point(x, y, scale_factor),
+                pressed_button,
+                modifiers: current_modifiers(),
+            })
+        };
         let handled = !func(input).propagate;
         self.state.callbacks.input.set(Some(func));
 
         if handled { Some(0) } else { Some(1) }
     }
 
+    fn handle_raw_input_msg(&self, lparam: LPARAM) -> Option<isize> {
+        if !self.state.raw_mouse_input.get() {
+            return None;
+        }
+        let mut raw = RAWINPUT::default();
+        let mut size = std::mem::size_of::<RAWINPUT>() as u32;
+        let read = unsafe {
+            GetRawInputData(
+                HRAWINPUT(lparam.0 as _),
+                RID_INPUT,
+                Some(&mut raw as *mut RAWINPUT as *mut _),
+                &mut size,
+                std::mem::size_of::<RAWINPUTHEADER>() as u32,
+            )
+        };
+        if read == u32::MAX || raw.header.dwType != RIM_TYPEMOUSE.0 {
+            return None;
+        }
+        let mouse = unsafe { raw.data.mouse };
+        // Pens and remote desktop sessions report absolute positions, which are already
+        // covered by regular mouse moves.
+        if mouse.usFlags.0 & MOUSE_MOVE_ABSOLUTE.0 != 0 {
+            return None;
+        }
+        let button_flags = u32::from(unsafe { mouse.Anonymous.Anonymous.usButtonFlags });
+        let (pressed_button, released_button) = raw_mouse_buttons(button_flags);
+        // Wheel-only reports carry nothing a raw motion event can express.
+        if mouse.lLastX == 0
+            && mouse.lLastY == 0
+            && pressed_button.is_none()
+            && released_button.is_none()
+        {
+            return None;
+        }
+
+        let mut func = self.state.callbacks.input.take()?;
+        func(PlatformInput::RawMouseMotion(RawMouseMotionEvent {
+            delta: point(mouse.lLastX as f32, mouse.lLastY as f32),
+            pressed_button,
+            released_button,
+            modifiers: current_modifiers(),
+        }));
+        self.state.callbacks.input.set(Some(func));
+
+        // Let DefWindowProc clean up after the raw input message.
+        None
+    }
+
     fn handle_mouse_leave_msg(&self) -> Option<isize> {
         self.state.hovered.set(false);
         if let Some(mut callback) = self.state.callbacks.hovered_status_change.take() {
@@ -720,6 +795,10 @@ impl WindowsWindowInner {
 
     fn handle_activate_msg(self: &Rc<Self>, wparam: WPARAM) -> Option<isize> {
         let activated = wparam.loword() > 0;
+        if !activated {
+            // Alt-tab and clicking another window must always give the cursor back.
+            release_pointer_lock(&self.state.pointer_lock);
+        }
         let this = self.clone();
         self.executor
             .spawn(async move {
@@ -1134,6 +1213,20 @@ impl WindowsWindowInner {
     }
 
     fn handle_input_language_changed(&self) -> Option<isize> {
+        // Deliver the change to the window in order with the key events around it, so keys
+        // pressed before the switch are still handled under the old layout.
+        if let Some(mut func) = self.state.callbacks.input.take() {
+            let layout_id = WindowsKeyboardLayout::new()
+                .unwrap_or_else(|_| WindowsKeyboardLayout::unknown())
+                .id()
+                .to_string();
+            func(PlatformInput::KeyboardLayoutChanged(
+                KeyboardLayoutChangedEvent {
+                    layout_id: layout_id.into(),
+                },
+            ));
+            self.state.callbacks.input.set(Some(func));
+        }
         unsafe {
             PostMessageW(
                 Some(self.platform_window_handle),
@@ -1148,11 +1241,21 @@ impl WindowsWindowInner {
 
     fn handle_window_visibility_changed(&self, handle: HWND, wparam: WPARAM) -> Option<isize> {
         if wparam.0 == 1 {
+            self.report_visibility(WindowVisibility::Visible);
             self.draw_window(handle, false);
+        } else {
+            self.report_visibility(WindowVisibility::Minimized);
         }
         None
     }
 
+    fn report_visibility(&self, visibility: WindowVisibility) {
+        if let Some(mut callback) = self.state.callbacks.visibility_changed.take() {
+            callback(visibility);
+            self.state.callbacks.visibility_changed.set(Some(callback));
+        }
+    }
+
     fn handle_device_lost(&self, lparam: LPARAM) -> Option<isize> {
         let devices = lparam.0 as *const DirectXDevices;
         let devices = unsafe { &*devices };
@@ -1534,6 +1637,46 @@ pub(crate) fn current_modifiers() -> Modifiers {
     }
 }
 
+/// The buttons that went down and up in a raw mouse report, from its `usButtonFlags`.
+fn raw_mouse_buttons(flags: u32) -> (Option<MouseButton>, Option<MouseButton>) {
+    const BUTTONS: [(u32, u32, MouseButton); 5] = [
+        (
+            RI_MOUSE_LEFT_BUTTON_DOWN,
+            RI_MOUSE_LEFT_BUTTON_UP,
+            MouseButton::Left,
+        ),
+        (
+            RI_MOUSE_RIGHT_BUTTON_DOWN,
+            RI_MOUSE_RIGHT_BUTTON_UP,
+            MouseButton::Right,
+        ),
+        (
+            RI_MOUSE_MIDDLE_BUTTON_DOWN,
+            RI_MOUSE_MIDDLE_BUTTON_UP,
+            MouseButton::Middle,
+        ),
+        (
+            RI_MOUSE_BUTTON_4_DOWN,
+            RI_MOUSE_BUTTON_4_UP,
+            MouseButton::Navigate(NavigationDirection::Back),
+        ),
+        (
+            RI_MOUSE_BUTTON_5_DOWN,
+            RI_MOUSE_BUTTON_5_UP,
+            MouseButton::Navigate(NavigationDirection::Forward),
+        ),
+    ];
+    let pressed = BUTTONS
+        .iter()
+        .find(|(down, _, _)| flags & down != 0)
+        .map(|(_, _, button)| *button);
+    let released = BUTTONS
+        .iter()
+        .find(|(_, up, _)| flags & up != 0)
+        .map(|(_, _, button)| *button);
+    (pressed, released)
+}
+
 #[inline]
 pub(crate) fn current_capslock() -> Capslock {
     let on = unsafe { GetKeyState(VK_CAPITAL.0 as i32) & 1 } > 0;
@@ -0,0 +1,234 @@
+use anyhow::{Result, anyhow};
+use futures::channel::oneshot;
+use image::RgbaImage;
+use windows::Win32::{
+    Foundation::S_OK,
+    Graphics::{
+        Direct3D11::{
+            D3D11_ASYNC_GETDATA_DONOTFLUSH, D3D11_BOX, D3D11_CPU_ACCESS_READ, D3D11_MAP_READ,
+            D3D11_MAPPED_SUBRESOURCE, D3D11_QUERY_DESC, D3D11_QUERY_EVENT, D3D11_TEXTURE2D_DESC,
+            D3D11_USAGE_STAGING, ID3D11Device, ID3D11DeviceContext, ID3D11Query, ID3D11Texture2D,
+        },
+        Dxgi::Common::{DXGI_FORMAT_B8G8R8A8_UNORM, DXGI_SAMPLE_DESC},
+    },
+    core::BOOL,
+};
+
+use crate::{Bounds, DevicePixels, Size, point};
+
+/// Reads regions of drawn frames back to the CPU. A region is copied into a staging texture
+/// right after the frame is drawn and read on a later frame, once the GPU has finished the
+/// copy, so capturing never stalls rendering.
+#[derive(Default)]
+pub(crate) struct FrameCaptures {
+    requested: Vec<CaptureRequest>,
+    /// The capture copied into `staging`, waiting for the GPU
+    in_flight: Option<InFlightCapture>,
+    /// Reused across captures, and only recreated to grow
+    staging: Option<(ID3D11Texture2D, Size<DevicePixels>)>,
+}
+
+struct CaptureRequest {
+    bounds: Bounds<DevicePixels>,
+    sender: oneshot::Sender<Result<RgbaImage>>,
+}
+
+struct InFlightCapture {
+    size: Size<DevicePixels>,
+    copied: ID3D11Query,
+    sender: oneshot::Sender<Result<RgbaImage>>,
+}
+
+impl FrameCaptures {
+    /// Capture `bounds` of the next frame drawn.
+    pub(crate) fn request(
+        &mut self,
+        bounds: Bounds<DevicePixels>,
+    ) -> oneshot::Receiver<Result<RgbaImage>> {
+        let (sender, receiver) = oneshot::channel();
+        self.requested.push(CaptureRequest { bounds, sender });
+        receiver
+    }
+
+    /// Resolve the capture whose copy the GPU finished, and copy the next requested region out
+    /// of `frame`, which was just drawn. One capture is in flight at a time, so that they can
+    /// share the staging texture.
+    pub(crate) fn after_draw(
+        &mut self,
+        device: &ID3D11Device,
+        device_context: &ID3D11DeviceContext,
+        frame: &ID3D11Texture2D,
+        frame_size: Size<DevicePixels>,
+    ) {
+        if let Some(capture) = self.in_flight.take() {
+            if is_done(device_context, &capture.copied) {
+                let result = self.read_staging(device_context, capture.size);
+                capture.sender.send(result).ok();
+            } else {
+                self.in_flight = Some(capture);
+                return;
+            }
+        }
+
+        self.requested
+            .retain(|request| !request.sender.is_canceled());
+        if self.requested.is_empty() {
+            return;
+        }
+        let request = self.requested.remove(0);
+        let frame_bounds = Bounds::new(point(DevicePixels(0), DevicePixels(0)), frame_size);
+        let bounds = request.bounds.intersect(&frame_bounds);
+        if bounds.is_empty() {
+            request
+                .sender
+                .send(Err(anyhow!(
+                    "capture bounds {:?} are outside the window",
+                    request.bounds
+                )))
+                .ok();
+            return;
+        }
+        let result = self.copy_to_staging(device, device_context, frame, bounds);
+        match result {
+            Ok(copied) => {
+                self.in_flight = Some(InFlightCapture {
+                    size: bounds.size,
+                    copied,
+                    sender: request.sender,
+                });
+            }
+            Err(error) => {
+                request.sender.send(Err(error)).ok();
+            }
+        }
+    }
+
+    /// Fail the capture in flight and drop the staging texture, e.g. because the device they
+    /// were created on was lost. Requested captures are taken from the next frame instead.
+    pub(crate) fn reset(&mut self) {
+        if let Some(capture) = self.in_flight.take() {
+            capture
+                .sender
+                .send(Err(anyhow!("the GPU device was lost during the capture")))
+                .ok();
+        }
+        self.staging = None;
+    }
+
+    fn copy_to_staging(
+        &mut self,
+        device: &ID3D11Device,
+        device_context: &ID3D11DeviceContext,
+        frame: &ID3D11Texture2D,
+        bounds: Bounds<DevicePixels>,
+    ) -> Result<ID3D11Query> {
+        let staging = match &self.staging {
+            Some((staging, staging_size))
+                if staging_size.width >= bounds.size.width
+                    && staging_size.height >= bounds.size.height =>
+            {
+                staging.clone()
+            }
+            _ => {
+                let staging_size = self
+                    .staging
+                    .as_ref()
+                    .map_or(bounds.size, |(_, staging_size)| {
+                        staging_size.max(&bounds.size)
+                    });
+                let staging = create_staging_texture(device, staging_size)?;
+                self.staging = Some((staging.clone(), staging_size));
+                staging
+            }
+        };
+
+        let desc = D3D11_QUERY_DESC {
+            Query: D3D11_QUERY_EVENT,
+            MiscFlags: 0,
+        };
+        let mut copied = None;
+        unsafe { device.CreateQuery(&desc, Some(&mut copied)) }?;
+        let copied = copied.ok_or_else(|| anyhow!("creating the capture query failed"))?;
+
+        let region = D3D11_BOX {
+            left: bounds.left().0 as u32,
+            top: bounds.top().0 as u32,
+            front: 0,
+            right: bounds.right().0 as u32,
+            bottom: bounds.bottom().0 as u32,
+            back: 1,
+        };
+        unsafe {
+            device_context.CopySubresourceRegion(&staging, 0, 0, 0, 0, frame, 0, Some(&region));
+            device_context.End(&copied);
+        }
+        Ok(copied)
+    }
+
+    /// Read the top-left `size` of the staging texture, converting the frame's BGRA to RGBA.
+    fn read_staging(
+        &self,
+        device_context: &ID3D11DeviceContext,
+        size: Size<DevicePixels>,
+    ) -> Result<RgbaImage> {
+        let (staging, _) = self
+            .staging
+            .as_ref()
+            .ok_or_else(|| anyhow!("the capture's staging texture was dropped"))?;
+        let width = size.width.0 as usize;
+        let height = size.height.0 as usize;
+        let mut pixels = vec![0u8; width * height * 4];
+        unsafe {
+            let mut mapped = D3D11_MAPPED_SUBRESOURCE::default();
+            device_context.Map(staging, 0, D3D11_MAP_READ, 0, Some(&mut mapped))?;
+            for (y, row) in pixels.chunks_exact_mut(width * 4).enumerate() {
+                let source = (mapped.pData as *const u8).add(mapped.RowPitch as usize * y);
+                std::ptr::copy_nonoverlapping(source, row.as_mut_ptr(), row.len());
+            }
+            device_context.Unmap(staging, 0);
+        }
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+        RgbaImage::from_raw(width as u32, height as u32, pixels)
+            .ok_or_else(|| anyhow!("creating the captured image failed"))
+    }
+}
+
+fn create_staging_texture(
+    device: &ID3D11Device,
+    size: Size<DevicePixels>,
+) -> Result<ID3D11Texture2D> {
+    let desc = D3D11_TEXTURE2D_DESC {
+        Width: size.width.0 as u32,
+        Height: size.height.0 as u32,
+        MipLevels: 1,
+        ArraySize: 1,
+        Format: DXGI_FORMAT_B8G8R8A8_UNORM,
+        SampleDesc: DXGI_SAMPLE_DESC {
+            Count: 1,
+            Quality: 0,
+        },
+        Usage: D3D11_USAGE_STAGING,
+        BindFlags: 0,
+        CPUAccessFlags: D3D11_CPU_ACCESS_READ.0 as u32,
+        MiscFlags: 0,
+    };
+    let mut texture = None;
+    unsafe { device.CreateTexture2D(&desc, None, Some(&mut texture)) }?;
+    texture.ok_or_else(|| anyhow!("creating the capture staging texture failed"))
+}
+
+/// Whether the GPU has reached an event query, checked without stalling.
+fn is_done(device_context: &ID3D11DeviceContext, query: &ID3D11Query) -> bool {
+    let mut done = BOOL::default();
+    unsafe {
+        device_context.GetData(
+            query,
+            Some(&mut done as *mut BOOL as *mut _),
+            std::mem::size_of::<BOOL>() as u32,
+            D3D11_ASYNC_GETDATA_DONOTFLUSH.0 as u32,
+        ) == S_OK
+            && done.as_bool()
+    }
+}
@@ -0,0 +1,201 @@
+use std::{
+    ffi::{CStr, c_void},
+    sync::Arc,
+};
+
+use ::util::ResultExt;
+use anyhow::{Context as _, Result};
+use windows::{
+    Win32::{
+        Foundation::HANDLE,
+        Graphics::Direct3D11::{ID3D11Device, ID3D11Device1, ID3D11Texture2D},
+    },
+    core::{BOOL, Interface},
+};
+
+use crate::{DirectXDevices, GpuTextureHandle};
+
+const GL_TEXTURE_2D: u32 = 0x0DE1;
+const WGL_ACCESS_WRITE_DISCARD_NV: u32 = 0x0002;
+
+type OpenDevice = unsafe extern "system" fn(dx_device: *mut c_void) -> HANDLE;
+type CloseDevice = unsafe extern "system" fn(device: HANDLE) -> BOOL;
+type RegisterObject = unsafe extern "system" fn(
+    device: HANDLE,
+    dx_object: *mut c_void,
+    name: u32,
+    object_type: u32,
+    access: u32,
+) -> HANDLE;
+type UnregisterObject = unsafe extern "system" fn(device: HANDLE, object: HANDLE) -> BOOL;
+type LockObjects =
+    unsafe extern "system" fn(device: HANDLE, count: i32, objects: *mut HANDLE) -> BOOL;
+
+/// Lets an OpenGL renderer draw into the textures of a [`GpuCanvasSource`](crate::GpuCanvasSource)
+/// through the `WGL_NV_DX_interop` extension.
+///
+/// Create it on the thread that owns the GL context, with that context current, then register
+/// each of the source's buffers as a GL texture:
+///
+/// ```ignore
+/// let interop = WglDxInterop::new(|name| wglGetProcAddress(name.as_ptr()))?;
+/// let mut buffer = interop.register(source.active_buffer(), gl_texture)?;
+/// buffer.lock()?;
+/// // Attach `gl_texture` to a framebuffer and draw into it.
+/// buffer.unlock()?;
+/// source.swap_buffers();
+/// ```
+///
+/// Textures must be unlocked before the source publishes them, since GL may still be writing to
+/// a locked texture.
+pub struct WglDxInterop {
+    device: Arc<InteropDevice>,
+}
+
+struct InteropDevice {
+    functions: InteropFunctions,
+    handle: HANDLE,
+    d3d_device: ID3D11Device,
+}
+
+struct InteropFunctions {
+    close_device: CloseDevice,
+    register_object: RegisterObject,
+    unregister_object: UnregisterObject,
+    lock_objects: LockObjects,
+    unlock_objects: LockObjects,
+}
+
+/// A gpu canvas texture registered with GL. Unregistered when dropped.
+pub struct WglDxTexture {
+    device: Arc<InteropDevice>,
+    object: HANDLE,
+    locked: bool,
+    // Keeps the texture open while GL refers to it.
+    _texture: ID3D11Texture2D,
+}
+
+impl WglDxInterop {
+    /// Load the interop entry points with `get_proc_address`, usually `wglGetProcAddress`, and
+    /// open a D3D11 device for GL to share textures with. Fails if the driver does not expose
+    /// `WGL_NV_DX_interop`.
+    pub fn new(mut get_proc_address: impl FnMut(&CStr) -> *const c_void) -> Result<Self> {
+        let mut load = |name: &CStr| {
+            let address = get_proc_address(name);
+            anyhow::ensure!(
+                !address.is_null(),
+                "{} is unavailable; the driver does not support WGL_NV_DX_interop",
+                name.to_string_lossy()
+            );
+            Ok(address)
+        };
+        // SAFETY: the addresses come from the GL loader for these exact entry points.
+        let (open_device, functions) = unsafe {
+            (
+                std::mem::transmute::<*const c_void, OpenDevice>(load(c"wglDXOpenDeviceNV")?),
+                InteropFunctions {
+                    close_device: std::mem::transmute(load(c"wglDXCloseDeviceNV")?),
+                    register_object: std::mem::transmute(load(c"wglDXRegisterObjectNV")?),
+                    unregister_object: std::mem::transmute(load(c"wglDXUnregisterObjectNV")?),
+                    lock_objects: std::mem::transmute(load(c"wglDXLockObjectsNV")?),
+                    unlock_objects: std::mem::transmute(load(c"wglDXUnlockObjectsNV")?),
+                },
+            )
+        };
+
+        let d3d_device = DirectXDevices::new()
+            .context("creating a device to share with GL")?
+            .device;
+        let handle = unsafe { open_device(d3d_device.as_raw()) };
+        if handle.is_invalid() {
+            return Err(windows::core::Error::from_win32()).context("wglDXOpenDeviceNV failed");
+        }
+        Ok(Self {
+            device: Arc::new(InteropDevice {
+                functions,
+                handle,
+                d3d_device,
+            }),
+        })
+    }
+
+    /// Register `texture`, one of a source's buffers, as the storage of the GL texture named
+    /// `gl_texture`. GL may only write to it, and must overwrite the whole texture each frame.
+    pub fn register(&self, texture: &GpuTextureHandle, gl_texture: u32) -> Result<WglDxTexture> {
+        anyhow::ensure!(
+            !texture.format.is_yuv(),
+            "GL cannot render into {:?} textures",
+            texture.format
+        );
+        let d3d_device: ID3D11Device1 = self.device.d3d_device.cast()?;
+        let d3d_texture: ID3D11Texture2D =
+            unsafe { d3d_device.OpenSharedResource1(HANDLE(texture.native_handle as _)) }
+                .context("opening the shared texture")?;
+        let object = unsafe {
+            (self.device.functions.register_object)(
+                self.device.handle,
+                d3d_texture.as_raw(),
+                gl_texture,
+                GL_TEXTURE_2D,
+                WGL_ACCESS_WRITE_DISCARD_NV,
+            )
+        };
+        if object.is_invalid() {
+            return Err(windows::core::Error::from_win32()).context("wglDXRegisterObjectNV failed");
+        }
+        Ok(WglDxTexture {
+            device: self.device.clone(),
+            object,
+            locked: false,
+            _texture: d3d_texture,
+        })
+    }
+}
+
+impl WglDxTexture {
+    /// Hand the texture to GL. Call before drawing into it.
+    pub fn lock(&mut self) -> Result<()> {
+        if !self.locked {
+            self.call(self.device.functions.lock_objects)
+                .context("wglDXLockObjectsNV failed")?;
+            self.locked = true;
+        }
+        Ok(())
+    }
+
+    /// Hand the texture back to D3D once GL is done drawing, so the canvas can display it.
+    pub fn unlock(&mut self) -> Result<()> {
+        if self.locked {
+            self.call(self.device.functions.unlock_objects)
+                .context("wglDXUnlockObjectsNV failed")?;
+            self.locked = false;
+        }
+        Ok(())
+    }
+
+    /// Whether GL currently owns the texture.
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    fn call(&mut self, function: LockObjects) -> windows::core::Result<()> {
+        unsafe { function(self.device.handle, 1, &mut self.object) }.ok()
+    }
+}
+
+impl Drop for WglDxTexture {
+    fn drop(&mut self) {
+        self.unlock().log_err();
+        unsafe { (self.device.functions.unregister_object)(self.device.handle, self.object) }
+            .ok()
+            .log_err();
+    }
+}
+
+impl Drop for InteropDevice {
+    fn drop(&mut self) {
+        unsafe { (self.functions.close_device)(self.handle) }
+            .ok()
+            .log_err();
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::VecDeque;
+
+use collections::FxHashMap;
+use windows::Win32::{
+    Foundation::S_OK,
+    Graphics::Direct3D11::{
+        D3D11_ASYNC_GETDATA_DONOTFLUSH, D3D11_QUERY, D3D11_QUERY_DATA_TIMESTAMP_DISJOINT,
+        D3D11_QUERY_DESC, D3D11_QUERY_TIMESTAMP, D3D11_QUERY_TIMESTAMP_DISJOINT, ID3D11Device,
+        ID3D11DeviceContext, ID3D11Query,
+    },
+};
+
+use crate::GpuTextureTimings;
+
+/// Frames whose timestamps have not been read back yet. If the GPU falls further behind, the
+/// oldest frames are dropped unmeasured.
+const MAX_PENDING_FRAMES: usize = 8;
+
+/// The GPU work measured for a shared texture.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum GpuTimedWork {
+    /// Drawing the texture into the window
+    Draw,
+    /// Writing into the texture, including copying the displayed frame into it
+    Copy,
+}
+
+/// Measures the GPU time spent on shared textures with timestamp queries. Does nothing until
+/// enabled, so that rendering pays nothing for it by default.
+#[derive(Default)]
+pub(crate) struct GpuTimer {
+    enabled: bool,
+    /// Spans recorded since the last `end_frame`, bracketed by a disjoint query
+    current: Option<TimedFrame>,
+    /// Frames waiting for the GPU to reach their queries
+    pending: VecDeque<TimedFrame>,
+    totals: FxHashMap<(isize, GpuTimedWork), SpanTotal>,
+}
+
+struct TimedFrame {
+    disjoint: ID3D11Query,
+    spans: Vec<TimedSpan>,
+}
+
+struct TimedSpan {
+    nt_handle: isize,
+    work: GpuTimedWork,
+    start: ID3D11Query,
+    end: ID3D11Query,
+}
+
+#[derive(Clone, Copy, Default)]
+struct SpanTotal {
+    micros: f64,
+    count: u64,
+}
+
+impl SpanTotal {
+    fn average(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.micros / self.count as f64)
+    }
+}
+
+impl GpuTimer {
+    /// Start or stop measuring. Stopping discards the measurements taken so far.
+    pub(crate) fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.pending.clear();
+            self.totals.clear();
+        }
+    }
+
+    /// Discard every query, e.g. because the device they were created on was lost.
+    pub(crate) fn reset(&mut self) {
+        *self = Self {
+            enabled: self.enabled,
+            ..Self::default()
+        };
+    }
+
+    /// Run `f`, which records `work` on the texture behind `nt_handle`, between two timestamps.
+    pub(crate) fn time<R>(
+        &mut self,
+        device: &ID3D11Device,
+        device_context: &ID3D11DeviceContext,
+        nt_handle: isize,
+        work: GpuTimedWork,
+        f: impl FnOnce() -> R,
+    ) -> R {
+        if !self.enabled {
+            return f();
+        }
+        let Some((start, end)) = create_query(device, D3D11_QUERY_TIMESTAMP)
+            .zip(create_query(device, D3D11_QUERY_TIMESTAMP))
+        else {
+            return f();
+        };
+        let mut frame = match self.current.take() {
+            Some(frame) => frame,
+            None => {
+                let Some(disjoint) = create_query(device, D3D11_QUERY_TIMESTAMP_DISJOINT) else {
+                    return f();
+                };
+                unsafe { device_context.Begin(&disjoint) };
+                TimedFrame {
+                    disjoint,
+                    spans: Vec::new(),
+                }
+            }
+        };
+
+        unsafe { device_context.End(&start) };
+        let result = f();
+        unsafe { device_context.End(&end) };
+        frame.spans.push(TimedSpan {
+            nt_handle,
+            work,
+            start,
+            end,
+        });
+        self.current = Some(frame);
+        result
+    }
+
+    /// Close the spans recorded since the last call, and add up those of earlier frames the GPU
+    /// has finished. Called once per drawn frame.
+    pub(crate) fn end_frame(&mut self, device_context: &ID3D11DeviceContext) {
+        if let Some(frame) = self.current.take() {
+            unsafe { device_context.End(&frame.disjoint) };
+            if self.enabled {
+                if self.pending.len() == MAX_PENDING_FRAMES {
+                    self.pending.pop_front();
+                }
+                self.pending.push_back(frame);
+            }
+        }
+
+        while let Some(frame) = self.pending.front() {
+            let mut disjoint = D3D11_QUERY_DATA_TIMESTAMP_DISJOINT::default();
+            if !get_data(device_context, &frame.disjoint, &mut disjoint) {
+                break;
+            }
+            let frame = self.pending.pop_front().unwrap();
+            // The timestamps of a frame during which the GPU clock changed are meaningless.
+            if disjoint.Disjoint.as_bool() {
+                continue;
+            }
+            for span in frame.spans {
+                let (mut start, mut end) = (0u64, 0u64);
+                if !get_data(device_context, &span.start, &mut start)
+                    || !get_data(device_context, &span.end, &mut end)
+                {
+                    continue;
+                }
+                if let Some(micros) = span_micros(start, end, disjoint.Frequency) {
+                    let total = self.totals.entry((span.nt_handle, span.work)).or_default();
+                    total.micros += micros;
+                    total.count += 1;
+                }
+            }
+        }
+    }
+
+    /// The average times measured for the texture behind `nt_handle`, or `None` if none were.
+    pub(crate) fn timings(&self, nt_handle: isize) -> Option<GpuTextureTimings> {
+        let average = |work| {
+            self.totals
+                .get(&(nt_handle, work))
+                .and_then(SpanTotal::average)
+        };
+        let timings = GpuTextureTimings {
+            draw_micros: average(GpuTimedWork::Draw),
+            copy_micros: average(GpuTimedWork::Copy),
+        };
+        (timings.draw_micros.is_some() || timings.copy_micros.is_some()).then_some(timings)
+    }
+
+    /// Drop the measurements of textures that were released, whose handles may be reused.
+    pub(crate) fn forget(&mut self, is_released: impl Fn(isize) -> bool) {
+        self.totals
+            .retain(|(nt_handle, _), _| !is_released(*nt_handle));
+    }
+}
+
+fn create_query(device: &ID3D11Device, query: D3D11_QUERY) -> Option<ID3D11Query> {
+    let desc = D3D11_QUERY_DESC {
+        Query: query,
+        MiscFlags: 0,
+    };
+    let mut query = None;
+    unsafe { device.CreateQuery(&desc, Some(&mut query)) }.ok()?;
+    query
+}
+
+/// Read a query's result without stalling, returning whether the GPU has produced it yet.
+fn get_data<T>(device_context: &ID3D11DeviceContext, query: &ID3D11Query, data: &mut T) -> bool {
+    unsafe {
+        device_context.GetData(
+            query,
+            Some(data as *mut T as *mut _),
+            std::mem::size_of::<T>() as u32,
+            D3D11_ASYNC_GETDATA_DONOTFLUSH.0 as u32,
+        ) == S_OK
+    }
+}
+
+/// The microseconds between two timestamps of a clock ticking `frequency` times a second, or
+/// `None` if they can't be trusted.
+fn span_micros(start: u64, end: u64, frequency: u64) -> Option<f64> {
+    if frequency == 0 || end < start {
+        return None;
+    }
+    Some((end - start) as f64 * 1_000_000. / frequency as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_span_micros_rejects_invalid_timestamps() {
+        assert_eq!(span_micros(1_000, 3_000, 1_000_000), Some(2_000.));
+        assert_eq!(span_micros(3_000, 1_000, 1_000_000), None);
+        assert_eq!(span_micros(1_000, 3_000, 0), None);
+    }
+}
@@ -0,0 +1,340 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use ::util::ResultExt;
+use anyhow::{Context as _, Result};
+use parking_lot::Mutex;
+use windows::{
+    Foundation::TypedEventHandler,
+    Graphics::{
+        Capture::{Direct3D11CaptureFramePool, GraphicsCaptureItem, GraphicsCaptureSession},
+        DirectX::{Direct3D11::IDirect3DDevice, DirectXPixelFormat},
+        SizeInt32,
+    },
+    Win32::{
+        Foundation::{CloseHandle, HANDLE, HWND},
+        Graphics::{
+            Direct3D11::{D3D11_BOX, ID3D11DeviceContext, ID3D11Texture2D},
+            Dxgi::IDXGIDevice,
+            Gdi::HMONITOR,
+        },
+        System::WinRT::{
+            Direct3D11::{CreateDirect3D11DeviceFromDXGIDevice, IDirect3DDxgiInterfaceAccess},
+            Graphics::Capture::IGraphicsCaptureItemInterop,
+        },
+    },
+    core::{IInspectable, Interface},
+};
+
+use crate::{
+    DevicePixels, DirectXDevices, GpuCanvasSource, GpuTextureFormat, GpuTextureHandle,
+    ProducerStatus, Size, create_shared_texture, size,
+};
+
+const CAPTURE_FORMAT: DirectXPixelFormat = DirectXPixelFormat::B8G8R8A8UIntNormalized;
+
+/// Captures a window or monitor with Windows.Graphics.Capture and publishes its frames through a
+/// [`GpuCanvasSource`], copying each one GPU to GPU without going through the CPU.
+///
+/// Frames arrive on a system worker thread, where they are copied into the back buffer of the
+/// source and swapped in. The source's buffers keep the size the capture started at: when the
+/// captured window changes size the capture follows it, but only the part that fits the buffers
+/// is shown, and [`GraphicsCapture::content_size`] tells how much of them is current.
+///
+/// The capture owns the source's textures, so keep it alive as long as a canvas displays them.
+pub struct GraphicsCapture {
+    item: GraphicsCaptureItem,
+    frame_pool: Direct3D11CaptureFramePool,
+    session: Mutex<Option<GraphicsCaptureSession>>,
+    frame_arrived_token: i64,
+    item_closed_token: i64,
+    source: GpuCanvasSource,
+    shared: Arc<CaptureShared>,
+}
+
+/// State shared with the frame pool's worker thread.
+struct CaptureShared {
+    source: GpuCanvasSource,
+    /// Whether arriving frames are copied into the source
+    running: AtomicBool,
+    /// Frames arriving sooner than this after the last copied one are dropped
+    min_frame_interval_nanos: AtomicU64,
+    state: Mutex<CaptureState>,
+}
+
+struct CaptureState {
+    device_context: ID3D11DeviceContext,
+    winrt_device: IDirect3DDevice,
+    /// The textures behind the source's two buffers, with the NT handles they are shared through
+    buffers: [(ID3D11Texture2D, isize); 2],
+    buffer_size: SizeInt32,
+    /// The size the frame pool currently allocates frames at
+    pool_size: SizeInt32,
+    /// Size of the most recent frame copied into the source, clamped to the buffers
+    content_size: SizeInt32,
+    next_buffer: usize,
+    last_copied_at: Option<Instant>,
+}
+
+impl GraphicsCapture {
+    /// Capture the window with the given `HWND`.
+    pub fn for_window(hwnd: isize) -> Result<Self> {
+        let interop = windows::core::factory::<GraphicsCaptureItem, IGraphicsCaptureItemInterop>()?;
+        let item = unsafe { interop.CreateForWindow(HWND(hwnd as _)) }
+            .context("creating a capture item for the window")?;
+        Self::new(item)
+    }
+
+    /// Capture the monitor with the given `HMONITOR`.
+    pub fn for_monitor(hmonitor: isize) -> Result<Self> {
+        let interop = windows::core::factory::<GraphicsCaptureItem, IGraphicsCaptureItemInterop>()?;
+        let item = unsafe { interop.CreateForMonitor(HMONITOR(hmonitor as _)) }
+            .context("creating a capture item for the monitor")?;
+        Self::new(item)
+    }
+
+    /// Capture `item`, for example one the user chose with a `GraphicsCapturePicker`. Capturing
+    /// starts immediately.
+    pub fn new(item: GraphicsCaptureItem) -> Result<Self> {
+        let devices = DirectXDevices::new().context("creating a capture device")?;
+        let dxgi_device: IDXGIDevice = devices.device.cast()?;
+        let winrt_device: IDirect3DDevice =
+            unsafe { CreateDirect3D11DeviceFromDXGIDevice(&dxgi_device) }?.cast()?;
+
+        let buffer_size = item.Size()?;
+        anyhow::ensure!(
+            buffer_size.Width > 0 && buffer_size.Height > 0,
+            "the capture item has no area"
+        );
+        let device_size = size(
+            DevicePixels(buffer_size.Width),
+            DevicePixels(buffer_size.Height),
+        );
+        let front = create_shared_texture(&devices.device, device_size, GpuTextureFormat::BGRA8)?;
+        let back = create_shared_texture(&devices.device, device_size, GpuTextureFormat::BGRA8)
+            .inspect_err(|_| unsafe {
+                CloseHandle(front.1).log_err();
+            })?;
+        let buffers = [front, back].map(|(texture, nt_handle)| (texture, nt_handle.0 as isize));
+        let handle = |nt_handle: isize| {
+            GpuTextureHandle::new_with_format(
+                nt_handle,
+                buffer_size.Width as u32,
+                buffer_size.Height as u32,
+                GpuTextureFormat::BGRA8,
+            )
+        };
+        let source = GpuCanvasSource::new(handle(buffers[0].1), handle(buffers[1].1));
+
+        let frame_pool = Direct3D11CaptureFramePool::CreateFreeThreaded(
+            &winrt_device,
+            CAPTURE_FORMAT,
+            2,
+            buffer_size,
+        )?;
+        let shared = Arc::new(CaptureShared {
+            source: source.clone(),
+            running: AtomicBool::new(true),
+            min_frame_interval_nanos: AtomicU64::new(0),
+            state: Mutex::new(CaptureState {
+                device_context: devices.device_context,
+                winrt_device,
+                buffers,
+                buffer_size,
+                pool_size: buffer_size,
+                content_size: SizeInt32::default(),
+                next_buffer: 1,
+                last_copied_at: None,
+            }),
+        });
+
+        let frame_arrived_token = frame_pool.FrameArrived(&TypedEventHandler::new({
+            let shared = shared.clone();
+            move |frame_pool: windows::core::Ref<Direct3D11CaptureFramePool>, _| {
+                if let Some(frame_pool) = frame_pool.as_ref() {
+                    shared.frame_arrived(frame_pool);
+                }
+                Ok(())
+            }
+        }))?;
+        let item_closed_token = item.Closed(&TypedEventHandler::new({
+            let shared = shared.clone();
+            move |_: windows::core::Ref<GraphicsCaptureItem>,
+                  _: windows::core::Ref<IInspectable>| {
+                shared.running.store(false, Ordering::Release);
+                shared.source.report_status(ProducerStatus::Idle);
+                Ok(())
+            }
+        }))?;
+
+        let this = Self {
+            item,
+            frame_pool,
+            session: Mutex::new(None),
+            frame_arrived_token,
+            item_closed_token,
+            source,
+            shared,
+        };
+        this.start()?;
+        Ok(this)
+    }
+
+    /// The source the captured frames are published through.
+    pub fn source(&self) -> &GpuCanvasSource {
+        &self.source
+    }
+
+    /// Resume capturing after [`GraphicsCapture::stop`].
+    pub fn start(&self) -> Result<()> {
+        let mut session = self.session.lock();
+        if session.is_none() {
+            let new_session = self.frame_pool.CreateCaptureSession(&self.item)?;
+            new_session.StartCapture()?;
+            *session = Some(new_session);
+        }
+        self.shared.running.store(true, Ordering::Release);
+        self.source.report_status(ProducerStatus::Rendering);
+        Ok(())
+    }
+
+    /// Stop capturing. The source keeps displaying the last captured frame.
+    pub fn stop(&self) {
+        self.shared.running.store(false, Ordering::Release);
+        if let Some(session) = self.session.lock().take() {
+            session.Close().log_err();
+        }
+        self.source.report_status(ProducerStatus::Idle);
+    }
+
+    /// Whether frames are being captured.
+    pub fn is_running(&self) -> bool {
+        self.shared.running.load(Ordering::Acquire)
+    }
+
+    /// Limit how many frames per second are copied into the source. Frames the system delivers
+    /// faster than this are dropped. `None` copies every frame.
+    pub fn set_max_frame_rate(&self, max_frame_rate: Option<f32>) {
+        let interval = max_frame_rate
+            .filter(|rate| *rate > 0.)
+            .map_or(Duration::ZERO, |rate| Duration::from_secs_f32(1. / rate));
+        self.shared
+            .min_frame_interval_nanos
+            .store(interval.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// How much of the source's buffers the most recent frame covers, measured from their
+    /// top-left corner. Display only this region, e.g. with
+    /// [`GpuCanvas::source_rect`](crate::GpuCanvas::source_rect), once the captured window has
+    /// shrunk.
+    pub fn content_size(&self) -> Size<DevicePixels> {
+        let content_size = self.shared.state.lock().content_size;
+        size(
+            DevicePixels(content_size.Width),
+            DevicePixels(content_size.Height),
+        )
+    }
+}
+
+impl CaptureShared {
+    fn frame_arrived(&self, frame_pool: &Direct3D11CaptureFramePool) {
+        if let Err(error) = self.copy_next_frame(frame_pool) {
+            log::error!("failed to copy captured frame: {error:?}");
+            let code = error
+                .downcast_ref::<windows::core::Error>()
+                .map_or(0, |error| error.code().0 as u32);
+            self.source.report_status(ProducerStatus::Error(code));
+        }
+    }
+
+    fn copy_next_frame(&self, frame_pool: &Direct3D11CaptureFramePool) -> Result<()> {
+        // Always take the frame, so the pool can reuse its buffer.
+        let frame = frame_pool.TryGetNextFrame()?;
+        if !self.running.load(Ordering::Acquire) {
+            return Ok(());
+        }
+
+        let mut state = self.state.lock();
+        let now = Instant::now();
+        let min_interval =
+            Duration::from_nanos(self.min_frame_interval_nanos.load(Ordering::Relaxed));
+        if state
+            .last_copied_at
+            .is_some_and(|last| now.saturating_duration_since(last) < min_interval)
+        {
+            return Ok(());
+        }
+
+        // Frames keep the size the pool was created with until it is recreated for the new one.
+        let content_size = frame.ContentSize()?;
+        if content_size != state.pool_size && content_size.Width > 0 && content_size.Height > 0 {
+            frame_pool.Recreate(&state.winrt_device, CAPTURE_FORMAT, 2, content_size)?;
+            state.pool_size = content_size;
+        }
+
+        let surface = frame.Surface()?;
+        let texture: ID3D11Texture2D = unsafe {
+            surface
+                .cast::<IDirect3DDxgiInterfaceAccess>()?
+                .GetInterface()
+        }?;
+        let copied_size = SizeInt32 {
+            Width: content_size.Width.min(state.buffer_size.Width).max(0),
+            Height: content_size.Height.min(state.buffer_size.Height).max(0),
+        };
+        let region = D3D11_BOX {
+            left: 0,
+            top: 0,
+            front: 0,
+            right: copied_size.Width as u32,
+            bottom: copied_size.Height as u32,
+            back: 1,
+        };
+        let back_buffer = state.next_buffer;
+        unsafe {
+            state.device_context.CopySubresourceRegion(
+                &state.buffers[back_buffer].0,
+                0,
+                0,
+                0,
+                0,
+                &texture,
+                0,
+                Some(&region),
+            );
+            // The renderer samples the copy from its own device.
+            state.device_context.Flush();
+        }
+        state.next_buffer ^= 1;
+        state.content_size = copied_size;
+        state.last_copied_at = Some(now);
+        drop(state);
+
+        self.source.set_active_buffer(back_buffer);
+        // Clears an error reported for an earlier frame.
+        if self.running.load(Ordering::Acquire) {
+            self.source.report_status(ProducerStatus::Rendering);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for GraphicsCapture {
+    fn drop(&mut self) {
+        self.stop();
+        self.frame_pool
+            .RemoveFrameArrived(self.frame_arrived_token)
+            .log_err();
+        self.item.RemoveClosed(self.item_closed_token).log_err();
+        self.frame_pool.Close().log_err();
+        let state = self.shared.state.lock();
+        for (_, nt_handle) in &state.buffers {
+            unsafe { CloseHandle(HANDLE(*nt_handle as _)) }.log_err();
+        }
+    }
+}
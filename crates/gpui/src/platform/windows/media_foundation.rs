@@ -0,0 +1,185 @@
+use std::time::Duration;
+
+use ::util::ResultExt;
+use anyhow::{Context as _, Result};
+use windows::{
+    Win32::{
+        Foundation::{CloseHandle, HANDLE},
+        Graphics::{
+            Direct3D11::{
+                D3D11_BOX, D3D11_TEXTURE2D_DESC, ID3D11Device, ID3D11DeviceContext, ID3D11Texture2D,
+            },
+            Dxgi::Common::DXGI_FORMAT_NV12,
+        },
+        Media::MediaFoundation::{IMFDXGIBuffer, IMFSample},
+    },
+    core::Interface,
+};
+
+use crate::{
+    DevicePixels, GpuCanvasSource, GpuTextureFormat, GpuTextureHandle, ProducerStatus, Size,
+    YuvConversion, create_shared_texture,
+};
+
+/// Publishes NV12 frames decoded by Media Foundation through a [`GpuCanvasSource`], copying each
+/// one GPU to GPU.
+///
+/// Decoders usually output slices of a texture array that other devices cannot open, so every
+/// frame is copied out of its slice into one of the source's two shared NV12 textures. Create
+/// the frames on the device the decoder outputs on, the one its `IMFDXGIDeviceManager` holds.
+///
+/// Copying and presenting are separate steps, so frames can be shown at their presentation
+/// time:
+///
+/// ```ignore
+/// let presentation_time = frames.copy_sample(&sample)?;
+/// // Wait until the playback clock reaches `presentation_time`.
+/// frames.present();
+/// ```
+///
+/// If the decoder's device is lost, [`MediaFoundationFrames::copy_sample`] fails and the source
+/// reports [`ProducerStatus::Error`]. Playback recovers by resetting the device manager with a
+/// new device and creating new frames, and a new source, on it.
+pub struct MediaFoundationFrames {
+    device: ID3D11Device,
+    device_context: ID3D11DeviceContext,
+    /// The textures behind the source's two buffers, with the NT handles they are shared through
+    buffers: [(ID3D11Texture2D, isize); 2],
+    size: Size<DevicePixels>,
+    /// The buffer the source displays
+    front_buffer: usize,
+    /// The buffer the last copied frame went to, if it has not been presented yet
+    copied_buffer: Option<usize>,
+    source: GpuCanvasSource,
+}
+
+impl MediaFoundationFrames {
+    /// Allocate buffers for frames of `size` on the decoder's `device`. Both dimensions must be
+    /// even. Frames decoded at a larger, padded size are cropped to it.
+    pub fn new(
+        device: &ID3D11Device,
+        size: Size<DevicePixels>,
+        yuv_conversion: YuvConversion,
+    ) -> Result<Self> {
+        let front = create_shared_texture(device, size, GpuTextureFormat::Nv12)?;
+        let back = create_shared_texture(device, size, GpuTextureFormat::Nv12).inspect_err(
+            |_| unsafe {
+                CloseHandle(front.1).log_err();
+            },
+        )?;
+        let buffers = [front, back].map(|(texture, nt_handle)| (texture, nt_handle.0 as isize));
+        let handle = |nt_handle: isize| {
+            GpuTextureHandle::new_with_format(
+                nt_handle,
+                u32::from(size.width),
+                u32::from(size.height),
+                GpuTextureFormat::Nv12,
+            )
+            .with_yuv_conversion(yuv_conversion)
+        };
+        let source = GpuCanvasSource::new(handle(buffers[0].1), handle(buffers[1].1));
+        Ok(Self {
+            device: device.clone(),
+            device_context: unsafe { device.GetImmediateContext() }?,
+            buffers,
+            size,
+            front_buffer: 0,
+            copied_buffer: None,
+            source,
+        })
+    }
+
+    /// The source the frames are published through.
+    pub fn source(&self) -> &GpuCanvasSource {
+        &self.source
+    }
+
+    /// Copy the frame in a decoder output `sample` into the buffer that is not displayed, and
+    /// return its presentation time. Returns `None` for samples without a presentation time.
+    ///
+    /// The frame is not displayed until [`MediaFoundationFrames::present`]. Copying another
+    /// sample first replaces it.
+    pub fn copy_sample(&mut self, sample: &IMFSample) -> Result<Option<Duration>> {
+        let result = self.copy_sample_internal(sample);
+        if result.is_err()
+            && let Err(reason) = unsafe { self.device.GetDeviceRemovedReason() }
+        {
+            self.source
+                .report_status(ProducerStatus::Error(reason.code().0 as u32));
+            return result.context("the decoder's device was lost");
+        }
+        result
+    }
+
+    fn copy_sample_internal(&mut self, sample: &IMFSample) -> Result<Option<Duration>> {
+        let buffer = unsafe { sample.GetBufferByIndex(0) }?;
+        let dxgi_buffer: IMFDXGIBuffer = buffer
+            .cast()
+            .context("the sample is not backed by a D3D11 texture")?;
+        let texture: ID3D11Texture2D = unsafe { dxgi_buffer.GetResource() }?;
+        // The decoder's output is one slice of a texture array.
+        let subresource = unsafe { dxgi_buffer.GetSubresourceIndex() }?;
+
+        let mut desc = D3D11_TEXTURE2D_DESC::default();
+        unsafe { texture.GetDesc(&mut desc) };
+        anyhow::ensure!(
+            desc.Format == DXGI_FORMAT_NV12,
+            "expected NV12 decoder output, got {:?}",
+            desc.Format
+        );
+        // NV12 regions must cover whole 2x2 chroma blocks.
+        let region = D3D11_BOX {
+            left: 0,
+            top: 0,
+            front: 0,
+            right: desc.Width.min(u32::from(self.size.width)) & !1,
+            bottom: desc.Height.min(u32::from(self.size.height)) & !1,
+            back: 1,
+        };
+
+        let back_buffer = self.front_buffer ^ 1;
+        unsafe {
+            self.device_context.CopySubresourceRegion(
+                &self.buffers[back_buffer].0,
+                0,
+                0,
+                0,
+                0,
+                &texture,
+                subresource,
+                Some(&region),
+            );
+            // The renderer samples the copy from its own device.
+            self.device_context.Flush();
+        }
+        self.copied_buffer = Some(back_buffer);
+
+        // Sample times are in 100 nanosecond units.
+        Ok(unsafe { sample.GetSampleTime() }
+            .ok()
+            .map(|time| Duration::from_nanos(time.max(0) as u64 * 100)))
+    }
+
+    /// Display the most recently copied frame. Does nothing if it has already been presented.
+    pub fn present(&mut self) {
+        if let Some(buffer) = self.copied_buffer.take() {
+            self.front_buffer = buffer;
+            self.source.set_active_buffer(buffer);
+            self.source.report_status(ProducerStatus::Rendering);
+        }
+    }
+
+    /// Whether a copied frame is waiting for [`MediaFoundationFrames::present`].
+    pub fn has_pending_frame(&self) -> bool {
+        self.copied_buffer.is_some()
+    }
+}
+
+impl Drop for MediaFoundationFrames {
+    fn drop(&mut self) {
+        self.source.report_status(ProducerStatus::Idle);
+        for (_, nt_handle) in &self.buffers {
+            unsafe { CloseHandle(HANDLE(*nt_handle as _)) }.log_err();
+        }
+    }
+}
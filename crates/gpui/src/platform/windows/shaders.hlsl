@@ -79,7 +79,7 @@ struct AtlasBounds {
 struct AtlasTile {
     AtlasTextureId texture_id;
     uint tile_id;
-    uint padding;
+    uint layer;
     AtlasBounds bounds;
 };
 
@@ -1300,3 +1300,215 @@ float4 polychrome_sprite_fragment(PolychromeSpriteFragmentInput input): SV_Targe
     color.a *= sprite.opacity * saturate(0.5 - distance);
     return color;
 }
+
+/*
+**
+**              GPU textures
+**
+*/
+
+struct GpuTextureSprite {
+    uint order;
+    // 0u is premultiplied, 1u is straight, 2u is opaque
+    uint alpha_mode;
+    Bounds bounds;
+    Bounds content_mask;
+    // The element's box; the texture is clipped to it with rounded corners
+    Bounds clip_bounds;
+    Corners corner_radii;
+    // Normalized sub-rectangle of the texture mapped onto bounds
+    Bounds uv;
+    // Bits 0-1 are clockwise quarter turns, bit 2 flips the texture vertically
+    uint orientation;
+    uint grayscale;
+    float opacity;
+    Hsla tint;
+    // 0u samples RGB, 1u converts YUV with BT.601, 2u with BT.709
+    uint yuv_matrix;
+    uint yuv_full_range;
+    // Non-zero when the red channel is the coverage of mask_color
+    uint mask;
+    Hsla mask_color;
+    // Where the texture is laid out. Differs from bounds during a transition, when bounds
+    // covers both textures.
+    Bounds texture_bounds;
+    // How far the crossfade from t_transition has progressed, 1.0 when there is none
+    float transition_progress;
+    // Layout and orientation of t_transition, like texture_bounds, uv and orientation
+    uint transition_orientation;
+    Bounds transition_bounds;
+    Bounds transition_uv;
+    // How the colors of the texture and t_transition are converted to the output color space,
+    // see transform_color
+    uint color_transform;
+    uint transition_color_transform;
+    // Applied to linear colors before they are tone mapped
+    float exposure;
+};
+
+struct GpuTextureVertexOutput {
+    nointerpolation uint sprite_id: TEXCOORD0;
+    float4 position: SV_Position;
+    float2 texture_coords: TEXCOORD1;
+    float2 transition_coords: TEXCOORD2;
+    float4 clip_distance: SV_ClipDistance;
+};
+
+struct GpuTextureFragmentInput {
+    nointerpolation uint sprite_id: TEXCOORD0;
+    float4 position: SV_Position;
+    float2 texture_coords: TEXCOORD1;
+    float2 transition_coords: TEXCOORD2;
+};
+
+StructuredBuffer<GpuTextureSprite> gpu_texture_sprites: register(t1);
+// The chroma plane of a YUV texture, whose luma plane is bound as t_sprite
+Texture2D<float2> t_chroma: register(t2);
+// The texture being faded out during a transition
+Texture2D<float4> t_transition: register(t3);
+
+float3 yuv_to_rgb(float y, float2 cb_cr, uint matrix_id, uint full_range) {
+    if (full_range != 0u) {
+        cb_cr -= 128.0 / 255.0;
+    } else {
+        y = (y - 16.0 / 255.0) * (255.0 / 219.0);
+        cb_cr = (cb_cr - 128.0 / 255.0) * (255.0 / 224.0);
+    }
+    float3 rgb;
+    if (matrix_id == 1u) {
+        rgb = float3(
+            y + 1.402 * cb_cr.y,
+            y - 0.344136 * cb_cr.x - 0.714136 * cb_cr.y,
+            y + 1.772 * cb_cr.x);
+    } else {
+        rgb = float3(
+            y + 1.5748 * cb_cr.y,
+            y - 0.187324 * cb_cr.x - 0.468124 * cb_cr.y,
+            y + 1.8556 * cb_cr.x);
+    }
+    return saturate(rgb);
+}
+
+// Showing the image rotated clockwise means sampling the texture counter-clockwise.
+float2 orient_unit_vertex(float2 unit_vertex, uint orientation) {
+    float2 texture_vertex = unit_vertex;
+    uint quarter_turns = orientation & 3u;
+    if (quarter_turns == 1u) {
+        texture_vertex = float2(unit_vertex.y, 1.0 - unit_vertex.x);
+    } else if (quarter_turns == 2u) {
+        texture_vertex = 1.0 - unit_vertex;
+    } else if (quarter_turns == 3u) {
+        texture_vertex = float2(1.0 - unit_vertex.y, unit_vertex.x);
+    }
+    if ((orientation & 4u) != 0u) {
+        texture_vertex.y = 1.0 - texture_vertex.y;
+    }
+    return texture_vertex;
+}
+
+// The coordinates sampled at `position` of a texture laid out in `texture_bounds`. Linear in
+// `position`, so they can be interpolated beyond the texture's bounds.
+float2 texture_coords_at(float2 position, Bounds texture_bounds, Bounds uv, uint orientation) {
+    float2 unit_vertex = (position - texture_bounds.origin) / texture_bounds.size;
+    return uv.origin + orient_unit_vertex(unit_vertex, orientation) * uv.size;
+}
+
+bool is_within_uv(float2 texture_coords, Bounds uv) {
+    float2 offset = texture_coords - uv.origin;
+    return all(offset >= 0.0) && all(offset <= uv.size);
+}
+
+// Converts a straight color to the sRGB the swap chain outputs:
+// 0u leaves it unchanged
+// 1u exposes a linear color, clamps it to SDR range and gamma encodes it
+// 2u does the same, compressing highlights with Reinhard instead of clamping
+// 3u expands limited range BT.709 to full range
+float3 transform_color(float3 color, uint transform, float exposure) {
+    if (transform == 3u) {
+        return saturate((color - 16.0 / 255.0) * (255.0 / 219.0));
+    }
+    if (transform == 1u || transform == 2u) {
+        color = max(color * exposure, 0.0);
+        if (transform == 2u) {
+            color = color / (1.0 + color);
+        }
+        return pow(saturate(color), 1.0 / 2.2);
+    }
+    return color;
+}
+
+// The premultiplied color of a texel, interpreted as the sprite's mask or alpha mode says and
+// converted per `color_transform`.
+float4 gpu_texture_color(GpuTextureSprite sprite, float4 texel, uint color_transform) {
+    if (sprite.mask != 0u) {
+        float4 mask_color = hsla_to_rgba(sprite.mask_color);
+        return float4(mask_color.rgb * mask_color.a, mask_color.a) * texel.r;
+    }
+    if (sprite.alpha_mode == 2u) {
+        texel.a = 1.0;
+    }
+    if (color_transform != 0u) {
+        // Conversions apply to the straight color, which premultiplied texels are divided back
+        // into.
+        if (sprite.alpha_mode == 0u && texel.a > 0.0) {
+            texel.rgb /= texel.a;
+        }
+        texel.rgb = transform_color(texel.rgb, color_transform, sprite.exposure);
+        texel.rgb *= texel.a;
+    } else if (sprite.alpha_mode == 1u) {
+        texel.rgb *= texel.a;
+    }
+    return texel;
+}
+
+GpuTextureVertexOutput gpu_texture_vertex(uint vertex_id: SV_VertexID, uint sprite_id: SV_InstanceID) {
+    float2 unit_vertex = float2(float(vertex_id & 1u), 0.5 * float(vertex_id & 2u));
+    GpuTextureSprite sprite = gpu_texture_sprites[sprite_id];
+
+    GpuTextureVertexOutput output;
+    output.position = to_device_position(unit_vertex, sprite.bounds);
+    float2 position = sprite.bounds.origin + unit_vertex * sprite.bounds.size;
+    output.texture_coords =
+        texture_coords_at(position, sprite.texture_bounds, sprite.uv, sprite.orientation);
+    output.transition_coords = texture_coords_at(
+        position, sprite.transition_bounds, sprite.transition_uv, sprite.transition_orientation);
+    output.sprite_id = sprite_id;
+    output.clip_distance = distance_from_clip_rect(unit_vertex, sprite.bounds, sprite.content_mask);
+    return output;
+}
+
+float4 gpu_texture_fragment(GpuTextureFragmentInput input): SV_Target {
+    GpuTextureSprite sprite = gpu_texture_sprites[input.sprite_id];
+    float4 texel;
+    if (sprite.mask == 0u && sprite.yuv_matrix != 0u) {
+        float y = t_sprite.Sample(s_sprite, input.texture_coords).r;
+        float2 cb_cr = t_chroma.Sample(s_sprite, input.texture_coords);
+        texel = float4(yuv_to_rgb(y, cb_cr, sprite.yuv_matrix, sprite.yuv_full_range), 1.0);
+    } else {
+        texel = t_sprite.Sample(s_sprite, input.texture_coords);
+    }
+    float4 color = gpu_texture_color(sprite, texel, sprite.color_transform);
+    if (sprite.transition_progress < 1.0) {
+        // Each texture only covers its own part of the bounds, which are shared between both.
+        if (!is_within_uv(input.texture_coords, sprite.uv)) {
+            color = 0.0;
+        }
+        float4 transition_color = 0.0;
+        if (is_within_uv(input.transition_coords, sprite.transition_uv)) {
+            transition_color = gpu_texture_color(
+                sprite,
+                t_transition.Sample(s_sprite, input.transition_coords),
+                sprite.transition_color_transform);
+        }
+        // Both colors are premultiplied, so they blend correctly where either is transparent.
+        color = lerp(transition_color, color, sprite.transition_progress);
+    }
+    if (sprite.grayscale != 0u) {
+        color.rgb = dot(color.rgb, GRAYSCALE_FACTORS);
+    }
+    // The color is premultiplied here, so scaling alpha means scaling every channel.
+    float4 tint = hsla_to_rgba(sprite.tint);
+    color *= float4(tint.rgb * tint.a, tint.a);
+    float distance = quad_sdf(input.position.xy, sprite.clip_bounds, sprite.corner_radii);
+    return color * sprite.opacity * saturate(0.5 - distance);
+}
@@ -55,7 +55,7 @@ impl VSyncProvider {
     }
 }
 
-fn get_dwm_interval() -> Result<Duration> {
+pub(crate) fn get_dwm_interval() -> Result<Duration> {
     let mut timing_info = DWM_TIMING_INFO {
         cbSize: std::mem::size_of::<DWM_TIMING_INFO>() as u32,
         ..Default::default()
@@ -1,12 +1,12 @@
 #![deny(unsafe_op_in_unsafe_fn)]
 
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     num::NonZeroIsize,
     path::PathBuf,
     rc::{Rc, Weak},
     str::FromStr,
-    sync::{Arc, Once},
+    sync::{Arc, Mutex, Once},
     time::{Duration, Instant},
 };
 
@@ -21,7 +21,16 @@ use windows::{
         Foundation::*,
         Graphics::Gdi::*,
         System::{Com::*, LibraryLoader::*, Ole::*, SystemServices::*},
-        UI::{Controls::*, HiDpi::*, Input::KeyboardAndMouse::*, Shell::*, WindowsAndMessaging::*},
+        UI::{
+            Controls::*,
+            HiDpi::*,
+            Input::{
+                KeyboardAndMouse::*, RAWINPUTDEVICE, RAWINPUTDEVICE_FLAGS, RIDEV_REMOVE,
+                RegisterRawInputDevices,
+            },
+            Shell::*,
+            WindowsAndMessaging::*,
+        },
     },
     core::*,
 };
@@ -54,6 +63,8 @@ pub struct WindowsWindowState {
     pub system_settings: WindowsSystemSettings,
     pub current_cursor: Option<HCURSOR>,
     pub nc_button_pressed: Option<u32>,
+    pub pointer_lock: Cell<Option<PointerLock>>,
+    pub raw_mouse_input: Cell<bool>,
 
     pub display: WindowsDisplay,
     fullscreen: Option<StyleAndBounds>,
@@ -118,6 +129,8 @@ impl WindowsWindowState {
         let click_state = ClickState::new();
         let system_settings = WindowsSystemSettings::new(display);
         let nc_button_pressed = None;
+        let pointer_lock = Cell::new(None);
+        let raw_mouse_input = Cell::new(false);
         let fullscreen = None;
         let initial_placement = None;
 
@@ -142,6 +155,8 @@ impl WindowsWindowState {
             system_settings,
             current_cursor,
             nc_button_pressed,
+            pointer_lock,
+            raw_mouse_input,
             display,
             fullscreen,
             initial_placement,
@@ -331,6 +346,7 @@ pub(crate) struct Callbacks {
     pub(crate) close: Option<Box<dyn FnOnce()>>,
     pub(crate) hit_test_window_control: Option<Box<dyn FnMut() -> Option<WindowControlArea>>>,
     pub(crate) appearance_changed: Option<Box<dyn FnMut()>>,
+    pub(crate) visibility_changed: Option<Box<dyn FnMut(WindowVisibility)>>,
 }
 
 struct WindowCreateContext {
@@ -958,6 +974,10 @@ impl PlatformWindow for WindowsWindow {
         self.0.state.borrow_mut().callbacks.appearance_changed = Some(callback);
     }
 
+    fn on_visibility_changed(&self, callback: Box<dyn FnMut(WindowVisibility)>) {
+        self.0.state.borrow_mut().callbacks.visibility_changed = Some(callback);
+    }
+
     fn draw(&self, scene: &Scene) {
         self.0.state.borrow_mut().renderer.draw(scene).log_err();
     }
@@ -990,9 +1010,210 @@ impl PlatformWindow for WindowsWindow {
         self.0.state.borrow().renderer.gpu_specs().log_err()
     }
 
+    fn refresh_interval(&self) -> Option<Duration> {
+        get_dwm_interval().ok()
+    }
+
+    fn gpu_texture_format_support(&self) -> Vec<GpuTextureFormatSupport> {
+        self.0.state.borrow().renderer.gpu_texture_format_support()
+    }
+
+    fn create_shared_texture(
+        &self,
+        size: Size<DevicePixels>,
+        format: GpuTextureFormat,
+    ) -> Result<GpuTextureHandle> {
+        self.0
+            .state
+            .borrow_mut()
+            .renderer
+            .create_shared_texture(size, format)
+    }
+
+    fn shared_textures(&self) -> Vec<GpuTextureHandle> {
+        self.0.state.borrow().renderer.shared_textures()
+    }
+
+    fn release_shared_textures(&self, textures: &[GpuTextureHandle]) {
+        self.0
+            .state
+            .borrow_mut()
+            .renderer
+            .release_shared_textures(textures)
+    }
+
+    fn invalidate_shared_texture(&self, native_handle: isize) {
+        self.0
+            .state
+            .borrow_mut()
+            .renderer
+            .invalidate_shared_texture(native_handle)
+    }
+
+    fn set_gpu_timing_enabled(&self, enabled: bool) {
+        self.0
+            .state
+            .borrow_mut()
+            .renderer
+            .set_gpu_timing_enabled(enabled)
+    }
+
+    fn gpu_texture_timings(&self, native_handle: isize) -> Option<GpuTextureTimings> {
+        self.0
+            .state
+            .borrow()
+            .renderer
+            .gpu_texture_timings(native_handle)
+    }
+
+    fn capture_bounds(
+        &self,
+        bounds: Bounds<DevicePixels>,
+    ) -> oneshot::Receiver<Result<image::RgbaImage>> {
+        self.0.state.borrow_mut().renderer.capture_bounds(bounds)
+    }
+
+    fn write_shared_texture(
+        &self,
+        texture: &GpuTextureHandle,
+        copy_from: Option<&GpuTextureHandle>,
+        region: Bounds<DevicePixels>,
+        data: &[u8],
+        stride: usize,
+    ) -> Result<()> {
+        self.0
+            .state
+            .borrow_mut()
+            .renderer
+            .write_shared_texture(texture, copy_from, region, data, stride)
+    }
+
     fn update_ime_position(&self, _bounds: Bounds<Pixels>) {
         // There is no such thing on Windows.
     }
+
+    fn lock_pointer(&self, bounds: Bounds<Pixels>) -> bool {
+        let state = self.0.state.borrow();
+        let scale_factor = state.scale_factor;
+        let mut top_left = POINT {
+            x: (bounds.left().0 * scale_factor).round() as i32,
+            y: (bounds.top().0 * scale_factor).round() as i32,
+        };
+        let mut bottom_right = POINT {
+            x: (bounds.right().0 * scale_factor).round() as i32,
+            y: (bounds.bottom().0 * scale_factor).round() as i32,
+        };
+        unsafe {
+            if !ClientToScreen(self.0.hwnd, &mut top_left).as_bool()
+                || !ClientToScreen(self.0.hwnd, &mut bottom_right).as_bool()
+            {
+                return false;
+            }
+        }
+        let clip = RECT {
+            left: top_left.x,
+            top: top_left.y,
+            right: bottom_right.x,
+            bottom: bottom_right.y,
+        };
+        let center = POINT {
+            x: (clip.left + clip.right) / 2,
+            y: (clip.top + clip.bottom) / 2,
+        };
+        unsafe {
+            if ClipCursor(Some(&clip as *const RECT)).log_err().is_none() {
+                return false;
+            }
+            // ShowCursor keeps a display count, so only hide once per lock.
+            if state.pointer_lock.get().is_none() {
+                ShowCursor(false);
+            }
+            SetCursorPos(center.x, center.y).log_err();
+        }
+        state.pointer_lock.set(Some(PointerLock { center }));
+        true
+    }
+
+    fn unlock_pointer(&self) {
+        release_pointer_lock(&self.0.state.borrow().pointer_lock);
+    }
+
+    fn is_pointer_locked(&self) -> bool {
+        self.0.state.borrow().pointer_lock.get().is_some()
+    }
+
+    fn set_raw_mouse_input(&self, enabled: bool) -> bool {
+        let state = self.0.state.borrow();
+        if state.raw_mouse_input.get() != enabled {
+            if set_raw_mouse_input_window(self.0.hwnd, enabled)
+                .log_err()
+                .is_none()
+            {
+                return false;
+            }
+            state.raw_mouse_input.set(enabled);
+        }
+        true
+    }
+}
+
+/// A pointer lock taken by [`PlatformWindow::lock_pointer`]: the cursor is hidden, clipped to the
+/// locked bounds and moved back to `center` (in screen coordinates) after every reported motion.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PointerLock {
+    pub center: POINT,
+}
+
+pub(crate) fn release_pointer_lock(pointer_lock: &Cell<Option<PointerLock>>) {
+    if pointer_lock.take().is_some() {
+        unsafe {
+            ClipCursor(None).log_err();
+            ShowCursor(true);
+        }
+    }
+}
+
+const HID_USAGE_PAGE_GENERIC: u16 = 0x01;
+const HID_USAGE_GENERIC_MOUSE: u16 = 0x02;
+
+/// The windows that enabled raw mouse input, most recent last. Raw input registrations are per
+/// process, so one registration serves all of them and targets the last.
+static RAW_MOUSE_INPUT_WINDOWS: Mutex<Vec<isize>> = Mutex::new(Vec::new());
+
+/// Add `hwnd` to the windows using raw mouse input, or remove it. The process's registration is
+/// moved to the most recent window still using raw input, and only removed along with the last.
+pub(crate) fn set_raw_mouse_input_window(hwnd: HWND, enabled: bool) -> Result<()> {
+    let mut windows = RAW_MOUSE_INPUT_WINDOWS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut updated = windows.clone();
+    updated.retain(|window| *window != hwnd.0 as isize);
+    if enabled {
+        updated.push(hwnd.0 as isize);
+    }
+    let target = updated.last().copied();
+    if target != windows.last().copied() {
+        register_raw_mouse_input(target.map(|target| HWND(target as _)))?;
+    }
+    *windows = updated;
+    Ok(())
+}
+
+/// Make `target` the receiver of `WM_INPUT` for mice, or stop the process receiving raw mouse
+/// input when it is `None`.
+fn register_raw_mouse_input(target: Option<HWND>) -> Result<()> {
+    let device = RAWINPUTDEVICE {
+        usUsagePage: HID_USAGE_PAGE_GENERIC,
+        usUsage: HID_USAGE_GENERIC_MOUSE,
+        dwFlags: if target.is_some() {
+            RAWINPUTDEVICE_FLAGS(0)
+        } else {
+            RIDEV_REMOVE
+        },
+        hwndTarget: target.unwrap_or_default(),
+    };
+    unsafe { RegisterRawInputDevices(&[device], std::mem::size_of::<RAWINPUTDEVICE>() as u32) }
+        .context("registering for raw mouse input")
 }
 
 impl WindowsWindow {
@@ -6,8 +6,8 @@ use serde::{Deserialize, Serialize};
 use slotmap::{SlotMap, new_key_type};
 
 use crate::{
-    AtlasTextureId, AtlasTile, Background, Bounds, ContentMask, Corners, Edges, Hsla, Pixels,
-    Point, Radians, ScaledPixels, Size, TransformId, TransformTable, point,
+    AtlasTextureId, AtlasTile, Background, Bounds, ContentMask, Corners, DevicePixels, Edges,
+    Hsla, Pixels, Point, Radians, ScaledPixels, Size, TransformId, TransformTable, point,
 };
 use std::{
     fmt::Debug,
@@ -1212,8 +1212,39 @@ pub(crate) struct PaintSurface {
     pub transform_index: u32,
     pub bounds: Bounds<ScaledPixels>,
     pub content_mask: ContentMask<ScaledPixels>,
+    pub corner_radii: Corners<ScaledPixels>,
     pub object_fit: crate::ObjectFit,
+    pub filter: crate::TextureFilter,
+    pub alpha_mode: crate::AlphaMode,
+    pub source_rect: Bounds<DevicePixels>,
+    pub flip_y: bool,
+    pub rotation: crate::CanvasRotation,
+    pub pixel_perfect: bool,
+    pub opacity: f32,
+    pub grayscale: bool,
+    pub tint: Option<Hsla>,
+    /// Set when the source holds YUV samples that must be converted to RGB
+    pub yuv_conversion: Option<crate::YuvConversion>,
+    /// Set when the source's red channel is the coverage of this color
+    pub mask_color: Option<Hsla>,
+    /// How the source's RGB values are encoded
+    pub color_space: crate::ColorSpace,
+    /// Applied to linear sources when the window displays SDR
+    pub tone_mapping: Option<crate::ToneMapping>,
+    /// Set while crossfading from another surface to `source`
+    pub transition: Option<SurfaceTransition>,
+    pub source: SurfaceSource,
+}
+
+/// The surface a [`PaintSurface`] fades in from. It is laid out with the same fit and rotation.
+#[derive(Clone, Debug)]
+pub(crate) struct SurfaceTransition {
     pub source: SurfaceSource,
+    pub source_rect: Bounds<DevicePixels>,
+    pub flip_y: bool,
+    pub color_space: crate::ColorSpace,
+    /// From 0.0, showing only this surface, to 1.0, showing only the one fading in
+    pub progress: f32,
 }
 
 #[derive(Clone, Debug)]
@@ -1225,6 +1256,8 @@ pub(crate) enum SurfaceSource {
         nt_handle: isize,
         width: u32,
         height: u32,
+        format: crate::GpuTextureFormat,
+        generation: u64,
     },
     #[cfg(target_os = "linux")]
     DmaBuf {
@@ -1234,6 +1267,21 @@ pub(crate) enum SurfaceSource {
     },
 }
 
+impl SurfaceSource {
+    /// The OS handle the surface was painted from, where the source is one.
+    #[cfg(any(test, feature = "test-support"))]
+    pub(crate) fn native_handle(&self) -> Option<isize> {
+        match self {
+            #[cfg(target_os = "macos")]
+            SurfaceSource::ImageBuffer(_) => None,
+            #[cfg(target_os = "windows")]
+            SurfaceSource::SharedTexture { nt_handle, .. } => Some(*nt_handle),
+            #[cfg(target_os = "linux")]
+            SurfaceSource::DmaBuf { fd, .. } => Some(*fd as isize),
+        }
+    }
+}
+
 impl From<PaintSurface> for Primitive {
     fn from(surface: PaintSurface) -> Self {
         Primitive::Surface(surface)
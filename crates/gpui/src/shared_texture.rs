@@ -28,7 +28,9 @@
 //! }
 //! ```
 
-use crate::{Size, DevicePixels};
+use crate::{ColorSpace, Size, DevicePixels};
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+use crate::{GpuTextureFormat, GpuTextureHandle};
 
 /// Cross-platform shared texture handle
 ///
@@ -51,6 +53,8 @@ pub enum SharedTextureHandle {
         size: Size<DevicePixels>,
         /// The DXGI format of the texture (typically DXGI_FORMAT_B8G8R8A8_UNORM = 87)
         format: u32,
+        /// How the texture's color values are encoded
+        color_space: ColorSpace,
     },
 
     /// macOS IOSurface handle
@@ -68,6 +72,8 @@ pub enum SharedTextureHandle {
         size: Size<DevicePixels>,
         /// The Metal pixel format (e.g., MTLPixelFormatBGRA8Unorm = 80)
         format: u32,
+        /// How the texture's color values are encoded
+        color_space: ColorSpace,
     },
 
     /// Linux DMA-BUF file descriptor
@@ -89,6 +95,8 @@ pub enum SharedTextureHandle {
         format: u32,
         /// Stride in bytes
         stride: u32,
+        /// How the texture's color values are encoded
+        color_space: ColorSpace,
     },
 }
 
@@ -105,6 +113,18 @@ impl SharedTextureHandle {
         }
     }
 
+    /// Get how the texture's color values are encoded
+    pub fn color_space(&self) -> ColorSpace {
+        match self {
+            #[cfg(target_os = "windows")]
+            SharedTextureHandle::D3D11NTHandle { color_space, .. } => *color_space,
+            #[cfg(target_os = "macos")]
+            SharedTextureHandle::IOSurface { color_space, .. } => *color_space,
+            #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+            SharedTextureHandle::DmaBuf { color_space, .. } => *color_space,
+        }
+    }
+
     /// Get a human-readable description of the handle type
     pub fn type_name(&self) -> &'static str {
         match self {
@@ -150,6 +170,147 @@ impl SharedTextureHandle {
     }
 }
 
+/// The parameters for importing a dma-buf into OpenGL or GLES with `eglCreateImageKHR`, using
+/// the `EGL_LINUX_DMA_BUF_EXT` target and a null client buffer.
+///
+/// ```ignore
+/// let attributes = image.attributes();
+/// let egl_image = eglCreateImageKHR(
+///     display,
+///     EGL_NO_CONTEXT,
+///     EGL_LINUX_DMA_BUF_EXT,
+///     std::ptr::null_mut(),
+///     attributes.as_ptr(),
+/// );
+/// glBindTexture(GL_TEXTURE_2D, texture);
+/// glEGLImageTargetTexture2DOES(GL_TEXTURE_2D, egl_image);
+/// ```
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EglDmaBufImage {
+    /// The dma-buf file descriptor. EGL duplicates it, so it stays owned by the caller.
+    pub fd: i32,
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+    /// The DRM fourcc code of the pixel format
+    pub fourcc: u32,
+    /// Byte offset of the first row
+    pub offset: u32,
+    /// Bytes between the starts of consecutive rows
+    pub stride: u32,
+    /// The DRM format modifier, if the exporter reported one
+    pub modifier: Option<u64>,
+}
+
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+impl EglDmaBufImage {
+    const DRM_FORMAT_ARGB8888: u32 = fourcc(b"AR24");
+    const DRM_FORMAT_ABGR8888: u32 = fourcc(b"AB24");
+    const DRM_FORMAT_ABGR16161616F: u32 = fourcc(b"AB4H");
+    const DRM_FORMAT_R8: u32 = fourcc(b"R8  ");
+    const DRM_FORMAT_MOD_INVALID: u64 = 0x00ff_ffff_ffff_ffff;
+
+    /// Describe a gpu canvas texture exported as a linear, tightly packed dma-buf. Returns `None`
+    /// for formats that need more than one plane.
+    pub fn for_texture(texture: &GpuTextureHandle) -> Option<Self> {
+        let fourcc = match texture.format {
+            GpuTextureFormat::RGBA8 => Self::DRM_FORMAT_ABGR8888,
+            GpuTextureFormat::BGRA8 => Self::DRM_FORMAT_ARGB8888,
+            GpuTextureFormat::RGBA16F => Self::DRM_FORMAT_ABGR16161616F,
+            GpuTextureFormat::R8 => Self::DRM_FORMAT_R8,
+            GpuTextureFormat::Nv12 => return None,
+        };
+        Some(Self {
+            fd: texture.native_handle as i32,
+            width: texture.width,
+            height: texture.height,
+            fourcc,
+            offset: 0,
+            stride: texture.width * texture.bytes_per_pixel(),
+            modifier: Some(0),
+        })
+    }
+
+    /// The `EGL_NONE` terminated attribute list to pass to `eglCreateImageKHR`.
+    pub fn attributes(&self) -> Vec<i32> {
+        const EGL_NONE: i32 = 0x3038;
+        const EGL_HEIGHT: i32 = 0x3056;
+        const EGL_WIDTH: i32 = 0x3057;
+        const EGL_LINUX_DRM_FOURCC_EXT: i32 = 0x3271;
+        const EGL_DMA_BUF_PLANE0_FD_EXT: i32 = 0x3272;
+        const EGL_DMA_BUF_PLANE0_OFFSET_EXT: i32 = 0x3273;
+        const EGL_DMA_BUF_PLANE0_PITCH_EXT: i32 = 0x3274;
+        const EGL_DMA_BUF_PLANE0_MODIFIER_LO_EXT: i32 = 0x3443;
+        const EGL_DMA_BUF_PLANE0_MODIFIER_HI_EXT: i32 = 0x3444;
+
+        let mut attributes = vec![
+            EGL_WIDTH,
+            self.width as i32,
+            EGL_HEIGHT,
+            self.height as i32,
+            EGL_LINUX_DRM_FOURCC_EXT,
+            self.fourcc as i32,
+            EGL_DMA_BUF_PLANE0_FD_EXT,
+            self.fd,
+            EGL_DMA_BUF_PLANE0_OFFSET_EXT,
+            self.offset as i32,
+            EGL_DMA_BUF_PLANE0_PITCH_EXT,
+            self.stride as i32,
+        ];
+        // Without a modifier the driver assumes its own default layout.
+        if let Some(modifier) = self.modifier {
+            attributes.extend([
+                EGL_DMA_BUF_PLANE0_MODIFIER_LO_EXT,
+                modifier as u32 as i32,
+                EGL_DMA_BUF_PLANE0_MODIFIER_HI_EXT,
+                (modifier >> 32) as u32 as i32,
+            ]);
+        }
+        attributes.push(EGL_NONE);
+        attributes
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+impl SharedTextureHandle {
+    /// Describe this dma-buf for import with `eglCreateImageKHR`. Returns `None` for Vulkan
+    /// formats without a matching DRM format.
+    pub fn egl_dma_buf_image(&self) -> Option<EglDmaBufImage> {
+        let SharedTextureHandle::DmaBuf {
+            fd,
+            modifier,
+            size,
+            format,
+            stride,
+            ..
+        } = self;
+        // VK_FORMAT_R8G8B8A8_UNORM/SRGB, VK_FORMAT_B8G8R8A8_UNORM/SRGB and
+        // VK_FORMAT_R16G16B16A16_SFLOAT.
+        let fourcc = match format {
+            37 | 43 => EglDmaBufImage::DRM_FORMAT_ABGR8888,
+            44 | 50 => EglDmaBufImage::DRM_FORMAT_ARGB8888,
+            97 => EglDmaBufImage::DRM_FORMAT_ABGR16161616F,
+            _ => return None,
+        };
+        Some(EglDmaBufImage {
+            fd: *fd,
+            width: size.width.0 as u32,
+            height: size.height.0 as u32,
+            fourcc,
+            offset: 0,
+            stride: *stride,
+            modifier: (*modifier != EglDmaBufImage::DRM_FORMAT_MOD_INVALID).then_some(*modifier),
+        })
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+const fn fourcc(code: &[u8; 4]) -> u32 {
+    u32::from_le_bytes(*code)
+}
+
 /// Information needed to resize a renderer in external window mode
 #[derive(Debug, Clone, Copy)]
 pub struct ResizeInfo {
@@ -176,3 +337,42 @@ impl ResizeInfo {
         }
     }
 }
+
+#[cfg(all(test, any(target_os = "linux", target_os = "freebsd")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_egl_dma_buf_attributes() {
+        let texture = GpuTextureHandle::new_with_format(7, 64, 32, GpuTextureFormat::BGRA8);
+        let image = EglDmaBufImage::for_texture(&texture).unwrap();
+        assert_eq!(image.fourcc, u32::from_le_bytes(*b"AR24"));
+        assert_eq!(image.stride, 256);
+        let attributes = image.attributes();
+        // Width, height, fourcc, fd, offset, pitch and a linear modifier, then EGL_NONE.
+        let values: Vec<i32> = attributes.iter().skip(1).step_by(2).copied().collect();
+        assert_eq!(values, [64, 32, image.fourcc as i32, 7, 0, 256, 0, 0]);
+        assert_eq!(attributes.last(), Some(&0x3038));
+
+        let shared = SharedTextureHandle::DmaBuf {
+            fd: 7,
+            modifier: 0x00ff_ffff_ffff_ffff,
+            size: crate::size(DevicePixels(64), DevicePixels(32)),
+            format: 37,
+            stride: 512,
+            color_space: ColorSpace::SrgbNonLinear,
+        };
+        let image = shared.egl_dma_buf_image().unwrap();
+        assert_eq!(image.fourcc, u32::from_le_bytes(*b"AB24"));
+        assert_eq!(image.modifier, None);
+        assert_eq!(image.attributes().len(), 13);
+
+        let mask = GpuTextureHandle::new_with_format(7, 64, 32, GpuTextureFormat::R8);
+        let image = EglDmaBufImage::for_texture(&mask).unwrap();
+        assert_eq!(image.fourcc, u32::from_le_bytes(*b"R8  "));
+        assert_eq!(image.stride, 64);
+
+        let nv12 = GpuTextureHandle::new_with_format(7, 64, 32, GpuTextureFormat::Nv12);
+        assert_eq!(EglDmaBufImage::for_texture(&nv12), None);
+    }
+}
@@ -47,6 +47,15 @@ impl ObjectFit {
         bounds: Bounds<Pixels>,
         image_size: Size<DevicePixels>,
     ) -> Bounds<Pixels> {
+        // Collapsed layouts and not-yet-sized textures would otherwise produce NaN or infinite
+        // ratios below, so both degenerate cases fit to an empty quad at the bounds' origin.
+        if bounds.is_empty() || image_size.width.0 <= 0 || image_size.height.0 <= 0 {
+            return Bounds {
+                origin: bounds.origin,
+                size: Size::default(),
+            };
+        }
+
         let image_size = image_size.map(|dimension| Pixels::from(u32::from(dimension)));
         let image_ratio = image_size.width / image_size.height;
         let bounds_ratio = bounds.size.width / bounds.size.height;
@@ -1388,6 +1397,33 @@ mod tests {
         );
     }
 
+    #[perf]
+    fn test_object_fit_degenerate_sizes() {
+        let bounds = Bounds::new(point(px(10.), px(20.)), size(px(0.), px(50.)));
+        let image_size = size(DevicePixels(64), DevicePixels(32));
+        let empty_texture = size(DevicePixels(0), DevicePixels(32));
+        let filled = Bounds::new(point(px(10.), px(20.)), size(px(100.), px(50.)));
+
+        for object_fit in [
+            ObjectFit::Fill,
+            ObjectFit::Contain,
+            ObjectFit::Cover,
+            ObjectFit::ScaleDown,
+            ObjectFit::None,
+        ] {
+            for (bounds, image_size) in [
+                (bounds, image_size),
+                (filled, empty_texture),
+                (bounds, empty_texture),
+            ] {
+                let fitted = object_fit.get_bounds(bounds, image_size);
+                assert_eq!(fitted.origin, bounds.origin, "{object_fit:?}");
+                assert!(fitted.is_empty(), "{object_fit:?} produced {fitted:?}");
+                assert!(!fitted.size.width.0.is_nan() && !fitted.size.height.0.is_nan());
+            }
+        }
+    }
+
     #[perf]
     fn test_combine_highlights() {
         assert_eq!(
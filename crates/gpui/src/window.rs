@@ -6,17 +6,18 @@ use crate::{
     Context, Corners, CursorStyle, Decorations, DevicePixels, DispatchActionListener,
     DispatchNodeId, DispatchTree, DisplayId, Edges, Effect, Entity, EntityId, EventEmitter,
     FileDropEvent, FontId, Global, GlobalElementId, GlyphId, GpuSpecs, Hsla, InputHandler, IsZero,
-    KeyBinding, KeyContext, KeyDownEvent, KeyEvent, Keystroke, KeystrokeEvent, LayoutId,
-    LineLayoutIndex, Modifiers, ModifiersChangedEvent, MonochromeSprite, MouseButton, MouseEvent,
-    MouseMoveEvent, MouseUpEvent, Path, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput,
-    PlatformInputHandler, PlatformWindow, Point, PolychromeSprite, PromptButton, PromptLevel, Quad,
-    Render, RenderGlyphParams, RenderImage, RenderImageParams, RenderSvgParams, Replay, ResizeEdge,
-    SMOOTH_SVG_SCALE_FACTOR, SUBPIXEL_VARIANTS_X, SUBPIXEL_VARIANTS_Y, ScaledPixels, Scene, Shadow,
-    SharedString, Size, StrikethroughStyle, Style, SubscriberSet, Subscription, SystemWindowTab,
-    SystemWindowTabController, TabStopMap, TaffyLayoutEngine, Task, TextStyle, TextStyleRefinement,
-    TransformationMatrix, Underline, UnderlineStyle, WindowAppearance, WindowBackgroundAppearance,
-    WindowBounds, WindowControls, WindowDecorations, WindowOptions, WindowParams, WindowTextSystem,
-    point, prelude::*, px, rems, size, transparent_black,
+    KeyBinding, KeyContext, KeyDownEvent, KeyEvent, KeyUpEvent, Keystroke, KeystrokeEvent,
+    LayoutId, LineLayoutIndex, Modifiers, ModifiersChangedEvent, MonochromeSprite, MouseButton,
+    MouseDownEvent, MouseEvent, MouseMoveEvent, MouseUpEvent, Path, Pixels, PlatformAtlas,
+    PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow, Point, PolychromeSprite,
+    PromptButton, PromptLevel, Quad, Render, RenderGlyphParams, RenderImage, RenderImageParams,
+    RenderSvgParams, Replay, ResizeEdge, SMOOTH_SVG_SCALE_FACTOR, SUBPIXEL_VARIANTS_X,
+    SUBPIXEL_VARIANTS_Y, ScaledPixels, Scene, Shadow, SharedString, Size, StrikethroughStyle,
+    Style, SubscriberSet, Subscription, SystemWindowTab, SystemWindowTabController, TabStopMap,
+    TaffyLayoutEngine, Task, TextStyle, TextStyleRefinement, TransformationMatrix, Underline,
+    UnderlineStyle, WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControls,
+    WindowDecorations, WindowOptions, WindowParams, WindowTextSystem, WindowVisibility, point,
+    prelude::*, px, rems, size, transparent_black,
 };
 use anyhow::{Context as _, Result, anyhow};
 use collections::{FxHashMap, FxHashSet};
@@ -179,6 +180,20 @@ impl WindowInvalidator {
 
 type AnyObserver = Box<dyn FnMut(&mut Window, &mut App) -> bool + 'static>;
 
+type AnyPresentObserver = Box<dyn FnMut(&PresentInfo) -> bool + 'static>;
+
+/// Describes a frame that a window handed to the compositor, as reported to
+/// [`Window::on_present`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PresentInfo {
+    /// Number of frames the window has presented, including this one
+    pub frame: u64,
+    /// When the platform's present call returned
+    pub presented_at: Instant,
+    /// The display's refresh interval, if the platform reports it
+    pub refresh_interval: Option<Duration>,
+}
+
 pub(crate) type AnyWindowFocusListener =
     Box<dyn FnMut(&WindowFocusEvent, &mut Window, &mut App) -> bool + 'static>;
 
@@ -462,6 +477,8 @@ type FrameCallback = Box<dyn FnOnce(&mut Window, &mut App)>;
 pub(crate) type AnyMouseListener =
     Box<dyn FnMut(&dyn Any, DispatchPhase, &mut Window, &mut App) + 'static>;
 
+pub(crate) type AnyFocusedInputListener = Rc<dyn Fn(&PlatformInput, &mut Window, &mut App)>;
+
 #[derive(Clone)]
 pub(crate) struct CursorStyleRequest {
     pub(crate) hitbox_id: Option<HitboxId>,
@@ -670,6 +687,7 @@ pub(crate) struct Frame {
     pub(crate) element_states: FxHashMap<(GlobalElementId, TypeId), ElementStateBox>,
     accessed_element_states: Vec<(GlobalElementId, TypeId)>,
     pub(crate) mouse_listeners: Vec<Option<AnyMouseListener>>,
+    pub(crate) focused_input_listeners: Vec<(FocusId, AnyFocusedInputListener)>,
     pub(crate) dispatch_tree: DispatchTree,
     pub(crate) scene: Scene,
     pub(crate) hitboxes: Vec<Hitbox>,
@@ -678,6 +696,9 @@ pub(crate) struct Frame {
     pub(crate) input_handlers: Vec<Option<PlatformInputHandler>>,
     pub(crate) tooltip_requests: Vec<Option<TooltipRequest>>,
     pub(crate) cursor_styles: Vec<CursorStyleRequest>,
+    /// One entry per canvas registration, so that reused prepaint ranges include every source
+    /// they displayed. A source shown by several canvases appears more than once.
+    pub(crate) gpu_canvas_sources: Vec<crate::GpuCanvasSource>,
     #[cfg(any(test, feature = "test-support"))]
     pub(crate) debug_bounds: FxHashMap<String, Bounds<Pixels>>,
     #[cfg(any(feature = "inspector", debug_assertions))]
@@ -694,6 +715,7 @@ pub(crate) struct PrepaintStateIndex {
     deferred_draws_index: usize,
     dispatch_tree_index: usize,
     accessed_element_states_index: usize,
+    gpu_canvas_sources_index: usize,
     line_layout_index: LineLayoutIndex,
 }
 
@@ -701,6 +723,7 @@ pub(crate) struct PrepaintStateIndex {
 pub(crate) struct PaintIndex {
     scene_index: usize,
     mouse_listeners_index: usize,
+    focused_input_listeners_index: usize,
     input_handlers_index: usize,
     cursor_styles_index: usize,
     accessed_element_states_index: usize,
@@ -716,6 +739,7 @@ impl Frame {
             element_states: FxHashMap::default(),
             accessed_element_states: Vec::new(),
             mouse_listeners: Vec::new(),
+            focused_input_listeners: Vec::new(),
             dispatch_tree,
             scene: Scene::default(),
             hitboxes: Vec::new(),
@@ -724,6 +748,7 @@ impl Frame {
             input_handlers: Vec::new(),
             tooltip_requests: Vec::new(),
             cursor_styles: Vec::new(),
+            gpu_canvas_sources: Vec::new(),
 
             #[cfg(any(test, feature = "test-support"))]
             debug_bounds: FxHashMap::default(),
@@ -741,11 +766,13 @@ impl Frame {
         self.element_states.clear();
         self.accessed_element_states.clear();
         self.mouse_listeners.clear();
+        self.focused_input_listeners.clear();
         self.dispatch_tree.clear();
         self.scene.clear();
         self.input_handlers.clear();
         self.tooltip_requests.clear();
         self.cursor_styles.clear();
+        self.gpu_canvas_sources.clear();
         self.hitboxes.clear();
         self.window_control_hitboxes.clear();
         self.deferred_draws.clear();
@@ -851,14 +878,24 @@ pub struct Window {
     focus_listeners: SubscriberSet<(), AnyWindowFocusListener>,
     pub(crate) focus_lost_listeners: SubscriberSet<(), AnyObserver>,
     default_prevented: bool,
+    dispatching_synthetic_input: bool,
     mouse_position: Point<Pixels>,
     mouse_hit_test: HitTest,
     modifiers: Modifiers,
     capslock: Capslock,
+    keyboard_layout_id: SharedString,
     scale_factor: f32,
     pub(crate) bounds_observers: SubscriberSet<(), AnyObserver>,
     appearance: WindowAppearance,
     pub(crate) appearance_observers: SubscriberSet<(), AnyObserver>,
+    scale_factor_observers: SubscriberSet<(), AnyObserver>,
+    visibility: WindowVisibility,
+    visibility_observers: SubscriberSet<(), AnyObserver>,
+    presented_frames: Cell<u64>,
+    present_observers: SubscriberSet<(), AnyPresentObserver>,
+    /// Buffers allocated by `create_gpu_canvas_source` whose sources have all been dropped,
+    /// freed after the next present
+    released_gpu_canvas_textures: Arc<parking_lot::Mutex<Vec<crate::GpuTextureHandle>>>,
     active: Rc<Cell<bool>>,
     hovered: Rc<Cell<bool>>,
     pub(crate) needs_present: Rc<Cell<bool>>,
@@ -903,6 +940,54 @@ pub(crate) struct ElementStateBox {
     pub(crate) type_name: &'static str,
 }
 
+/// The platform-specific surface a universal [`GpuTextureHandle`](crate::GpuTextureHandle)
+/// refers to. All platforms share the same byte formats, just different OS handles.
+fn gpu_texture_surface_source(
+    texture_handle: &crate::GpuTextureHandle,
+) -> crate::scene::SurfaceSource {
+    use crate::scene::SurfaceSource;
+
+    #[cfg(target_os = "windows")]
+    let source = SurfaceSource::SharedTexture {
+        nt_handle: texture_handle.native_handle,
+        width: texture_handle.width,
+        height: texture_handle.height,
+        format: texture_handle.format,
+        generation: texture_handle.generation,
+    };
+
+    #[cfg(target_os = "macos")]
+    let source = {
+        // On macOS, native_handle is an IOSurface ID
+        // Create IOSurface from the handle
+        use metal::IOSurface;
+        let io_surface = unsafe {
+            // IOSurface::from_id creates an IOSurface from its integer ID
+            IOSurface::from_id(texture_handle.native_handle as u32)
+        };
+        SurfaceSource::ImageBuffer(io_surface)
+    };
+
+    #[cfg(target_os = "linux")]
+    let source = SurfaceSource::DmaBuf {
+        fd: texture_handle.native_handle as i32,
+        width: texture_handle.width,
+        height: texture_handle.height,
+    };
+
+    source
+}
+
+/// The color space a texture's samples are in once converted to RGB. YUV textures convert to
+/// nonlinear sRGB, whatever their handle declares.
+fn rgb_color_space(texture_handle: &crate::GpuTextureHandle) -> crate::ColorSpace {
+    if texture_handle.format.is_yuv() {
+        crate::ColorSpace::SrgbNonLinear
+    } else {
+        texture_handle.color_space
+    }
+}
+
 fn default_bounds(display_id: Option<DisplayId>, cx: &mut App) -> Bounds<Pixels> {
     const DEFAULT_WINDOW_OFFSET: Point<Pixels> = point(px(0.), px(35.));
 
@@ -945,6 +1030,7 @@ impl Window {
         let mouse_position = platform_window.mouse_position();
         let modifiers = platform_window.modifiers();
         let capslock = platform_window.capslock();
+        let keyboard_layout_id = SharedString::from(cx.keyboard_layout().id().to_string());
         let content_size = platform_window.content_size();
         let scale_factor = platform_window.scale_factor();
         let appearance = platform_window.appearance();
@@ -1078,6 +1164,16 @@ impl Window {
                     .log_err();
             }
         }));
+        platform_window.on_visibility_changed(Box::new({
+            let mut cx = cx.to_async();
+            move |visibility| {
+                handle
+                    .update(&mut cx, |_, window, cx| {
+                        window.visibility_changed(visibility, cx)
+                    })
+                    .log_err();
+            }
+        }));
         platform_window.on_should_close(Box::new({
             let mut cx = cx.to_async();
             move || {
@@ -1121,14 +1217,22 @@ impl Window {
             focus_listeners: SubscriberSet::new(),
             focus_lost_listeners: SubscriberSet::new(),
             default_prevented: true,
+            dispatching_synthetic_input: false,
             mouse_position,
             mouse_hit_test: HitTest::default(),
             modifiers,
             capslock,
+            keyboard_layout_id,
             scale_factor,
             bounds_observers: SubscriberSet::new(),
             appearance,
             appearance_observers: SubscriberSet::new(),
+            scale_factor_observers: SubscriberSet::new(),
+            visibility: WindowVisibility::Visible,
+            visibility_observers: SubscriberSet::new(),
+            presented_frames: Cell::new(0),
+            present_observers: SubscriberSet::new(),
+            released_gpu_canvas_textures: Arc::default(),
             active,
             hovered,
             needs_present,
@@ -1203,6 +1307,7 @@ impl Window {
         let mouse_position = platform_window.mouse_position();
         let modifiers = platform_window.modifiers();
         let capslock = platform_window.capslock();
+        let keyboard_layout_id = SharedString::from(cx.keyboard_layout().id().to_string());
         let content_size = platform_window.content_size();
         let scale_factor = platform_window.scale_factor();
         let appearance = platform_window.appearance();
@@ -1310,6 +1415,16 @@ impl Window {
                     .log_err();
             }
         }));
+        platform_window.on_visibility_changed(Box::new({
+            let mut cx = cx.to_async();
+            move |visibility| {
+                handle
+                    .update(&mut cx, |_, window, cx| {
+                        window.visibility_changed(visibility, cx)
+                    })
+                    .log_err();
+            }
+        }));
         platform_window.on_active_status_change(Box::new({
             let mut cx = cx.to_async();
             move |active| {
@@ -1455,14 +1570,22 @@ impl Window {
             focus_listeners: SubscriberSet::new(),
             focus_lost_listeners: SubscriberSet::new(),
             default_prevented: true,
+            dispatching_synthetic_input: false,
             mouse_position,
             mouse_hit_test: HitTest::default(),
             modifiers,
             capslock,
+            keyboard_layout_id,
             scale_factor,
             bounds_observers: SubscriberSet::new(),
             appearance,
             appearance_observers: SubscriberSet::new(),
+            scale_factor_observers: SubscriberSet::new(),
+            visibility: WindowVisibility::Visible,
+            visibility_observers: SubscriberSet::new(),
+            presented_frames: Cell::new(0),
+            present_observers: SubscriberSet::new(),
+            released_gpu_canvas_textures: Arc::default(),
             active,
             hovered,
             needs_present,
@@ -1558,6 +1681,27 @@ impl Window {
         subscription
     }
 
+    /// Registers a callback to be invoked with the new scale factor when it changes, e.g. when
+    /// the window moves to a display with a different DPI. The window's size in device pixels
+    /// changes with it even if its logical size does not, so producers rendering at device
+    /// resolution should reallocate. Canvases with a
+    /// [`GpuCanvas::on_resize`](crate::GpuCanvas::on_resize) listener are notified through that
+    /// too.
+    pub fn observe_scale_factor(
+        &self,
+        mut callback: impl FnMut(f32, &mut Window, &mut App) + 'static,
+    ) -> Subscription {
+        let (subscription, activate) = self.scale_factor_observers.insert(
+            (),
+            Box::new(move |window, cx| {
+                callback(window.scale_factor(), window, cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
     /// Gets the shared texture handle for zero-copy GPU composition in external window mode.
     ///
     /// Returns a platform-specific handle that can be used to share the GPUI render target
@@ -1576,15 +1720,15 @@ impl Window {
     /// ## Example
     /// ```ignore
     /// match window.get_shared_texture_handle()? {
-    ///     Some(SharedTextureHandle::D3D11NTHandle { handle, size, format }) => {
+    ///     Some(SharedTextureHandle::D3D11NTHandle { handle, size, format, .. }) => {
     ///         // Windows: Open handle in D3D12 device
     ///         d3d12_device.OpenSharedHandle(handle)?;
     ///     }
-    ///     Some(SharedTextureHandle::IOSurface { io_surface, size, format }) => {
+    ///     Some(SharedTextureHandle::IOSurface { io_surface, size, format, .. }) => {
     ///         // macOS: Create Metal texture from IOSurface
     ///         let texture = metal_device.newTextureWithDescriptor(desc, iosurface: io_surface);
     ///     }
-    ///     Some(SharedTextureHandle::DmaBuf { fd, modifier, size, format, stride }) => {
+    ///     Some(SharedTextureHandle::DmaBuf { fd, modifier, size, format, stride, .. }) => {
     ///         // Linux: Import into Vulkan
     ///         vk_device.create_image_from_dmabuf(fd, modifier, ...)?;
     ///     }
@@ -1937,6 +2081,7 @@ impl Window {
     }
 
     fn bounds_changed(&mut self, cx: &mut App) {
+        let previous_scale_factor = self.scale_factor;
         self.scale_factor = self.platform_window.scale_factor();
         self.viewport_size = self.platform_window.content_size();
         self.display_id = self.platform_window.display().map(|display| display.id());
@@ -1946,6 +2091,11 @@ impl Window {
         self.bounds_observers
             .clone()
             .retain(&(), |callback| callback(self, cx));
+        if self.scale_factor != previous_scale_factor {
+            self.scale_factor_observers
+                .clone()
+                .retain(&(), |callback| callback(self, cx));
+        }
     }
 
     /// Returns the bounds of the current window in the global coordinate space, which could span across multiple displays.
@@ -1997,6 +2147,76 @@ impl Window {
         self.appearance
     }
 
+    fn visibility_changed(&mut self, visibility: WindowVisibility, cx: &mut App) {
+        if visibility == self.visibility {
+            return;
+        }
+        self.visibility = visibility;
+        for source in &self.rendered_frame.gpu_canvas_sources {
+            source.set_consumer_active(visibility == WindowVisibility::Visible);
+        }
+        self.visibility_observers
+            .clone()
+            .retain(&(), |callback| callback(self, cx));
+    }
+
+    /// Returns whether the window's contents can currently be seen.
+    pub fn visibility(&self) -> WindowVisibility {
+        self.visibility
+    }
+
+    /// Registers a callback to be invoked when the window is minimized, restored, or becomes
+    /// covered or uncovered by other windows. Producers rendering into a
+    /// [`GpuCanvasSource`](crate::GpuCanvasSource) can pause while the window is not visible;
+    /// [`GpuCanvasSource::consumer_active`](crate::GpuCanvasSource::consumer_active) offers the
+    /// same signal without subscribing.
+    pub fn observe_visibility(
+        &self,
+        mut callback: impl FnMut(WindowVisibility, &mut Window, &mut App) + 'static,
+    ) -> Subscription {
+        let (subscription, activate) = self.visibility_observers.insert(
+            (),
+            Box::new(move |window, cx| {
+                callback(window.visibility, window, cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    /// Registers a callback to be invoked each time the window presents a frame, after the
+    /// platform's present call returns. Producers can use it to pace rendering to the
+    /// compositor rather than to their own buffer swaps;
+    /// [`GpuCanvasSource::latch_presents`](crate::GpuCanvasSource::latch_presents) makes the
+    /// latest [`PresentInfo`] readable from other threads.
+    pub fn on_present(&self, mut callback: impl FnMut(PresentInfo) + 'static) -> Subscription {
+        let (subscription, activate) = self.present_observers.insert(
+            (),
+            Box::new(move |info| {
+                callback(*info);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    /// The gpu canvas sources displayed in the window's most recently drawn frame, each listed
+    /// once however many canvases display it.
+    pub fn gpu_canvas_sources(&self) -> impl Iterator<Item = &crate::GpuCanvasSource> {
+        let sources = &self.rendered_frame.gpu_canvas_sources;
+        sources.iter().enumerate().filter_map(move |(ix, source)| {
+            (!sources[..ix].iter().any(|earlier| earlier.ptr_eq(source))).then_some(source)
+        })
+    }
+
+    /// Records that a gpu canvas displayed `source` in the frame being drawn.
+    pub(crate) fn register_gpu_canvas_source(&mut self, source: &crate::GpuCanvasSource) {
+        self.next_frame.gpu_canvas_sources.push(source.clone());
+        source.set_consumer_active(self.visibility == WindowVisibility::Visible);
+    }
+
     /// Returns the size of the drawable area within the window.
     pub fn viewport_size(&self) -> Size<Pixels> {
         self.viewport_size
@@ -2022,6 +2242,34 @@ impl Window {
         }
     }
 
+    /// Hide the cursor and confine it to `bounds`, reporting relative motion as
+    /// [`MouseMotionEvent`](crate::MouseMotionEvent)s instead of absolute mouse moves. The lock
+    /// is released by [`Window::unlock_pointer`] and whenever the window is deactivated.
+    /// Returns whether the platform supports pointer lock (currently Windows only).
+    pub fn lock_pointer(&self, bounds: Bounds<Pixels>) -> bool {
+        self.platform_window.lock_pointer(bounds)
+    }
+
+    /// Release a pointer lock taken with [`Window::lock_pointer`], showing the cursor again.
+    pub fn unlock_pointer(&self) {
+        self.platform_window.unlock_pointer()
+    }
+
+    /// Returns whether the pointer is currently locked to this window.
+    pub fn is_pointer_locked(&self) -> bool {
+        self.platform_window.is_pointer_locked()
+    }
+
+    /// Start or stop reporting [`RawMouseMotionEvent`](crate::RawMouseMotionEvent)s, the
+    /// unaccelerated deltas a mouse reports at its full polling rate. Regular mouse events keep
+    /// being dispatched alongside them. Only one window per application receives raw input at a
+    /// time: the one that enabled it most recently. When that window disables it or closes, the
+    /// previous window that enabled it receives raw input again. Returns whether the platform
+    /// supports raw mouse input (currently Windows only).
+    pub fn set_raw_mouse_input(&self, enabled: bool) -> bool {
+        self.platform_window.set_raw_mouse_input(enabled)
+    }
+
     /// Toggle zoom on the window.
     pub fn zoom_window(&self) {
         self.platform_window.zoom();
@@ -2189,6 +2437,13 @@ impl Window {
         self.capslock
     }
 
+    /// The platform identifier of the keyboard layout that key events are currently decoded
+    /// with. Updated in order with key events, so key handlers always see the layout their
+    /// event was decoded under.
+    pub fn keyboard_layout_id(&self) -> &SharedString {
+        &self.keyboard_layout_id
+    }
+
     fn complete_frame(&mut self) {
         self.refreshing = false;
         self.invalidator.set_phase(DrawPhase::None);
@@ -2228,6 +2483,16 @@ impl Window {
         let previous_focus_path = self.rendered_frame.focus_path();
         let previous_window_active = self.rendered_frame.window_active;
         mem::swap(&mut self.rendered_frame, &mut self.next_frame);
+        for source in &self.next_frame.gpu_canvas_sources {
+            if !self
+                .rendered_frame
+                .gpu_canvas_sources
+                .iter()
+                .any(|displayed| displayed.ptr_eq(source))
+            {
+                source.set_consumer_active(false);
+            }
+        }
         self.next_frame.clear();
         let current_focus_path = self.rendered_frame.focus_path();
         let current_window_active = self.rendered_frame.window_active;
@@ -2294,7 +2559,27 @@ impl Window {
     #[profiling::function]
     pub(crate) fn present(&self) {
         self.platform_window.draw(&self.rendered_frame.scene);
+        let presented_at = Instant::now();
         self.needs_present.set(false);
+
+        // The frame just drawn only shows sources that are still alive, so nothing samples
+        // these anymore.
+        let released_textures = mem::take(&mut *self.released_gpu_canvas_textures.lock());
+        if !released_textures.is_empty() {
+            self.platform_window
+                .release_shared_textures(&released_textures);
+        }
+
+        let frame = self.presented_frames.get() + 1;
+        self.presented_frames.set(frame);
+        let mut info = None;
+        self.present_observers.clone().retain(&(), |callback| {
+            callback(info.get_or_insert_with(|| PresentInfo {
+                frame,
+                presented_at,
+                refresh_interval: self.platform_window.refresh_interval(),
+            }))
+        });
         profiling::finish_frame!();
     }
 
@@ -2513,6 +2798,7 @@ impl Window {
             deferred_draws_index: self.next_frame.deferred_draws.len(),
             dispatch_tree_index: self.next_frame.dispatch_tree.len(),
             accessed_element_states_index: self.next_frame.accessed_element_states.len(),
+            gpu_canvas_sources_index: self.next_frame.gpu_canvas_sources.len(),
             line_layout_index: self.text_system.layout_index(),
         }
     }
@@ -2535,6 +2821,12 @@ impl Window {
                 .iter()
                 .map(|(id, type_id)| (GlobalElementId(id.0.clone()), *type_id)),
         );
+        self.next_frame.gpu_canvas_sources.extend(
+            self.rendered_frame.gpu_canvas_sources
+                [range.start.gpu_canvas_sources_index..range.end.gpu_canvas_sources_index]
+                .iter()
+                .cloned(),
+        );
         self.text_system
             .reuse_layouts(range.start.line_layout_index..range.end.line_layout_index);
 
@@ -2570,6 +2862,7 @@ impl Window {
         PaintIndex {
             scene_index: self.next_frame.scene.len(),
             mouse_listeners_index: self.next_frame.mouse_listeners.len(),
+            focused_input_listeners_index: self.next_frame.focused_input_listeners.len(),
             input_handlers_index: self.next_frame.input_handlers.len(),
             cursor_styles_index: self.next_frame.cursor_styles.len(),
             accessed_element_states_index: self.next_frame.accessed_element_states.len(),
@@ -2597,6 +2890,12 @@ impl Window {
                 .iter_mut()
                 .map(|listener| listener.take()),
         );
+        self.next_frame.focused_input_listeners.extend(
+            self.rendered_frame.focused_input_listeners[range.start.focused_input_listeners_index
+                ..range.end.focused_input_listeners_index]
+                .iter()
+                .cloned(),
+        );
         self.next_frame.accessed_element_states.extend(
             self.rendered_frame.accessed_element_states[range.start.accessed_element_states_index
                 ..range.end.accessed_element_states_index]
@@ -2759,6 +3058,9 @@ impl Window {
             self.next_frame
                 .accessed_element_states
                 .truncate(index.accessed_element_states_index);
+            self.next_frame
+                .gpu_canvas_sources
+                .truncate(index.gpu_canvas_sources_index);
             self.text_system.truncate_layouts(index.line_layout_index);
         }
         result
@@ -3464,54 +3766,124 @@ impl Window {
 
     /// Paint a GPU shared texture (zero-copy from external renderer like Bevy).
     /// NO allocations - just passes the handle to the renderer.
+    ///
+    /// The texture is fitted within `bounds` and clipped to the rounded rectangle described by
+    /// `bounds` and `corner_radii`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            skip_all,
+            fields(texture = texture_handle.native_handle)
+        )
+    )]
     pub fn paint_gpu_texture(
         &mut self,
         bounds: Bounds<Pixels>,
+        corner_radii: Corners<Pixels>,
+        texture_handle: crate::GpuTextureHandle,
+        options: &crate::GpuTextureOptions,
+    ) {
+        self.paint_gpu_texture_with_transition(bounds, corner_radii, texture_handle, None, options);
+    }
+
+    /// Paint a GPU shared texture like [`Window::paint_gpu_texture`], crossfading to it from
+    /// `from`. Both textures are laid out with the same `options` and blended in one draw, so
+    /// that the result is correct where either is transparent. `progress` runs from 0.0, showing
+    /// only `from`, to 1.0, showing only `texture_handle`. Currently drawn by the DirectX
+    /// renderer only.
+    pub fn paint_gpu_texture_transition(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        corner_radii: Corners<Pixels>,
+        from: crate::GpuTextureHandle,
+        texture_handle: crate::GpuTextureHandle,
+        progress: f32,
+        options: &crate::GpuTextureOptions,
+    ) {
+        self.paint_gpu_texture_with_transition(
+            bounds,
+            corner_radii,
+            texture_handle,
+            Some((from, progress)),
+            options,
+        );
+    }
+
+    fn paint_gpu_texture_with_transition(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        corner_radii: Corners<Pixels>,
         texture_handle: crate::GpuTextureHandle,
-        object_fit: crate::ObjectFit,
+        transition: Option<(crate::GpuTextureHandle, f32)>,
+        options: &crate::GpuTextureOptions,
     ) {
         use crate::PaintSurface;
-        use crate::scene::SurfaceSource;
+        use crate::scene::SurfaceTransition;
         
         self.invalidator.debug_assert_paint();
         
+        let opacity = crate::gpu_texture_opacity(options, self.element_opacity());
+        if texture_handle.is_empty() || opacity <= 0. {
+            return;
+        }
+
         let scale_factor = self.scale_factor();
         let bounds = bounds.scale(scale_factor);
+        if bounds.is_empty() {
+            return;
+        }
+        let source_rect =
+            crate::clamp_source_rect(options.source_rect, texture_handle.size());
+        if source_rect.is_empty() {
+            return;
+        }
+        let source_rect = crate::source_rect_in_storage(
+            source_rect,
+            texture_handle.size(),
+            texture_handle.origin,
+        );
+        let flip_y = options.flip_y != texture_handle.is_bottom_up();
         let content_mask = self.content_mask().scale(scale_factor);
+        let corner_radii = corner_radii.scale(scale_factor);
         
-        // Convert universal GpuTextureHandle to platform-specific SurfaceSource
-        // All platforms use the same RGBA8 byte format - just different OS handles
-        #[cfg(target_os = "windows")]
-        let source = SurfaceSource::SharedTexture {
-            nt_handle: texture_handle.native_handle,
-            width: texture_handle.width,
-            height: texture_handle.height,
-        };
-
-        #[cfg(target_os = "macos")]
-        let source = {
-            // On macOS, native_handle is an IOSurface ID
-            // Create IOSurface from the handle
-            use metal::IOSurface;
-            let io_surface = unsafe {
-                // IOSurface::from_id creates an IOSurface from its integer ID
-                IOSurface::from_id(texture_handle.native_handle as u32)
-            };
-            SurfaceSource::ImageBuffer(io_surface)
-        };
+        let source = gpu_texture_surface_source(&texture_handle);
+        let transition = transition.and_then(|(from, progress)| {
+            let source_rect = crate::clamp_source_rect(options.source_rect, from.size());
+            (!from.is_empty() && !source_rect.is_empty()).then(|| SurfaceTransition {
+                source: gpu_texture_surface_source(&from),
+                source_rect: crate::source_rect_in_storage(source_rect, from.size(), from.origin),
+                flip_y: options.flip_y != from.is_bottom_up(),
+                color_space: rgb_color_space(&from),
+                progress: progress.clamp(0., 1.),
+            })
+        });
 
-        #[cfg(target_os = "linux")]
-        let source = SurfaceSource::DmaBuf {
-            fd: texture_handle.native_handle as i32,
-            width: texture_handle.width,
-            height: texture_handle.height,
-        };
-        
         self.next_frame.scene.insert_primitive(PaintSurface {
             order: 0,
             bounds,
             content_mask,
-            object_fit,
+            corner_radii,
+            object_fit: options.object_fit,
+            filter: options.filter,
+            alpha_mode: options.alpha_mode,
+            source_rect,
+            flip_y,
+            rotation: options.rotation,
+            pixel_perfect: options.pixel_perfect,
+            opacity,
+            grayscale: options.grayscale,
+            tint: options.tint,
+            yuv_conversion: texture_handle
+                .format
+                .is_yuv()
+                .then_some(texture_handle.yuv_conversion),
+            mask_color: options.mask_color.or_else(|| {
+                (texture_handle.format == crate::GpuTextureFormat::R8).then(crate::white)
+            }),
+            color_space: rgb_color_space(&texture_handle),
+            tone_mapping: options.tone_mapping,
+            transition,
             source,
         });
     }
@@ -3550,6 +3922,13 @@ impl Window {
         Ok(())
     }
 
+    /// Removes every rasterized glyph from the sprite atlas, e.g. after the UI font changed and
+    /// the old glyphs won't be drawn again. Glyphs that are still needed are rasterized again on
+    /// their next paint.
+    pub fn drop_glyphs(&mut self) {
+        self.sprite_atlas.remove_matching(&|key| matches!(key, crate::AtlasKey::Glyph(_)));
+    }
+
     /// Add a node to the layout tree for the current frame. Takes the `Style` of the element for which
     /// layout is being requested, along with the layout ids of any children. This method is called during
     /// calls to the [`Element::request_layout`] trait method and enables any element to participate in layout.
@@ -3798,6 +4177,24 @@ impl Window {
         ));
     }
 
+    /// Register a listener that receives raw keyboard input while the given focus handle is
+    /// focused, before the keymap is consulted. Call `cx.stop_propagation()` from the listener
+    /// to consume the event; otherwise it continues through normal key dispatch. Key down, key
+    /// up and modifiers changed events are delivered.
+    ///
+    /// This method should only be called as part of the paint phase of element drawing.
+    pub fn on_focused_input(
+        &mut self,
+        focus_handle: &FocusHandle,
+        listener: impl Fn(&PlatformInput, &mut Window, &mut App) + 'static,
+    ) {
+        self.invalidator.debug_assert_paint();
+
+        self.next_frame
+            .focused_input_listeners
+            .push((focus_handle.id, Rc::new(listener)));
+    }
+
     /// Register a modifiers changed event listener on the window for the next frame.
     ///
     /// This is a fairly low-level method, so prefer using event handlers on elements unless you have
@@ -3903,6 +4300,129 @@ impl Window {
         false
     }
 
+    /// Dispatch `event` as though the platform had delivered it, going through the same dispatch
+    /// path as real input. Listeners can tell it apart with [`Window::is_synthetic_input`], for
+    /// example to keep injected events out of latency measurements. Works on any window, not just
+    /// those of the test platform, so it can drive integration tests of a running app.
+    pub fn simulate_platform_input(
+        &mut self,
+        event: PlatformInput,
+        cx: &mut App,
+    ) -> DispatchEventResult {
+        let was_synthetic = mem::replace(&mut self.dispatching_synthetic_input, true);
+        let result = self.dispatch_event(event, cx);
+        self.dispatching_synthetic_input = was_synthetic;
+        result
+    }
+
+    /// Returns whether the event being dispatched was injected with
+    /// [`Window::simulate_platform_input`] or one of the helpers built on it.
+    pub fn is_synthetic_input(&self) -> bool {
+        self.dispatching_synthetic_input
+    }
+
+    /// Simulate moving the mouse to `position` and clicking `button` there.
+    pub fn simulate_click(
+        &mut self,
+        position: Point<Pixels>,
+        button: MouseButton,
+        modifiers: Modifiers,
+        cx: &mut App,
+    ) {
+        self.simulate_platform_input(
+            PlatformInput::MouseMove(MouseMoveEvent {
+                position,
+                pressed_button: None,
+                modifiers,
+            }),
+            cx,
+        );
+        self.simulate_platform_input(
+            PlatformInput::MouseDown(MouseDownEvent {
+                button,
+                position,
+                modifiers,
+                click_count: 1,
+                first_mouse: false,
+            }),
+            cx,
+        );
+        self.simulate_platform_input(
+            PlatformInput::MouseUp(MouseUpEvent {
+                button,
+                position,
+                modifiers,
+                click_count: 1,
+            }),
+            cx,
+        );
+    }
+
+    /// Simulate dragging with the left button from `from` to `to`, reporting `steps` evenly spaced
+    /// mouse moves in between.
+    pub fn simulate_drag(
+        &mut self,
+        from: Point<Pixels>,
+        to: Point<Pixels>,
+        steps: usize,
+        cx: &mut App,
+    ) {
+        let modifiers = Modifiers::default();
+        self.simulate_platform_input(
+            PlatformInput::MouseMove(MouseMoveEvent {
+                position: from,
+                pressed_button: None,
+                modifiers,
+            }),
+            cx,
+        );
+        self.simulate_platform_input(
+            PlatformInput::MouseDown(MouseDownEvent {
+                button: MouseButton::Left,
+                position: from,
+                modifiers,
+                click_count: 1,
+                first_mouse: false,
+            }),
+            cx,
+        );
+        let steps = steps.max(1);
+        for step in 1..=steps {
+            let t = step as f32 / steps as f32;
+            let position = point(from.x + (to.x - from.x) * t, from.y + (to.y - from.y) * t);
+            self.simulate_platform_input(
+                PlatformInput::MouseMove(MouseMoveEvent {
+                    position,
+                    pressed_button: Some(MouseButton::Left),
+                    modifiers,
+                }),
+                cx,
+            );
+        }
+        self.simulate_platform_input(
+            PlatformInput::MouseUp(MouseUpEvent {
+                button: MouseButton::Left,
+                position: to,
+                modifiers,
+                click_count: 1,
+            }),
+            cx,
+        );
+    }
+
+    /// Simulate typing `text`, one keystroke per character.
+    pub fn simulate_text(&mut self, text: &str, cx: &mut App) {
+        let was_synthetic = mem::replace(&mut self.dispatching_synthetic_input, true);
+        for keystroke in text
+            .split("")
+            .filter(|key| !key.is_empty())
+            .filter_map(|key| Keystroke::parse(key).log_err())
+        {
+            self.dispatch_keystroke(keystroke, cx);
+        }
+        self.dispatching_synthetic_input = was_synthetic;
+    }
+
     /// Return a key binding string for an action, to display in the UI. Uses the highest precedence
     /// binding for the action (last binding added to the keymap).
     pub fn keystroke_text_for(&self, action: &dyn Action) -> String {
@@ -3935,6 +4455,15 @@ impl Window {
                 self.modifiers = mouse_move.modifiers;
                 PlatformInput::MouseMove(mouse_move)
             }
+            PlatformInput::MouseMotion(mouse_motion) => {
+                self.mouse_position = mouse_motion.position;
+                self.modifiers = mouse_motion.modifiers;
+                PlatformInput::MouseMotion(mouse_motion)
+            }
+            PlatformInput::RawMouseMotion(raw_motion) => {
+                self.modifiers = raw_motion.modifiers;
+                PlatformInput::RawMouseMotion(raw_motion)
+            }
             PlatformInput::MouseDown(mouse_down) => {
                 self.mouse_position = mouse_down.position;
                 self.modifiers = mouse_down.modifiers;
@@ -3954,6 +4483,11 @@ impl Window {
                 self.capslock = modifiers_changed.capslock;
                 PlatformInput::ModifiersChanged(modifiers_changed)
             }
+            PlatformInput::KeyboardLayoutChanged(layout_changed) => {
+                self.keyboard_layout_id = layout_changed.layout_id.clone();
+                self.refresh();
+                PlatformInput::KeyboardLayoutChanged(layout_changed)
+            }
             PlatformInput::ScrollWheel(scroll_wheel) => {
                 self.mouse_position = scroll_wheel.position;
                 self.modifiers = scroll_wheel.modifiers;
@@ -4074,6 +4608,11 @@ impl Window {
             self.draw(cx).clear();
         }
 
+        self.dispatch_focused_input(event, cx);
+        if !cx.propagate_event {
+            return;
+        }
+
         let node_id = self.focus_node_id_in_rendered_frame(self.focus);
         let dispatch_path = self.rendered_frame.dispatch_tree.dispatch_path(node_id);
 
@@ -4191,6 +4730,38 @@ impl Window {
         self.pending_input_changed(cx);
     }
 
+    fn dispatch_focused_input(&mut self, event: &dyn Any, cx: &mut App) {
+        let Some(focus) = self.focus else {
+            return;
+        };
+        let listeners = self
+            .rendered_frame
+            .focused_input_listeners
+            .iter()
+            .filter(|(focus_id, _)| *focus_id == focus)
+            .map(|(_, listener)| listener.clone())
+            .collect::<SmallVec<[_; 1]>>();
+        if listeners.is_empty() {
+            return;
+        }
+
+        let input = if let Some(event) = event.downcast_ref::<KeyDownEvent>() {
+            PlatformInput::KeyDown(event.clone())
+        } else if let Some(event) = event.downcast_ref::<KeyUpEvent>() {
+            PlatformInput::KeyUp(event.clone())
+        } else if let Some(event) = event.downcast_ref::<ModifiersChangedEvent>() {
+            PlatformInput::ModifiersChanged(event.clone())
+        } else {
+            return;
+        };
+        for listener in listeners {
+            listener(&input, self, cx);
+            if !cx.propagate_event {
+                return;
+            }
+        }
+    }
+
     fn finish_dispatch_key_event(
         &mut self,
         event: &dyn Any,
@@ -4720,6 +5291,197 @@ impl Window {
         self.platform_window.gpu_specs()
     }
 
+    /// Query which [`GpuTextureFormat`](crate::GpuTextureFormat)s this window's GPU device
+    /// supports, so producers can pick a frame format at startup. Formats the device cannot
+    /// sample are reported with `sample: false`. Returns an empty list on platforms that cannot
+    /// paint GPU textures yet (currently everything but Windows).
+    pub fn gpu_texture_format_support(&self) -> Vec<crate::GpuTextureFormatSupport> {
+        self.platform_window.gpu_texture_format_support()
+    }
+
+    /// Create a [`GpuCanvasSource`](crate::GpuCanvasSource) whose two buffers are allocated by
+    /// this window's renderer, ready to pass to [`gpu_canvas`](crate::gpu_canvas). The producer
+    /// opens the buffers' native handles on its own device and renders into them. The textures
+    /// are released once every clone of the source has been dropped, after the window next
+    /// presents, or when the window closes if that comes first. Currently Windows only.
+    pub fn create_gpu_canvas_source(
+        &self,
+        size: Size<DevicePixels>,
+        format: crate::GpuTextureFormat,
+    ) -> Result<crate::GpuCanvasSource> {
+        let front = self.platform_window.create_shared_texture(size, format)?;
+        let back = self
+            .platform_window
+            .create_shared_texture(size, format)
+            .inspect_err(|_| {
+                self.platform_window
+                    .release_shared_textures(std::slice::from_ref(&front))
+            })?;
+        let source = crate::GpuCanvasSource::new(front.clone(), back.clone());
+        let released_textures = self.released_gpu_canvas_textures.clone();
+        source.on_release(move || released_textures.lock().extend([front, back]));
+        Ok(source)
+    }
+
+    /// The buffers this window's renderer allocated with
+    /// [`Window::create_gpu_canvas_source`], each describing its size and format. A source's
+    /// buffers stay listed until every clone of it has been dropped and the window has presented
+    /// since, so the list also helps hunting leaked sources.
+    pub fn gpu_canvas_textures(&self) -> Vec<crate::GpuTextureHandle> {
+        self.platform_window.shared_textures()
+    }
+
+    /// The buffer allocated by [`Window::create_gpu_canvas_source`] behind `native_handle`, if
+    /// this window's renderer allocated it.
+    pub fn gpu_canvas_texture(&self, native_handle: isize) -> Option<crate::GpuTextureHandle> {
+        self.gpu_canvas_textures()
+            .into_iter()
+            .find(|texture| texture.native_handle == native_handle)
+    }
+
+    /// The color space this window outputs, currently always SDR sRGB. Producers of linear
+    /// textures rely on [`GpuCanvas::tone_mapping`](crate::GpuCanvas::tone_mapping) or map their
+    /// output to SDR themselves. Textures are converted from the
+    /// [`ColorSpace`](crate::ColorSpace) their handle declares.
+    pub fn output_color_space(&self) -> crate::OutputColorSpace {
+        self.platform_window.output_color_space()
+    }
+
+    /// Forget what the renderer imported for the texture behind `texture`'s handle, for producers
+    /// that destroyed it. Imports are otherwise dropped once the texture goes undrawn for a few
+    /// seconds' worth of frames, or when a texture of another size, format or
+    /// [generation](crate::GpuTextureHandle::generation) is drawn behind the same handle value.
+    /// Invalidating frees the texture right away and keeps a later texture that the OS gives the
+    /// same handle value from being confused with it.
+    pub fn invalidate_gpu_texture(&self, texture: &crate::GpuTextureHandle) {
+        self.platform_window
+            .invalidate_shared_texture(texture.native_handle);
+    }
+
+    /// Start or stop measuring the GPU time spent drawing and writing gpu textures, reported by
+    /// [`Window::gpu_texture_timings`]. Off by default, when measuring costs nothing; stopping
+    /// discards the measurements. Currently Windows only.
+    pub fn set_gpu_timing_enabled(&self, enabled: bool) {
+        self.platform_window.set_gpu_timing_enabled(enabled);
+    }
+
+    /// The GPU time measured for the texture behind `native_handle` since
+    /// [`Window::set_gpu_timing_enabled`] turned timing on, or `None` if nothing was measured.
+    /// Measurements arrive a few frames after the work they time, and frames during which the
+    /// GPU clock was unreliable are left out rather than reported wrong. A
+    /// [`GpuCanvasSource`](crate::GpuCanvasSource) alternates between two buffers, so its time is
+    /// split between the handles of its front and back buffer.
+    pub fn gpu_texture_timings(&self, native_handle: isize) -> Option<crate::GpuTextureTimings> {
+        self.platform_window.gpu_texture_timings(native_handle)
+    }
+
+    /// Read back what the window draws within `bounds` on its next frame, after object fit,
+    /// cropping, rounded corners and everything painted over it, e.g. for golden-image tests.
+    /// The region is copied on the GPU and read once the copy completed, so the task resolves
+    /// a frame or two later without stalling rendering. Parts of `bounds` outside the window are
+    /// left out of the image. Currently Windows only.
+    pub fn capture_element_bounds(
+        &self,
+        bounds: Bounds<Pixels>,
+        cx: &App,
+    ) -> Task<Result<image::RgbaImage>> {
+        let capture = self
+            .platform_window
+            .capture_bounds(bounds.to_device_pixels(self.scale_factor()));
+        self.spawn(cx, async move |_| {
+            capture
+                .await
+                .map_err(|_| anyhow!("the window closed before the capture completed"))?
+        })
+    }
+
+    /// Upload CPU-drawn pixels into `region` of the back buffer of `source`, for canvases simple
+    /// enough not to need a GPU producer. `data` holds the region's rows in the buffer's format,
+    /// each starting `src_stride` bytes after the previous one.
+    ///
+    /// Writes made between presents are batched into the next frame, which
+    /// [`GpuCanvasSource::swap_buffers`](crate::GpuCanvasSource::swap_buffers) displays. The
+    /// first write after a present starts from a copy of the displayed frame, so writing only
+    /// the regions that changed is enough. Currently Windows only.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            skip_all,
+            fields(
+                texture = source.back_buffer().native_handle,
+                content_version = source.content_version(),
+                ?region,
+            )
+        )
+    )]
+    pub fn write_gpu_canvas_pixels(
+        &self,
+        source: &crate::GpuCanvasSource,
+        region: Bounds<DevicePixels>,
+        data: &[u8],
+        src_stride: usize,
+    ) -> Result<()> {
+        let texture = source.back_buffer();
+        texture.validate_pixel_write(region, data.len(), src_stride)?;
+        let version = source.content_version();
+        let copy_from = source
+            .back_buffer_needs_sync()
+            .then(|| source.active_buffer());
+        self.platform_window
+            .write_shared_texture(texture, copy_from, region, data, src_stride)?;
+        source.mark_back_buffer_synced(version);
+        Ok(())
+    }
+
+    /// Upload a whole frame of `format` pixels into the back buffer of `source`, converting
+    /// them to the buffer's format. Converting between RGBA8 and BGRA8 is supported; other
+    /// formats must match the buffer's. Rows start `src_stride` bytes apart. See
+    /// [`Window::write_gpu_canvas_pixels`] for when the frame is displayed.
+    pub fn write_gpu_canvas_frame(
+        &self,
+        source: &crate::GpuCanvasSource,
+        format: crate::GpuTextureFormat,
+        data: &[u8],
+        src_stride: usize,
+    ) -> Result<()> {
+        let texture = source.back_buffer();
+        let (data, stride) = crate::convert_pixel_rows(
+            data,
+            src_stride,
+            texture.width as usize,
+            texture.height as usize,
+            format,
+            texture.format,
+        )?;
+        let region = Bounds::new(Point::default(), texture.size());
+        self.write_gpu_canvas_pixels(source, region, &data, stride)
+    }
+
+    /// Upload `image` as the next frame of `source`, e.g. a decoded thumbnail or camera frame.
+    /// The source's buffers must be the image's size; create a new source when it changes.
+    pub fn write_gpu_canvas_image(
+        &self,
+        source: &crate::GpuCanvasSource,
+        image: &image::RgbaImage,
+    ) -> Result<()> {
+        let texture = source.back_buffer();
+        anyhow::ensure!(
+            image.dimensions() == (texture.width, texture.height),
+            "a {}x{} image does not fit {}x{} canvas buffers",
+            image.width(),
+            image.height(),
+            texture.width,
+            texture.height
+        );
+        self.write_gpu_canvas_frame(
+            source,
+            crate::GpuTextureFormat::RGBA8,
+            image.as_raw(),
+            image.width() as usize * 4,
+        )
+    }
+
     /// Perform titlebar double-click action.
     /// This is macOS specific.
     pub fn titlebar_double_click(&self) {
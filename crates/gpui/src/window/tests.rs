@@ -2213,6 +2213,62 @@
         assert_eq!(click_count.get(), 2, "click listener should fire after resize");
     }
 
+    struct SyntheticClickView {
+        clicks: Rc<RefCell<Vec<bool>>>,
+    }
+
+    impl Render for SyntheticClickView {
+        fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+            let clicks = self.clicks.clone();
+            div()
+                .id("clickable")
+                .size(px(100.))
+                .on_click(move |_, window, _| {
+                    clicks.borrow_mut().push(window.is_synthetic_input());
+                })
+        }
+    }
+
+    #[gpui::test]
+    fn test_simulated_click_is_marked_synthetic(cx: &mut TestAppContext) {
+        let clicks = Rc::new(RefCell::new(Vec::new()));
+        let (_view, cx) = cx.add_window_view(|_, _| SyntheticClickView {
+            clicks: clicks.clone(),
+        });
+        cx.update(|window, cx| {
+            window.draw(cx);
+        });
+
+        let click_pos = point(px(50.), px(50.));
+        cx.update(|window, cx| {
+            window.simulate_click(click_pos, MouseButton::Left, Modifiers::default(), cx);
+            assert!(!window.is_synthetic_input());
+        });
+        cx.update(|window, cx| {
+            window.dispatch_event(
+                PlatformInput::MouseDown(MouseDownEvent {
+                    position: click_pos,
+                    button: MouseButton::Left,
+                    modifiers: Modifiers::default(),
+                    click_count: 1,
+                    first_mouse: false,
+                }),
+                cx,
+            );
+            window.dispatch_event(
+                PlatformInput::MouseUp(MouseUpEvent {
+                    position: click_pos,
+                    button: MouseButton::Left,
+                    modifiers: Modifiers::default(),
+                    click_count: 1,
+                }),
+                cx,
+            );
+        });
+
+        assert_eq!(*clicks.borrow(), vec![true, false]);
+    }
+
     #[gpui::test]
     fn test_listeners_work_after_cached_frame(cx: &mut TestAppContext) {
         // EXPECTED BEHAVIOR: After a frame where rendering is cached (no changes),